@@ -187,14 +187,7 @@ impl Cache {
     pub async fn get(&self, file: FileRequirement) -> miette::Result<Option<Package>> {
         let entry: Entry = file.into();
 
-        let file = self.path().join(entry.filename());
-
-        let tgz = tokio::fs::read(&file)
-            .await
-            .into_diagnostic()
-            .map(bytes::Bytes::from);
-
-        if let Ok(tgz) = tgz {
+        if let Some(tgz) = self.read(&entry).await {
             let pkg = Package::parse(tgz)?;
 
             return Ok(Some(pkg));
@@ -203,6 +196,13 @@ impl Cache {
         Ok(None)
     }
 
+    /// Read the archive of a cache entry without parsing it
+    pub async fn read(&self, entry: &Entry) -> Option<Bytes> {
+        let file = self.path().join(entry.filename());
+
+        tokio::fs::read(&file).await.ok().map(Bytes::from)
+    }
+
     /// Put a locked package in the cache
     pub async fn put(&self, entry: Entry, bytes: Bytes) -> miette::Result<()> {
         let file = self.path().join(entry.filename());
@@ -230,6 +230,19 @@ impl Cache {
 pub struct Entry(PathBuf);
 
 impl Entry {
+    /// Locates the archive of a package with a given digest
+    pub fn new(package: &PackageName, digest: &Digest) -> Self {
+        Self(
+            format!(
+                "{}.{}.{}.tgz",
+                package,
+                digest.algorithm(),
+                hex::encode(digest.as_bytes())
+            )
+            .into(),
+        )
+    }
+
     /// The filename of the cache entry
     pub fn filename(&self) -> &Path {
         self.0.as_path()
@@ -244,16 +257,7 @@ impl From<Package> for Entry {
 
 impl From<&Package> for Entry {
     fn from(value: &Package) -> Self {
-        let digest = value.digest(DigestAlgorithm::SHA256);
-        Self(
-            format!(
-                "{}.{}.{}.tgz",
-                value.name(),
-                digest.algorithm(),
-                hex::encode(digest.as_bytes())
-            )
-            .into(),
-        )
+        Self::new(value.name(), &value.digest(DigestAlgorithm::SHA256))
     }
 }
 
@@ -265,14 +269,6 @@ impl From<FileRequirement> for Entry {
 
 impl From<&FileRequirement> for Entry {
     fn from(req: &FileRequirement) -> Entry {
-        Self(
-            format!(
-                "{}.{}.{}.tgz",
-                req.package,
-                req.digest.algorithm(),
-                hex::encode(req.digest.as_bytes())
-            )
-            .into(),
-        )
+        Self::new(&req.package, &req.digest)
     }
 }
@@ -13,7 +13,9 @@
 // limitations under the License.
 
 use std::{
+    collections::{BTreeMap, BTreeSet},
     env,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -26,16 +28,21 @@ use tokio::{
 };
 
 use crate::{
+    cache::{Cache, Entry as CacheEntry},
     credentials::Credentials,
     io::File,
+    lock::{LOCKFILE, Lockfile},
     manifest::{
         MANIFEST_FILE, Manifest,
         package::{Dependency, PackageManifest, PackagesManifest},
     },
+    operations::info::PackageInfo,
     operations::install::{Install, InstallationContext, NetworkMode},
     operations::publish::Publisher,
+    operations::tree::{DependencyTree, ExportedGraph, GraphFormat, TreeOptions},
     package::{PackageName, PackageStore, PackageType},
-    registry::{Artifactory, RegistryUri},
+    registry::{self, Artifactory, RegistryKind, RegistryUri},
+    resolver::{DEFAULT_CONCURRENCY, DependencyGraph},
 };
 
 const INITIAL_VERSION: Version = Version::new(0, 1, 0);
@@ -198,11 +205,10 @@ pub async fn add(registry: RegistryUri, dependency: &str) -> miette::Result<()>
     let version = match version {
         DependencyLocatorVersion::Version(version_req) => version_req,
         DependencyLocatorVersion::Latest => {
-            // query artifactory to retrieve the actual latest version
+            // query the registry to retrieve the actual latest version
             let credentials = Credentials::load().await?;
-            let artifactory = Artifactory::new(registry.clone(), &credentials)?;
 
-            let latest_version = artifactory
+            let latest_version = registry::connect(&registry, &credentials)?
                 .get_latest_version(repository.clone(), package.clone())
                 .await?;
             // Convert semver::Version to semver::VersionReq. It will default to operator `>`, which is what we want for Proto.toml
@@ -314,16 +320,282 @@ pub async fn publish(
 ///
 /// * `preserve_mtime` - If true, local dependencies preserve their modification time
 /// * `network_mode` - Controls whether network requests are allowed
-pub async fn install(preserve_mtime: bool, network_mode: NetworkMode) -> miette::Result<()> {
+/// * `concurrency` - Maximum number of remote packages fetched at a time
+/// * `locked` - If true, fails instead of modifying the lockfile
+pub async fn install(
+    preserve_mtime: bool,
+    network_mode: NetworkMode,
+    concurrency: NonZeroUsize,
+    locked: bool,
+) -> miette::Result<()> {
     let manifest = Manifest::load().await?;
 
-    let ctx = InstallationContext::cwd(preserve_mtime, network_mode).await?;
+    let ctx = InstallationContext::cwd(preserve_mtime, network_mode)
+        .await?
+        .with_concurrency(concurrency)
+        .with_locked(locked);
 
     manifest.install(&ctx).await?;
 
     Ok(())
 }
 
+/// Updates locked dependencies to the newest versions allowed by the manifest
+///
+/// # Arguments
+///
+/// * `packages` - Packages to update; all locked packages are updated if empty
+/// * `dry_run` - If true, the changes are printed without modifying the lockfile
+/// * `preserve_mtime` - If true, local dependencies preserve their modification time
+/// * `concurrency` - Maximum number of remote packages fetched at a time
+pub async fn update(
+    packages: Vec<PackageName>,
+    dry_run: bool,
+    preserve_mtime: bool,
+    concurrency: NonZeroUsize,
+) -> miette::Result<()> {
+    let cwd = env::current_dir().into_diagnostic()?;
+    let manifest = Manifest::load().await?;
+    let lock = Lockfile::read_from_or_infer(&cwd).await?;
+
+    for package in &packages {
+        ensure!(
+            lock.packages().any(|locked| &locked.name == package),
+            "package {package} is not locked in {LOCKFILE}"
+        );
+    }
+
+    let packages = if packages.is_empty() {
+        lock.packages().map(|locked| locked.name.clone()).collect()
+    } else {
+        packages
+    };
+
+    let unlocked = lock.without(&packages);
+
+    // Dry runs resolve like a real update, but leave the package store and lockfile untouched
+    let ctx = InstallationContext::new(&cwd, preserve_mtime, NetworkMode::Online)
+        .await?
+        .with_lock(unlocked)
+        .with_concurrency(concurrency)
+        .with_dry_run(dry_run);
+
+    let updated: BTreeSet<(PackageName, Version)> = manifest
+        .install(&ctx)
+        .await?
+        .remote
+        .into_iter()
+        .map(|locked| (locked.name, locked.version))
+        .collect();
+
+    let locked: BTreeSet<(PackageName, Version)> = lock
+        .packages()
+        .map(|locked| (locked.name.clone(), locked.version.clone()))
+        .collect();
+
+    print_update_summary(&locked, &updated);
+
+    if dry_run {
+        tracing::info!("dry run: {LOCKFILE} was not modified");
+    }
+
+    Ok(())
+}
+
+/// Resolves the dependency graph of every package in the current project without installing it
+///
+/// Returns one entry per workspace member, or a single entry for a package manifest.
+async fn resolve_graphs(
+    manifest: &Manifest,
+    cwd: &Path,
+    lock: Lockfile,
+    concurrency: NonZeroUsize,
+) -> miette::Result<Vec<(PathBuf, PackagesManifest, DependencyGraph)>> {
+    let credentials = Credentials::load().await?;
+
+    let members = match manifest {
+        Manifest::Package(manifest) => vec![(cwd.to_path_buf(), manifest.clone())],
+        Manifest::Workspace(workspace) => {
+            let mut members = vec![];
+
+            for member in workspace.workspace.members(cwd)? {
+                let path = cwd.join(member);
+                let manifest = Manifest::require_package_manifest(&path).await?;
+                let manifest = workspace.patch_member(manifest, cwd);
+
+                members.push((path, manifest));
+            }
+
+            members
+        }
+    };
+
+    let mut graphs = vec![];
+
+    for (path, manifest) in members {
+        let graph = DependencyGraph::build_with_concurrency(
+            &manifest,
+            &path,
+            &credentials,
+            Some(lock.clone()),
+            NetworkMode::Online,
+            concurrency,
+        )
+        .await?;
+
+        graphs.push((path, manifest, graph));
+    }
+
+    Ok(graphs)
+}
+
+/// Prints the version changes between two sets of locked packages
+fn print_update_summary(
+    locked: &BTreeSet<(PackageName, Version)>,
+    updated: &BTreeSet<(PackageName, Version)>,
+) {
+    let mut changes: BTreeMap<&PackageName, (Vec<&Version>, Vec<&Version>)> = BTreeMap::new();
+
+    for (name, version) in locked {
+        changes.entry(name).or_default().0.push(version);
+    }
+
+    for (name, version) in updated {
+        changes.entry(name).or_default().1.push(version);
+    }
+
+    let join = |versions: &[&Version]| {
+        versions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut unchanged = true;
+
+    for (name, (old, new)) in changes {
+        if old == new {
+            continue;
+        }
+
+        unchanged = false;
+
+        match (old.is_empty(), new.is_empty()) {
+            (true, _) => tracing::info!("adding {name} {}", join(&new)),
+            (_, true) => tracing::info!("removing {name} {}", join(&old)),
+            _ => tracing::info!("updating {name} {} → {}", join(&old), join(&new)),
+        }
+    }
+
+    if unchanged {
+        tracing::info!("all locked packages are up to date");
+    }
+}
+
+/// Prints the resolved dependency graph as a tree
+///
+/// For workspaces, one tree is printed per workspace member. All other formats print a single
+/// graph containing all workspace members.
+///
+/// # Arguments
+///
+/// * `options` - Controls inversion and depth of the printed trees
+/// * `format` - The format to print the dependency graph in
+pub async fn tree(options: TreeOptions, format: GraphFormat) -> miette::Result<()> {
+    ensure!(
+        format == GraphFormat::Text || (options.invert.is_none() && options.depth.is_none()),
+        "--invert and --depth are only supported by the text format"
+    );
+
+    let cwd = env::current_dir().into_diagnostic()?;
+    let manifest = Manifest::load().await?;
+    let lock = Lockfile::read_from_or_infer(&cwd).await?;
+
+    let graphs = resolve_graphs(&manifest, &cwd, lock, DEFAULT_CONCURRENCY).await?;
+
+    let trees: Vec<DependencyTree> = graphs
+        .iter()
+        .map(|(_, manifest, graph)| DependencyTree::new(manifest, graph, &cwd))
+        .collect();
+
+    let exported = || ExportedGraph::new(&trees);
+
+    match format {
+        GraphFormat::Text => {}
+        GraphFormat::Dot => {
+            print!("{}", exported().to_dot());
+            return Ok(());
+        }
+        GraphFormat::Mermaid => {
+            print!("{}", exported().to_mermaid());
+            return Ok(());
+        }
+        GraphFormat::Json => {
+            // hint: always ok, as per serde_json doc
+            if let Ok(json) = serde_json::to_string_pretty(&exported()) {
+                println!("{json}");
+            }
+            return Ok(());
+        }
+    }
+
+    let trees: Vec<String> = trees
+        .iter()
+        .filter(|tree| {
+            options
+                .invert
+                .as_ref()
+                .is_none_or(|package| tree.contains(package))
+        })
+        .map(|tree| tree.render(&options))
+        .collect();
+
+    if let Some(package) = &options.invert {
+        ensure!(
+            !trees.is_empty(),
+            "package {package} is not part of the dependency graph"
+        );
+    }
+
+    println!("{}", trees.join("\n").trim_end());
+
+    Ok(())
+}
+
+/// Prints every path through which a package enters the dependency graph
+///
+/// Paths start at the root manifest, or at the workspace members depending on the package.
+/// Each hop shows the dependency as declared by its dependant.
+///
+/// # Arguments
+///
+/// * `package` - The package to explain
+pub async fn why(package: &PackageName) -> miette::Result<()> {
+    let cwd = env::current_dir().into_diagnostic()?;
+    let manifest = Manifest::load().await?;
+    let lock = Lockfile::read_from_or_infer(&cwd).await?;
+
+    let graphs = resolve_graphs(&manifest, &cwd, lock, DEFAULT_CONCURRENCY).await?;
+
+    let trees: Vec<DependencyTree> = graphs
+        .iter()
+        .map(|(_, manifest, graph)| DependencyTree::new(manifest, graph, &cwd))
+        .collect();
+
+    let Some(label) = trees.iter().find_map(|tree| tree.package_label(package)) else {
+        bail!("package {package} is not part of the dependency graph");
+    };
+
+    println!("{label}");
+
+    for path in trees.iter().flat_map(|tree| tree.paths_to(package)) {
+        println!("{path}");
+    }
+
+    Ok(())
+}
+
 /// Uninstalls dependencies
 ///
 /// Behavior depends on the manifest type:
@@ -420,9 +692,22 @@ pub async fn lint() -> miette::Result<()> {
 
 /// Logs you in for a registry
 pub async fn login(registry: RegistryUri) -> miette::Result<()> {
+    ensure!(
+        registry.kind() != RegistryKind::Filesystem,
+        "{registry} is a filesystem registry, which does not use credentials"
+    );
+
     let mut credentials = Credentials::load().await?;
 
-    tracing::info!("please enter your artifactory token:");
+    match registry.kind() {
+        RegistryKind::Oci => {
+            tracing::info!("please enter your registry credentials (`username:password` or token):")
+        }
+        RegistryKind::S3 => {
+            tracing::info!("please enter your access key (`access_key_id:secret_access_key`):")
+        }
+        _ => tracing::info!("please enter your artifactory token:"),
+    }
 
     let token = {
         let mut raw = String::new();
@@ -439,7 +724,8 @@ pub async fn login(registry: RegistryUri) -> miette::Result<()> {
 
     credentials.registry_tokens.insert(registry.clone(), token);
 
-    if env::var(BUFFRS_TESTSUITE_VAR).is_err() {
+    // Only Artifactory offers an endpoint to validate tokens with
+    if env::var(BUFFRS_TESTSUITE_VAR).is_err() && registry.kind() == RegistryKind::Artifactory {
         Artifactory::new(registry, &credentials)?
             .ping()
             .await
@@ -456,22 +742,350 @@ pub async fn logout(registry: RegistryUri) -> miette::Result<()> {
     credentials.write().await
 }
 
+/// Searches a registry for packages whose name contains a term
+///
+/// Prints every matching package with its published versions, or the list of
+/// [`SearchResult`](registry::SearchResult)s as JSON.
+pub async fn search(
+    registry: RegistryUri,
+    term: &str,
+    repository: Option<String>,
+    json: bool,
+) -> miette::Result<()> {
+    let term = term.trim().to_lowercase();
+
+    ensure!(
+        term.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'),
+        "search term {term} may only contain letters, digits and dashes"
+    );
+
+    let credentials = Credentials::load().await?;
+
+    let results = registry::connect(&registry, &credentials)?
+        .search(&term, repository)
+        .await?;
+
+    if json {
+        // hint: always ok, as per serde_json doc
+        if let Ok(json) = serde_json::to_string_pretty(&results) {
+            println!("{json}");
+        }
+        return Ok(());
+    }
+
+    if results.is_empty() {
+        tracing::info!("no packages matching {term} found in {registry}");
+        return Ok(());
+    }
+
+    for result in results {
+        let versions: Vec<String> = result.versions.iter().map(Version::to_string).collect();
+
+        println!(
+            "{}/{} ({})",
+            result.repository,
+            result.name,
+            versions.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints the published manifest and the files of a package
+///
+/// The latest version is described unless a version requirement is given. Packages locked by the
+/// current project are read from the cache, all others are downloaded and cached.
+pub async fn info(registry: RegistryUri, package: &str, json: bool) -> miette::Result<()> {
+    let DependencyLocator {
+        repository,
+        package: name,
+        version,
+    } = package.parse()?;
+
+    let credentials = Credentials::load().await?;
+    let client = registry::connect(&registry, &credentials)?;
+
+    let version = match version {
+        DependencyLocatorVersion::Latest => {
+            client
+                .get_latest_version(repository.clone(), name.clone())
+                .await?
+        }
+        DependencyLocatorVersion::Version(requirement) => client
+            .list_versions(repository.clone(), name.clone())
+            .await?
+            .into_iter()
+            .rfind(|version| requirement.matches(version))
+            .ok_or_else(|| {
+                miette!("no version of {name} matching {requirement} is published to {repository}")
+            })?,
+    };
+
+    let cache = Cache::open().await?;
+
+    let locked = if Lockfile::exists().await? {
+        Some(Lockfile::read_from_or_infer(env::current_dir().into_diagnostic()?).await?)
+    } else {
+        None
+    };
+
+    let locked = locked.as_ref().and_then(|lock| {
+        lock.packages()
+            .find(|locked| {
+                locked.name == name
+                    && locked.version == version
+                    && locked.registry == registry
+                    && locked.repository == repository
+            })
+            .cloned()
+    });
+
+    let cached = match &locked {
+        Some(locked) => cache.get(locked.into()).await?,
+        None => None,
+    };
+
+    let package = match cached {
+        Some(package) => {
+            tracing::debug!("resolved {name}@{version} from local cache");
+            package
+        }
+        None => {
+            let requirement = VersionReq::parse(&format!("={version}")).into_diagnostic()?;
+
+            let package = client
+                .download(Dependency::new(
+                    registry.clone(),
+                    repository.clone(),
+                    name.clone(),
+                    requirement,
+                ))
+                .await?;
+
+            if let Some(locked) = &locked {
+                locked.validate(&package)?;
+            }
+
+            cache
+                .put(CacheEntry::from(&package), package.tgz.clone())
+                .await
+                .ok();
+
+            package
+        }
+    };
+
+    ensure!(
+        package.manifest.package.is_some(),
+        "published manifest of {name}@{version} has no package declaration"
+    );
+
+    ensure!(
+        *package.name() == name && *package.version() == version,
+        "{repository}/{name}@{version} contains {}@{}",
+        package.name(),
+        package.version()
+    );
+
+    let info = PackageInfo::new(&package, registry, repository)?;
+
+    if json {
+        // hint: always ok, as per serde_json doc
+        if let Ok(json) = serde_json::to_string_pretty(&info) {
+            println!("{json}");
+        }
+        return Ok(());
+    }
+
+    print!("{info}");
+
+    Ok(())
+}
+
 /// Commands on the lockfile
 pub mod lock {
+    use std::env;
+
+    use miette::{IntoDiagnostic, ensure};
+    use semver::VersionReq;
+
+    use crate::cache::{Cache, Entry as CacheEntry};
+    use crate::credentials::Credentials;
     use crate::io::File;
-    use crate::lock::{FileRequirement, Lockfile};
+    use crate::lock::{
+        DigestAlgorithm, FileRequirement, FilesFormat, LOCKFILE, LockedPackage, Lockfile,
+        LockfileVersion,
+    };
+    use crate::manifest::{Manifest, package::Dependency};
+    use crate::operations::conflicts;
+    use crate::operations::verify::{self, VerificationFailed};
+    use crate::registry::{self, RegistryKind};
 
-    /// Prints the file requirements serialized as JSON
-    pub async fn print_files() -> miette::Result<()> {
+    /// Prints the file requirements in the given format
+    pub async fn print_files(format: FilesFormat) -> miette::Result<()> {
         let lock = Lockfile::load().await?;
 
         let requirements: Vec<FileRequirement> = lock.into();
 
-        // hint: always ok, as per serde_json doc
-        if let Ok(json) = serde_json::to_string_pretty(&requirements) {
-            println!("{json}");
+        match format {
+            FilesFormat::Json => {
+                // hint: always ok, as per serde_json doc
+                if let Ok(json) = serde_json::to_string_pretty(&requirements) {
+                    println!("{json}");
+                }
+            }
+            FilesFormat::Nix => print!("{}", FileRequirement::to_nix(&requirements)),
+            FilesFormat::Bazel => print!("{}", FileRequirement::to_bazel(&requirements)),
+        }
+
+        Ok(())
+    }
+
+    /// Upgrades the lockfile to the latest format version
+    ///
+    /// Locked packages are read from the cache, or downloaded from their registry if missing, to
+    /// record the information that older versions of the format lack. Packages locked from OCI
+    /// registries are always downloaded, to record the digest of their artifact manifest.
+    pub async fn migrate() -> miette::Result<()> {
+        let cwd = env::current_dir().into_diagnostic()?;
+
+        ensure!(
+            Lockfile::exists().await?,
+            "no {LOCKFILE} found, run `buffrs install` to create one"
+        );
+
+        let lock = Lockfile::read_from_or_infer(&cwd).await?;
+
+        if lock.version() == LockfileVersion::LATEST {
+            tracing::info!(
+                "{LOCKFILE} already uses the latest format ({})",
+                lock.version()
+            );
+            return Ok(());
+        }
+
+        let credentials = Credentials::load().await?;
+        let cache = Cache::open().await?;
+
+        let mut packages = Vec::new();
+
+        for locked in lock.packages() {
+            // The digest of an artifact manifest is only known to the registry storing it
+            let cached = match locked.registry.kind() {
+                RegistryKind::Oci => None,
+                _ => cache.get(locked.into()).await?,
+            };
+
+            let (package, manifest) = match cached {
+                Some(package) => (package, None),
+                None => {
+                    let version =
+                        VersionReq::parse(&format!("={}", locked.version)).into_diagnostic()?;
+
+                    let dependency = Dependency::new(
+                        locked.registry.clone(),
+                        locked.repository.clone(),
+                        locked.name.clone(),
+                        version,
+                    );
+
+                    let (package, manifest) = registry::connect(&locked.registry, &credentials)?
+                        .download_artifact(dependency, None)
+                        .await?;
+
+                    cache
+                        .put(CacheEntry::from(&package), package.tgz.clone())
+                        .await
+                        .ok();
+
+                    (package, manifest)
+                }
+            };
+
+            locked.validate(&package)?;
+
+            packages.push(LockedPackage {
+                manifest,
+                edition: Some(package.manifest.edition.clone()),
+                files: package.file_digests(DigestAlgorithm::SHA256)?,
+                ..locked.clone()
+            });
         }
 
+        let previous = lock.version();
+
+        lock.with_version(LockfileVersion::LATEST)
+            .with_packages(packages)
+            .save_to(&cwd)
+            .await?;
+
+        tracing::info!(
+            "migrated {LOCKFILE} from {previous} to {}",
+            LockfileVersion::LATEST
+        );
+
+        Ok(())
+    }
+
+    /// Resolves git merge conflicts in the lockfile
+    ///
+    /// Both sides of the conflicts are merged and validated against the manifest, and a clean
+    /// lockfile is written back.
+    pub async fn resolve_conflicts() -> miette::Result<()> {
+        let cwd = env::current_dir().into_diagnostic()?;
+
+        ensure!(
+            Lockfile::exists().await?,
+            "no {LOCKFILE} found, run `buffrs install` to create one"
+        );
+
+        let contents = tokio::fs::read_to_string(cwd.join(LOCKFILE))
+            .await
+            .into_diagnostic()?;
+
+        if !Lockfile::has_conflicts(&contents) {
+            tracing::info!("no merge conflicts found in {LOCKFILE}");
+            return Ok(());
+        }
+
+        let manifest = Manifest::load().await?;
+
+        conflicts::resolve(&cwd, &manifest, &contents)
+            .await?
+            .save_to(&cwd)
+            .await?;
+
+        tracing::info!("resolved merge conflicts in {LOCKFILE}");
+
+        Ok(())
+    }
+
+    /// Verifies the cache and the vendored packages against the lockfile
+    ///
+    /// Prints every difference to stdout and fails if there are any.
+    pub async fn verify() -> miette::Result<()> {
+        let cwd = env::current_dir().into_diagnostic()?;
+
+        ensure!(
+            Lockfile::exists().await?,
+            "no {LOCKFILE} found, run `buffrs install` to create one"
+        );
+
+        let manifest = Manifest::load().await?;
+        let lock = Lockfile::read_from_or_infer(&cwd).await?;
+        let cache = Cache::open().await?;
+
+        let findings = verify::verify(&cwd, &manifest, &lock, &cache).await?;
+
+        for finding in &findings {
+            println!("{finding}");
+        }
+
+        ensure!(findings.is_empty(), VerificationFailed(findings));
+
+        tracing::info!("installed packages match {LOCKFILE}");
+
         Ok(())
     }
 }
@@ -0,0 +1,301 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use miette::{Context as _, IntoDiagnostic, bail, ensure};
+
+use crate::{
+    lock::{DigestAlgorithm, LockedGitSource},
+    manifest::GitDependencyManifest,
+};
+
+/// Directory in the buffrs home holding git repositories and their checkouts
+const GIT_DIRECTORY: &str = "git";
+
+/// A checkout of a git dependency
+#[derive(Debug, Clone)]
+pub struct Checkout {
+    /// Root directory of the checked out repository
+    pub root: PathBuf,
+    /// The commit that was checked out
+    pub source: LockedGitSource,
+}
+
+/// Checks out a git dependency in the buffrs home
+///
+/// Repositories are fetched into a bare clone under `git/db`, which is shared by all revisions,
+/// and every commit is checked out once under `git/checkouts`. The locked commit is checked out
+/// if given, and the declared revision is resolved otherwise. Without network access, only
+/// commits which have been fetched before can be checked out.
+pub async fn checkout(
+    manifest: &GitDependencyManifest,
+    locked: Option<&str>,
+    online: bool,
+) -> miette::Result<Checkout> {
+    // Git runs in different directories, which requires an absolute location
+    let directory = std::path::absolute(crate::home().into_diagnostic()?.join(GIT_DIRECTORY))
+        .into_diagnostic()?;
+    let identifier = identifier(&manifest.git);
+    let database = directory.join("db").join(&identifier);
+
+    if !database.exists() {
+        ensure!(
+            online,
+            "git repository {} has not been fetched yet and network access is disabled",
+            manifest.git
+        );
+
+        tokio::fs::create_dir_all(&database)
+            .await
+            .into_diagnostic()?;
+
+        git(["init", "--bare", "--quiet"], &database).await?;
+    }
+
+    if let Some(commit) = locked {
+        ensure!(
+            is_commit_id(commit),
+            "locked commit {commit} of {} is not a full commit id",
+            manifest.git
+        );
+    }
+
+    let commit = match locked {
+        Some(commit) if has_commit(&database, commit).await => commit.to_owned(),
+        Some(commit) => {
+            ensure!(
+                online,
+                "commit {commit} of {} has not been fetched yet and network access is disabled",
+                manifest.git
+            );
+
+            fetch(&database, &manifest.git).await?;
+
+            ensure!(
+                has_commit(&database, commit).await,
+                "locked commit {commit} does not exist in {}",
+                manifest.git
+            );
+
+            commit.to_owned()
+        }
+        None => {
+            ensure!(
+                online,
+                "revision {} of {} cannot be resolved while network access is disabled",
+                manifest.rev.as_deref().unwrap_or("HEAD"),
+                manifest.git
+            );
+
+            fetch(&database, &manifest.git).await?;
+            resolve(&database, &manifest.git, manifest.rev.as_deref()).await?
+        }
+    };
+
+    let root = directory.join("checkouts").join(&identifier).join(&commit);
+
+    if !root.exists() {
+        tracing::debug!("checking out {} at {commit}", manifest.git);
+
+        // Checkouts are prepared next to their final location, so interrupted checkouts are
+        // never mistaken for complete ones
+        let staging = root.with_extension("partial");
+
+        if staging.exists() {
+            tokio::fs::remove_dir_all(&staging)
+                .await
+                .into_diagnostic()?;
+        }
+
+        git(
+            [
+                OsStr::new("clone"),
+                OsStr::new("--quiet"),
+                OsStr::new("--no-checkout"),
+                database.as_os_str(),
+                staging.as_os_str(),
+            ],
+            &directory,
+        )
+        .await?;
+
+        git(["checkout", "--quiet", "--detach", &commit], &staging).await?;
+
+        tokio::fs::rename(&staging, &root)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to move checkout to {}", root.display()))?;
+    }
+
+    Ok(Checkout {
+        root,
+        source: LockedGitSource {
+            url: manifest.git.clone(),
+            rev: manifest.rev.clone(),
+            commit,
+        },
+    })
+}
+
+/// Identifies a repository by its name and a hash of its URL
+fn identifier(url: &str) -> String {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(".git");
+
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+
+    let hash = DigestAlgorithm::SHA256.digest(url.as_bytes()).to_hex();
+
+    format!("{name}-{}", &hash[..16])
+}
+
+/// Fetches all branches, tags and the default branch of a repository
+async fn fetch(database: &Path, url: &str) -> miette::Result<()> {
+    tracing::info!("fetching {url}");
+
+    git(
+        [
+            "fetch",
+            "--quiet",
+            "--force",
+            "--tags",
+            "--",
+            url,
+            "+HEAD:refs/remotes/origin/HEAD",
+            "+refs/heads/*:refs/remotes/origin/*",
+        ],
+        database,
+    )
+    .await
+    .wrap_err_with(|| format!("failed to fetch {url}"))?;
+
+    Ok(())
+}
+
+/// Resolves a branch, tag or commit to a commit hash, or the default branch if no revision is given
+async fn resolve(database: &Path, url: &str, rev: Option<&str>) -> miette::Result<String> {
+    let candidates = match rev {
+        Some(rev) => vec![
+            format!("refs/remotes/origin/{rev}"),
+            format!("refs/tags/{rev}"),
+            rev.to_owned(),
+        ],
+        None => vec!["refs/remotes/origin/HEAD".to_owned()],
+    };
+
+    for candidate in candidates {
+        let object = format!("{candidate}^{{commit}}");
+
+        if let Ok(commit) = git(["rev-parse", "--verify", "--quiet", &object], database).await {
+            return Ok(commit);
+        }
+    }
+
+    bail!("revision {} does not exist in {url}", rev.unwrap_or("HEAD"))
+}
+
+/// Whether a string is a full SHA-1 or SHA-256 commit id
+///
+/// Locked commits are used as revisions and directory names, so abbreviated ids, revision
+/// expressions and paths are rejected.
+fn is_commit_id(commit: &str) -> bool {
+    matches!(commit.len(), 40 | 64) && commit.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+async fn has_commit(database: &Path, commit: &str) -> bool {
+    git(
+        ["cat-file", "-e", &format!("{commit}^{{commit}}")],
+        database,
+    )
+    .await
+    .is_ok()
+}
+
+/// Runs git in a directory, returning its trimmed output
+async fn git<I, S>(args: I, cwd: &Path) -> miette::Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let args: Vec<_> = args
+        .into_iter()
+        .map(|arg| arg.as_ref().to_owned())
+        .collect();
+
+    let output = tokio::process::Command::new("git")
+        .args(&args)
+        .current_dir(cwd)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .into_diagnostic()
+        .wrap_err("failed to run git - is it installed?")?;
+
+    if !output.status.success() {
+        let command = args
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        bail!(
+            "`git {command}` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    String::from_utf8(output.stdout)
+        .into_diagnostic()
+        .map(|stdout| stdout.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{identifier, is_commit_id};
+
+    #[test]
+    fn identifies_repositories() {
+        let https = identifier("https://github.com/helsing-ai/protos.git");
+        let ssh = identifier("git@github.com:helsing-ai/protos.git");
+
+        assert!(https.starts_with("protos-"));
+        assert!(ssh.starts_with("protos-"));
+        assert_ne!(https, ssh);
+        assert_eq!(https.len(), "protos-".len() + 16);
+        assert!(identifier("file:///srv/git/my repo/").starts_with("my-repo-"));
+    }
+
+    #[test]
+    fn validates_commit_ids() {
+        assert!(is_commit_id("3f786850e387550fdab836ed7e6dc881de23001b"));
+        assert!(is_commit_id(&"a".repeat(64)));
+        assert!(!is_commit_id("3f78685"));
+        assert!(!is_commit_id("HEAD"));
+        assert!(!is_commit_id(&format!("../{}", "a".repeat(37))));
+        assert!(!is_commit_id(&"g".repeat(40)));
+    }
+}
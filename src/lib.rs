@@ -28,6 +28,9 @@ pub mod command;
 pub mod credentials;
 /// Common error types
 pub mod errors;
+/// Checkouts of git dependencies
+#[cfg(feature = "git")]
+pub mod git;
 /// IO traits
 pub mod io;
 /// Lockfile implementation
@@ -44,6 +47,10 @@ pub mod package;
 pub mod registry;
 /// Resolve package dependencies
 pub mod resolver;
+/// Downloads of tarball dependencies
+pub mod tarball;
+/// Helpers shared across modules
+pub(crate) mod util;
 /// Validation for buffrs packages.
 #[cfg(feature = "validation")]
 pub mod validation;
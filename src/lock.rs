@@ -13,12 +13,14 @@
 // limitations under the License.
 
 use std::{
-    collections::{BTreeMap, HashSet},
-    path::Path,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
-use miette::{Context, IntoDiagnostic, ensure};
-use semver::Version;
+use miette::{Context, Diagnostic, IntoDiagnostic, ensure};
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::fs;
@@ -28,17 +30,89 @@ use crate::{
     ManagedFile,
     errors::{DeserializationError, FileNotFound, SerializationError, WriteError},
     io::File,
-    manifest::Manifest,
+    manifest::{Edition, Manifest},
     package::{Package, PackageName},
-    registry::RegistryUri,
+    registry::{RegistryKind, RegistryUri},
 };
 
+mod conflicts;
 mod digest;
+mod fetch;
 pub use digest::{Digest, DigestAlgorithm};
+pub use fetch::FilesFormat;
 
 /// File name of the lockfile
 pub const LOCKFILE: &str = "Proto.lock";
 
+/// Version of the lockfile format
+///
+/// New lockfiles are written in the latest version. Existing lockfiles keep their version until
+/// they are migrated with `buffrs lock migrate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(into = "u16", try_from = "u16")]
+pub enum LockfileVersion {
+    /// Records the source, digest and dependency names of each package
+    V1,
+    /// Additionally records the edition, the digest of each protocol buffer file, the kind of
+    /// each dependency and the digest of OCI artifact manifests
+    #[default]
+    V2,
+}
+
+impl LockfileVersion {
+    /// The latest version of the lockfile format
+    pub const LATEST: Self = Self::V2;
+}
+
+impl From<LockfileVersion> for u16 {
+    fn from(version: LockfileVersion) -> Self {
+        match version {
+            LockfileVersion::V1 => 1,
+            LockfileVersion::V2 => 2,
+        }
+    }
+}
+
+impl TryFrom<u16> for LockfileVersion {
+    type Error = UnsupportedLockfileVersion;
+
+    fn try_from(version: u16) -> Result<Self, Self::Error> {
+        match version {
+            1 => Ok(Self::V1),
+            2 => Ok(Self::V2),
+            _ => Err(UnsupportedLockfileVersion(version)),
+        }
+    }
+}
+
+impl fmt::Display for LockfileVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", u16::from(*self))
+    }
+}
+
+/// The lockfile was written by a newer version of buffrs
+#[derive(Error, Debug)]
+#[error("unsupported lockfile version {0}, consider upgrading buffrs")]
+pub struct UnsupportedLockfileVersion(u16);
+
+/// The lockfile contains git conflict markers
+#[derive(Error, Diagnostic, Debug)]
+#[error("{LOCKFILE} contains unresolved merge conflicts")]
+#[diagnostic(help("run `buffrs lock resolve-conflicts` to merge both sides"))]
+struct UnresolvedConflicts;
+
+/// Two entries lock the same version of a package from different sources
+#[derive(Error, Diagnostic, Debug)]
+#[error("{name} {version} is locked from different sources: {existing} and {locked}")]
+#[diagnostic(help("depend on {name} from the same registry and repository everywhere"))]
+struct ConflictingSources {
+    name: PackageName,
+    version: Version,
+    existing: String,
+    locked: String,
+}
+
 /// A locked dependency with exact name and version
 ///
 /// Serializes as "name version" string (Cargo format)
@@ -86,6 +160,15 @@ impl LockedDependency {
     }
 }
 
+impl fmt::Display for LockedDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named { name } => write!(f, "{name}"),
+            Self::Qualified { name, version } => write!(f, "{name} {version}"),
+        }
+    }
+}
+
 // Custom serialization to match Cargo's "name version" format
 impl Serialize for LockedDependency {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -142,12 +225,22 @@ pub struct LockedPackage {
     pub registry: RegistryUri,
     /// The identifier of the repository where the package was published
     pub repository: String,
+    /// The digest of the artifact manifest the package was published with, only recorded by v2
+    /// lockfiles for registries which store packages as content addressed artifacts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<Digest>,
     /// Names of dependency packages
     pub dependencies: Vec<LockedDependency>,
     /// Count of dependant packages in the current graph
     ///
     /// This is used to detect when an entry can be safely removed from the lockfile.
     pub dependants: usize,
+    /// The edition of the package manifest, only recorded by v2 lockfiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edition: Option<Edition>,
+    /// Digests of the protocol buffer files of the package, only recorded by v2 lockfiles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<LockedFile>,
 }
 
 impl LockedPackage {
@@ -157,11 +250,12 @@ impl LockedPackage {
         registry: RegistryUri,
         repository: String,
         dependants: usize,
-    ) -> Self {
-        Self {
+    ) -> miette::Result<Self> {
+        Ok(Self {
             name: package.name().to_owned(),
             registry,
             repository,
+            manifest: None,
             digest: package.digest(DigestAlgorithm::SHA256).to_owned(),
             version: package.version().to_owned(),
             dependencies: package
@@ -172,6 +266,46 @@ impl LockedPackage {
                 .map(|d| LockedDependency::named(d.package.clone()))
                 .collect(),
             dependants,
+            edition: Some(package.manifest.edition.clone()),
+            files: package.file_digests(DigestAlgorithm::SHA256)?,
+        })
+    }
+
+    /// Whether another entry locks the same contents from the same location
+    fn same_source(&self, other: &Self) -> bool {
+        (
+            &self.registry,
+            &self.repository,
+            &self.digest,
+            &self.manifest,
+        ) == (
+            &other.registry,
+            &other.repository,
+            &other.digest,
+            &other.manifest,
+        )
+    }
+
+    /// Describes the location and contents of the locked package
+    fn source(&self) -> String {
+        let source = format!("{} in {} ({})", self.repository, self.registry, self.digest);
+
+        match &self.manifest {
+            Some(manifest) => format!("{source}, manifest {manifest}"),
+            None => source,
+        }
+    }
+
+    /// Drops the information not recorded by the given lockfile version
+    fn restrict_to(self, version: LockfileVersion) -> Self {
+        match version {
+            LockfileVersion::V1 => Self {
+                manifest: None,
+                edition: None,
+                files: vec![],
+                ..self
+            },
+            LockfileVersion::V2 => self,
         }
     }
 
@@ -218,17 +352,217 @@ impl LockedPackage {
     }
 }
 
+/// Captures the location and contents of a local dependency
+///
+/// Local dependencies are always installed from their path, so this entry is not used to select
+/// versions. It makes changes to the contents of local packages visible in the lockfile.
+///
+/// Git dependencies are checked out and installed like local packages. Their entries record the
+/// commit that was checked out, which is reused by later installations.
+///
+/// Tarball dependencies are not recorded, since the digest declared in the manifest pins their
+/// contents and dependants cannot declare them with different digests.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LockedLocalPackage {
+    /// The name of the package
+    pub name: PackageName,
+    /// The version declared in the manifest of the package
+    pub version: Version,
+    /// The path of the package, relative to the lockfile or to the root of the git repository
+    pub path: PathBuf,
+    /// The cryptographic digest of the released package, excluding modification times
+    pub digest: Digest,
+    /// The git repository the package was checked out from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<LockedGitSource>,
+}
+
+/// Captures the commit a git dependency was checked out at
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LockedGitSource {
+    /// The URL of the git repository
+    pub url: String,
+    /// The branch, tag or commit declared in the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// The commit the declared revision resolved to
+    pub commit: String,
+}
+
+/// Captures the contents of a protocol buffer file of a locked package
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LockedFile {
+    /// The path of the file inside the package
+    pub path: PathBuf,
+    /// The cryptographic digest of the file contents
+    pub digest: Digest,
+}
+
+/// Serialized form of package and workspace lockfiles, which share the same format
 #[derive(Serialize, Deserialize)]
-struct RawPackageLockfile {
-    version: u16,
-    packages: Vec<LockedPackage>,
+struct RawLockfile<P> {
+    version: LockfileVersion,
+    packages: Vec<P>,
+    /// Written by all versions, since older versions of buffrs ignore unknown sections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    local: Vec<LockedLocalPackage>,
 }
 
-impl RawPackageLockfile {
-    pub fn v1(packages: Vec<LockedPackage>) -> Self {
+impl RawLockfile<LockedPackage> {
+    /// Prepares the entries of a lockfile for serialization in a stable order
+    fn new<'a>(
+        version: LockfileVersion,
+        packages: impl Iterator<Item = &'a LockedPackage>,
+        local: impl Iterator<Item = &'a LockedLocalPackage>,
+    ) -> Self {
+        let mut packages: Vec<_> = packages
+            .map(|pkg| {
+                let mut locked = pkg.clone().restrict_to(version);
+                locked.dependencies.sort();
+                locked
+            })
+            .collect();
+
+        packages.sort();
+
         Self {
-            version: 1,
+            version,
             packages,
+            local: local.cloned().collect(),
+        }
+    }
+
+    /// Parses a lockfile of any supported version
+    fn parse(contents: &str) -> miette::Result<Self> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: LockfileVersion,
+        }
+
+        ensure!(!conflicts::has_markers(contents), UnresolvedConflicts);
+
+        let Versioned { version } = toml::from_str(contents)
+            .into_diagnostic()
+            .wrap_err(DeserializationError(ManagedFile::Lock))?;
+
+        match version {
+            LockfileVersion::V1 => toml::from_str(contents)
+                .into_diagnostic()
+                .wrap_err(DeserializationError(ManagedFile::Lock)),
+            LockfileVersion::V2 => {
+                let raw: RawLockfile<RawLockedPackage> = toml::from_str(contents)
+                    .into_diagnostic()
+                    .wrap_err(DeserializationError(ManagedFile::Lock))?;
+
+                Ok(Self {
+                    version,
+                    packages: raw.packages.into_iter().map(LockedPackage::from).collect(),
+                    local: raw.local,
+                })
+            }
+        }
+    }
+
+    /// Serializes the lockfile in the format of its version
+    fn to_toml(&self) -> miette::Result<String> {
+        let serialized = match self.version {
+            LockfileVersion::V1 => toml::to_string(self),
+            LockfileVersion::V2 => toml::to_string(&RawLockfile {
+                version: self.version,
+                packages: self
+                    .packages
+                    .iter()
+                    .cloned()
+                    .map(RawLockedPackage::from)
+                    .collect(),
+                local: self.local.clone(),
+            }),
+        };
+
+        serialized
+            .into_diagnostic()
+            .wrap_err(SerializationError(ManagedFile::Lock))
+    }
+}
+
+/// Serialized form of a locked package in v2 lockfiles
+#[derive(Serialize, Deserialize)]
+struct RawLockedPackage {
+    name: PackageName,
+    version: Version,
+    digest: Digest,
+    registry: RegistryUri,
+    repository: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    manifest: Option<Digest>,
+    // Editions are deserialized from borrowed strings, which nested TOML tables cannot provide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    edition: Option<String>,
+    dependants: usize,
+    #[serde(default)]
+    dependencies: Vec<RawLockedDependency>,
+    #[serde(default)]
+    files: Vec<LockedFile>,
+}
+
+/// Serialized form of a locked dependency in v2 lockfiles, recording the kind of the edge
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum RawLockedDependency {
+    Named { name: PackageName },
+    Qualified { name: PackageName, version: Version },
+}
+
+impl From<LockedPackage> for RawLockedPackage {
+    fn from(package: LockedPackage) -> Self {
+        Self {
+            name: package.name,
+            version: package.version,
+            digest: package.digest,
+            registry: package.registry,
+            repository: package.repository,
+            manifest: package.manifest,
+            edition: package
+                .edition
+                .map(|edition| <&str>::from(edition).to_owned()),
+            dependants: package.dependants,
+            dependencies: package
+                .dependencies
+                .into_iter()
+                .map(|dependency| match dependency {
+                    LockedDependency::Named { name } => RawLockedDependency::Named { name },
+                    LockedDependency::Qualified { name, version } => {
+                        RawLockedDependency::Qualified { name, version }
+                    }
+                })
+                .collect(),
+            files: package.files,
+        }
+    }
+}
+
+impl From<RawLockedPackage> for LockedPackage {
+    fn from(package: RawLockedPackage) -> Self {
+        Self {
+            name: package.name,
+            version: package.version,
+            digest: package.digest,
+            registry: package.registry,
+            repository: package.repository,
+            manifest: package.manifest,
+            dependencies: package
+                .dependencies
+                .into_iter()
+                .map(|dependency| match dependency {
+                    RawLockedDependency::Named { name } => LockedDependency::Named { name },
+                    RawLockedDependency::Qualified { name, version } => {
+                        LockedDependency::Qualified { name, version }
+                    }
+                })
+                .collect(),
+            dependants: package.dependants,
+            edition: package.edition.as_deref().map(Edition::from),
+            files: package.files,
         }
     }
 }
@@ -238,7 +572,9 @@ impl RawPackageLockfile {
 /// Used to ensure future installations will deterministically select the exact same packages.
 #[derive(Default, Debug, PartialEq, Clone)]
 pub struct PackageLockfile {
+    version: LockfileVersion,
     packages: BTreeMap<PackageName, LockedPackage>,
+    local: BTreeMap<PackageName, LockedLocalPackage>,
 }
 
 impl PackageLockfile {
@@ -251,6 +587,42 @@ impl PackageLockfile {
     pub fn packages(&self) -> impl Iterator<Item = &LockedPackage> {
         self.packages.values()
     }
+
+    /// Returns all local packages in the lockfile
+    pub fn local_packages(&self) -> impl Iterator<Item = &LockedLocalPackage> {
+        self.local.values()
+    }
+
+    /// Replaces the local packages of the lockfile
+    pub fn with_local_packages(self, local: impl IntoIterator<Item = LockedLocalPackage>) -> Self {
+        Self {
+            local: local
+                .into_iter()
+                .map(|locked| (locked.name.clone(), locked))
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Returns the format version of the lockfile
+    pub fn version(&self) -> LockfileVersion {
+        self.version
+    }
+
+    /// Changes the format version of the lockfile
+    ///
+    /// Information not recorded by the given version is dropped from all entries.
+    pub fn with_version(self, version: LockfileVersion) -> Self {
+        Self {
+            version,
+            packages: self
+                .packages
+                .into_iter()
+                .map(|(key, locked)| (key, locked.restrict_to(version)))
+                .collect(),
+            ..self
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -262,12 +634,7 @@ impl File for PackageLockfile {
         P: AsRef<Path> + Send + Sync,
     {
         match fs::read_to_string(path).await {
-            Ok(contents) => {
-                let raw: RawPackageLockfile = toml::from_str(&contents)
-                    .into_diagnostic()
-                    .wrap_err(DeserializationError(ManagedFile::Lock))?;
-                Ok(Self::from_iter(raw.packages))
-            }
+            Ok(contents) => contents.parse(),
             Err(err) if matches!(err.kind(), std::io::ErrorKind::NotFound) => {
                 Err(FileNotFound(LOCKFILE.into()).into())
             }
@@ -279,31 +646,25 @@ impl File for PackageLockfile {
     where
         P: AsRef<Path> + Send + Sync,
     {
-        let mut packages: Vec<_> = self
-            .packages
-            .values()
-            .map(|pkg| {
-                let mut locked = pkg.clone();
-                locked.dependencies.sort();
-                locked
-            })
-            .collect();
+        let raw = RawLockfile::new(self.version, self.packages.values(), self.local.values());
+        let lockfile_path = path.as_ref().join(LOCKFILE);
 
-        packages.sort();
+        fs::write(lockfile_path, raw.to_toml()?.into_bytes())
+            .await
+            .into_diagnostic()
+            .wrap_err(WriteError(LOCKFILE))
+    }
+}
 
-        let raw = RawPackageLockfile::v1(packages);
-        let lockfile_path = path.as_ref().join(LOCKFILE);
+impl FromStr for PackageLockfile {
+    type Err = miette::Report;
 
-        fs::write(
-            lockfile_path,
-            toml::to_string(&raw)
-                .into_diagnostic()
-                .wrap_err(SerializationError(ManagedFile::Lock))?
-                .into_bytes(),
-        )
-        .await
-        .into_diagnostic()
-        .wrap_err(WriteError(LOCKFILE))
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let raw = RawLockfile::parse(contents)?;
+
+        Ok(Self::from_iter(raw.packages)
+            .with_local_packages(raw.local)
+            .with_version(raw.version))
     }
 }
 
@@ -336,21 +697,8 @@ impl FromIterator<LockedPackage> for PackageLockfile {
                 .into_iter()
                 .map(|locked| (locked.name.clone(), locked))
                 .collect(),
-        }
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-struct RawWorkspaceLockfile {
-    version: u16,
-    packages: Vec<LockedPackage>,
-}
-
-impl RawWorkspaceLockfile {
-    pub fn v1(packages: Vec<LockedPackage>) -> Self {
-        Self {
-            version: 1,
-            packages,
+            local: BTreeMap::new(),
+            version: LockfileVersion::LATEST,
         }
     }
 }
@@ -362,7 +710,9 @@ impl RawWorkspaceLockfile {
 /// versions of the same package.
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct WorkspaceLockfile {
+    version: LockfileVersion,
     packages: BTreeMap<(PackageName, Version), LockedPackage>,
+    local: BTreeMap<(PackageName, PathBuf), LockedLocalPackage>,
 }
 
 impl WorkspaceLockfile {
@@ -375,6 +725,44 @@ impl WorkspaceLockfile {
     pub fn packages(&self) -> impl Iterator<Item = &LockedPackage> {
         self.packages.values()
     }
+
+    /// Returns all local packages in the lockfile
+    pub fn local_packages(&self) -> impl Iterator<Item = &LockedLocalPackage> {
+        self.local.values()
+    }
+
+    /// Replaces the local packages of the lockfile
+    ///
+    /// Local packages shared by several workspace members are identified by name and path.
+    pub fn with_local_packages(self, local: impl IntoIterator<Item = LockedLocalPackage>) -> Self {
+        Self {
+            local: local
+                .into_iter()
+                .map(|locked| ((locked.name.clone(), locked.path.clone()), locked))
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Returns the format version of the lockfile
+    pub fn version(&self) -> LockfileVersion {
+        self.version
+    }
+
+    /// Changes the format version of the lockfile
+    ///
+    /// Information not recorded by the given version is dropped from all entries.
+    pub fn with_version(self, version: LockfileVersion) -> Self {
+        Self {
+            version,
+            packages: self
+                .packages
+                .into_iter()
+                .map(|(key, locked)| (key, locked.restrict_to(version)))
+                .collect(),
+            ..self
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -395,12 +783,7 @@ impl File for WorkspaceLockfile {
         };
 
         match fs::read_to_string(resolved).await {
-            Ok(contents) => {
-                let raw: RawWorkspaceLockfile = toml::from_str(&contents)
-                    .into_diagnostic()
-                    .wrap_err(DeserializationError(ManagedFile::Lock))?;
-                Ok(Self::from_iter(raw.packages))
-            }
+            Ok(contents) => contents.parse(),
             Err(err) if matches!(err.kind(), std::io::ErrorKind::NotFound) => {
                 Err(FileNotFound(LOCKFILE.into()).into())
             }
@@ -413,31 +796,25 @@ impl File for WorkspaceLockfile {
     where
         P: AsRef<Path> + Send + Sync,
     {
-        let mut packages: Vec<_> = self
-            .packages
-            .values()
-            .map(|pkg| {
-                let mut locked = pkg.clone();
-                locked.dependencies.sort();
-                locked
-            })
-            .collect();
+        let raw = RawLockfile::new(self.version, self.packages.values(), self.local.values());
+        let lockfile_path = path.as_ref().join(LOCKFILE);
 
-        packages.sort();
+        fs::write(lockfile_path, raw.to_toml()?.into_bytes())
+            .await
+            .into_diagnostic()
+            .wrap_err(WriteError(LOCKFILE))
+    }
+}
 
-        let raw = RawWorkspaceLockfile::v1(packages);
-        let lockfile_path = path.as_ref().join(LOCKFILE);
+impl FromStr for WorkspaceLockfile {
+    type Err = miette::Report;
 
-        fs::write(
-            lockfile_path,
-            toml::to_string(&raw)
-                .into_diagnostic()
-                .wrap_err(SerializationError(ManagedFile::Lock))?
-                .into_bytes(),
-        )
-        .await
-        .into_diagnostic()
-        .wrap_err(WriteError(LOCKFILE))
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let raw = RawLockfile::parse(contents)?;
+
+        Ok(Self::from_iter(raw.packages)
+            .with_local_packages(raw.local)
+            .with_version(raw.version))
     }
 }
 
@@ -449,6 +826,8 @@ impl FromIterator<LockedPackage> for WorkspaceLockfile {
                 .into_iter()
                 .map(|locked| ((locked.name.clone(), locked.version.clone()), locked))
                 .collect(),
+            local: BTreeMap::new(),
+            version: LockfileVersion::LATEST,
         }
     }
 }
@@ -488,6 +867,100 @@ impl Lockfile {
         }
     }
 
+    /// Finds the highest locked version of a package that satisfies a version requirement
+    pub fn find_matching(
+        &self,
+        name: &PackageName,
+        requirement: &VersionReq,
+    ) -> Option<&LockedPackage> {
+        self.packages()
+            .filter(|pkg| pkg.name == *name)
+            .filter(|pkg| requirement.matches(&pkg.version))
+            .max_by_key(|pkg| &pkg.version)
+    }
+
+    /// Returns a copy of the lockfile without the entries of the given packages
+    ///
+    /// Locked dependencies which only the given packages depend on are removed as well, so they
+    /// can be updated along with them. Packages required by a manifest, a local package or any
+    /// retained package stay locked. Local packages are always retained, since they are never
+    /// used to select versions.
+    pub fn without(&self, names: &[PackageName]) -> Self {
+        let mut removed: Vec<&LockedPackage> = self
+            .packages()
+            .filter(|pkg| names.contains(&pkg.name))
+            .collect();
+
+        loop {
+            let exclusive: Vec<&LockedPackage> = self
+                .packages()
+                .filter(|pkg| !removed.contains(pkg))
+                .filter(|pkg| {
+                    let dependants = removed
+                        .iter()
+                        .filter(|dependant| {
+                            dependant.dependencies.iter().any(|dependency| {
+                                dependency.name() == &pkg.name
+                                    && dependency.version().is_none_or(|v| v == &pkg.version)
+                            })
+                        })
+                        .count();
+
+                    dependants > 0 && dependants >= pkg.dependants
+                })
+                .collect();
+
+            if exclusive.is_empty() {
+                break;
+            }
+
+            removed.extend(exclusive);
+        }
+
+        let retained: Vec<_> = self
+            .packages()
+            .filter(|pkg| !removed.contains(pkg))
+            .cloned()
+            .collect();
+
+        self.clone().with_packages(retained)
+    }
+
+    /// Replaces the packages of the lockfile, retaining its kind, version and local packages
+    pub fn with_packages(self, packages: impl IntoIterator<Item = LockedPackage>) -> Self {
+        let version = self.version();
+        let local: Vec<_> = self.local_packages().cloned().collect();
+
+        match self {
+            Self::Package(_) => Self::Package(
+                PackageLockfile::from_iter(packages)
+                    .with_local_packages(local)
+                    .with_version(version),
+            ),
+            Self::Workspace(_) => Self::Workspace(
+                WorkspaceLockfile::from_iter(packages)
+                    .with_local_packages(local)
+                    .with_version(version),
+            ),
+        }
+    }
+
+    /// Returns the format version of the lockfile
+    pub fn version(&self) -> LockfileVersion {
+        match self {
+            Self::Package(lock) => lock.version(),
+            Self::Workspace(lock) => lock.version(),
+        }
+    }
+
+    /// Changes the format version of the lockfile
+    pub fn with_version(self, version: LockfileVersion) -> Self {
+        match self {
+            Self::Package(lock) => Self::Package(lock.with_version(version)),
+            Self::Workspace(lock) => Self::Workspace(lock.with_version(version)),
+        }
+    }
+
     /// Returns all packages in the lockfile
     pub fn packages(&self) -> impl Iterator<Item = &LockedPackage> {
         let pkgs: Vec<&LockedPackage> = match self {
@@ -498,6 +971,16 @@ impl Lockfile {
         pkgs.into_iter()
     }
 
+    /// Returns all local packages in the lockfile
+    pub fn local_packages(&self) -> impl Iterator<Item = &LockedLocalPackage> {
+        let pkgs: Vec<&LockedLocalPackage> = match self {
+            Self::Package(pkg) => pkg.local_packages().collect(),
+            Self::Workspace(wrk) => wrk.local_packages().collect(),
+        };
+
+        pkgs.into_iter()
+    }
+
     /// Loads an existing lockfile or creates a default one, using the manifest to determine
     /// whether this is a package or workspace lockfile.
     ///
@@ -506,6 +989,17 @@ impl Lockfile {
     pub async fn load_from_or_infer(path: impl AsRef<Path>) -> miette::Result<Self> {
         let path = path.as_ref();
 
+        let lock = Self::read_from_or_infer(path).await?;
+
+        lock.save_to(path).await?;
+
+        Ok(lock)
+    }
+
+    /// Like [`Lockfile::load_from_or_infer`], but never writes the lockfile to disk
+    pub async fn read_from_or_infer(path: impl AsRef<Path>) -> miette::Result<Self> {
+        let path = path.as_ref();
+
         let cwd = if path.is_dir() {
             path
         } else {
@@ -529,11 +1023,125 @@ impl Lockfile {
                 .map(Self::Workspace)?
         };
 
-        lock.save_to(path).await?;
-
         Ok(lock)
     }
 
+    /// Returns true if the contents of a lockfile contain git conflict markers
+    pub fn has_conflicts(contents: &str) -> bool {
+        conflicts::has_markers(contents)
+    }
+
+    /// Parses both sides of the git conflicts in the contents of a lockfile
+    ///
+    /// Like [`Lockfile::read_from_or_infer`], the manifest determines the kind of the lockfile.
+    pub fn parse_conflicts(contents: &str, manifest: &Manifest) -> miette::Result<(Self, Self)> {
+        let (ours, theirs) = conflicts::split(contents)
+            .wrap_err_with(|| format!("failed to parse the merge conflicts in {LOCKFILE}"))?;
+
+        let parse = |contents: &str| -> miette::Result<Self> {
+            match manifest {
+                Manifest::Package(_) => contents.parse().map(Self::Package),
+                Manifest::Workspace(_) => contents.parse().map(Self::Workspace),
+            }
+        };
+
+        Ok((parse(&ours)?, parse(&theirs)?))
+    }
+
+    /// Merges the entries of two lockfiles of the same kind
+    ///
+    /// Entries locking the same package version are merged like the lockfiles of workspace
+    /// members, and package lockfiles keep the highest version of each package. Entries which
+    /// both sides lock differently are dropped, so the next installation locks them again. The
+    /// result uses the older format version of both lockfiles. Dependants are not recounted.
+    pub fn merge(self, other: Self) -> miette::Result<Self> {
+        let version = self.version().min(other.version());
+
+        let mut packages: BTreeMap<(PackageName, Version), Vec<LockedPackage>> = BTreeMap::new();
+
+        for package in self.packages().chain(other.packages()) {
+            packages
+                .entry((package.name.clone(), package.version.clone()))
+                .or_default()
+                .push(package.clone());
+        }
+
+        let packages: Vec<_> = packages
+            .into_values()
+            .filter(|entries| {
+                let first = &entries[0];
+
+                let Some(other) = entries.iter().find(|other| !first.same_source(other)) else {
+                    return true;
+                };
+
+                tracing::warn!(
+                    "[warn] both sides lock {} {} from different sources: {} and {}, run `buffrs install` to lock it again",
+                    first.name,
+                    first.version,
+                    first.source(),
+                    other.source()
+                );
+
+                false
+            })
+            .flatten()
+            .collect();
+
+        let merged = WorkspaceLockfile::try_from(packages)?;
+
+        // Local packages locked differently by both sides map to `None`
+        let mut local: BTreeMap<(PackageName, PathBuf), Option<LockedLocalPackage>> =
+            BTreeMap::new();
+
+        for package in self.local_packages().chain(other.local_packages()) {
+            let key = (package.name.clone(), package.path.clone());
+
+            match local.get(&key) {
+                Some(Some(existing)) if existing != package => {
+                    tracing::warn!(
+                        "[warn] both sides lock {} ({}) with different contents, run `buffrs install` to lock it again",
+                        package.name,
+                        package.path.display()
+                    );
+
+                    local.insert(key, None);
+                }
+                Some(_) => {}
+                None => {
+                    local.insert(key, Some(package.clone()));
+                }
+            }
+        }
+
+        let local: Vec<_> = local.into_values().flatten().collect();
+
+        let merged = match self {
+            Self::Package(_) => {
+                let mut highest: BTreeMap<PackageName, LockedPackage> = BTreeMap::new();
+
+                // Packages are sorted by name and version, so the highest version comes last
+                for package in merged.packages.into_values() {
+                    if let Some(previous) = highest.insert(package.name.clone(), package.clone()) {
+                        tracing::warn!(
+                            "[warn] both sides lock {}, keeping version {} over {}",
+                            package.name,
+                            package.version,
+                            previous.version
+                        );
+                    }
+                }
+
+                Self::Package(
+                    PackageLockfile::from_iter(highest.into_values()).with_local_packages(local),
+                )
+            }
+            Self::Workspace(_) => Self::Workspace(merged.with_local_packages(local)),
+        };
+
+        Ok(merged.with_version(version))
+    }
+
     /// Returns true if this is a package lockfile
     pub fn is_package_lockfile(&self) -> bool {
         match self {
@@ -619,71 +1227,274 @@ impl From<Lockfile> for Vec<FileRequirement> {
 
 /// Aggregates locked packages from multiple workspace members into a workspace lockfile
 ///
-/// Merges packages by (name, version), deduplicating and summing dependants counts.
+/// Merges packages by (name, version). Dependants are expected to be counted across the whole
+/// workspace, so duplicates carry the same count. The dependencies of duplicates are merged.
+/// Duplicates locking the package from different sources are rejected, since neither of them can
+/// be preferred.
 impl TryFrom<Vec<LockedPackage>> for WorkspaceLockfile {
     type Error = miette::Report;
 
     fn try_from(locked_packages: Vec<LockedPackage>) -> Result<Self, Self::Error> {
-        use std::collections::BTreeMap;
-
-        let mut workspace_packages: BTreeMap<(PackageName, semver::Version), LockedPackage> =
+        let mut workspace_packages: BTreeMap<(PackageName, Version), LockedPackage> =
             BTreeMap::new();
 
         for locked in locked_packages {
             let key = (locked.name.clone(), locked.version.clone());
 
-            workspace_packages
-                .entry(key)
-                .and_modify(|existing| {
-                    // Same package (name, version) - sum dependants
-                    existing.dependants += locked.dependants;
+            let Some(existing) = workspace_packages.get_mut(&key) else {
+                workspace_packages.insert(key, locked);
+                continue;
+            };
 
-                    // Verify consistency of other fields
-                    if existing.registry != locked.registry {
-                        tracing::warn!(
-                            "registry mismatch for {}@{}: {} vs {}. Using first seen.",
-                            locked.name,
-                            locked.version,
-                            existing.registry,
-                            locked.registry
-                        );
-                    }
-                    if existing.digest != locked.digest {
-                        tracing::warn!(
-                            "digest mismatch for {}@{}: {} vs {}. Using first seen.",
-                            locked.name,
-                            locked.version,
-                            existing.digest,
-                            locked.digest
-                        );
-                    }
-                    // Dependencies are conceptually an unordered set for a
-                    // given (name, version) — compare without regard to order.
-                    let existing_deps: HashSet<_> = existing.dependencies.iter().collect();
-                    let locked_deps: HashSet<_> = locked.dependencies.iter().collect();
-                    if existing_deps != locked_deps {
-                        tracing::warn!(
-                            "dependencies mismatch for {}@{}: {:?} vs {:?}. Using first seen.",
-                            locked.name,
-                            locked.version,
-                            existing.dependencies,
-                            locked.dependencies
-                        );
-                    }
-                })
-                .or_insert(locked);
+            // Members may resolve different versions of a dependency, so edges are merged
+            let dependencies: Vec<_> = existing
+                .dependencies
+                .iter()
+                .chain(&locked.dependencies)
+                .cloned()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+
+            let dependants = existing.dependants.max(locked.dependants);
+
+            ensure!(
+                existing.same_source(&locked),
+                ConflictingSources {
+                    name: locked.name.clone(),
+                    version: locked.version.clone(),
+                    existing: existing.source(),
+                    locked: locked.source(),
+                }
+            );
+
+            existing.dependencies = dependencies;
+            existing.dependants = dependants;
         }
 
         Ok(Self::from_iter(workspace_packages.into_values()))
     }
 }
 
+/// The entries that differ between two lockfiles
+///
+/// Entries are identified by package name and version, or by package name and path for local
+/// packages. A changed identity is reported as a removed and an added entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockfileDiff {
+    /// Entries only present in the new lockfile
+    pub added: Vec<LockedPackage>,
+    /// Entries only present in the old lockfile
+    pub removed: Vec<LockedPackage>,
+    /// Entries present in both lockfiles with different contents, as `(old, new)`
+    pub changed: Vec<(LockedPackage, LockedPackage)>,
+    /// Local entries only present in the new lockfile
+    pub added_local: Vec<LockedLocalPackage>,
+    /// Local entries only present in the old lockfile
+    pub removed_local: Vec<LockedLocalPackage>,
+    /// Local entries present in both lockfiles with different contents, as `(old, new)`
+    pub changed_local: Vec<(LockedLocalPackage, LockedLocalPackage)>,
+}
+
+impl LockfileDiff {
+    /// Compares the entries of two lockfiles
+    pub fn new(old: &Lockfile, new: &Lockfile) -> Self {
+        fn index(lock: &Lockfile) -> BTreeMap<(PackageName, Version), LockedPackage> {
+            lock.packages()
+                .map(|package| {
+                    // Dependencies are an unordered set, see WorkspaceLockfile::try_from
+                    let mut package = package.clone();
+                    package.dependencies.sort();
+                    ((package.name.clone(), package.version.clone()), package)
+                })
+                .collect()
+        }
+
+        fn index_local(lock: &Lockfile) -> BTreeMap<(PackageName, PathBuf), LockedLocalPackage> {
+            lock.local_packages()
+                .map(|package| {
+                    (
+                        (package.name.clone(), package.path.clone()),
+                        package.clone(),
+                    )
+                })
+                .collect()
+        }
+
+        let (added, removed, changed) = compare(index(old), index(new));
+        let (added_local, removed_local, changed_local) =
+            compare(index_local(old), index_local(new));
+
+        Self {
+            added,
+            removed,
+            changed,
+            added_local,
+            removed_local,
+            changed_local,
+        }
+    }
+
+    /// Returns true if both lockfiles have identical entries
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.added_local.is_empty()
+            && self.removed_local.is_empty()
+            && self.changed_local.is_empty()
+    }
+}
+
+/// Splits two sets of entries into added, removed and changed entries
+fn compare<K: Ord, V: PartialEq>(
+    mut old: BTreeMap<K, V>,
+    new: BTreeMap<K, V>,
+) -> (Vec<V>, Vec<V>, Vec<(V, V)>) {
+    let mut added = vec![];
+    let mut changed = vec![];
+
+    for (key, entry) in new {
+        match old.remove(&key) {
+            None => added.push(entry),
+            Some(previous) if previous != entry => changed.push((previous, entry)),
+            Some(_) => {}
+        }
+    }
+
+    (added, old.into_values().collect(), changed)
+}
+
+impl fmt::Display for LockfileDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn join(dependencies: &[LockedDependency]) -> String {
+            let dependencies: Vec<String> = dependencies.iter().map(|d| d.to_string()).collect();
+            format!("[{}]", dependencies.join(", "))
+        }
+
+        fn edition(package: &LockedPackage) -> String {
+            package
+                .edition
+                .clone()
+                .map_or("none", <&str>::from)
+                .to_owned()
+        }
+
+        fn manifest(package: &LockedPackage) -> String {
+            package
+                .manifest
+                .as_ref()
+                .map_or_else(|| "none".to_owned(), Digest::to_string)
+        }
+
+        fn files(package: &LockedPackage) -> BTreeMap<&Path, String> {
+            package
+                .files
+                .iter()
+                .map(|file| (file.path.as_path(), file.digest.to_string()))
+                .collect()
+        }
+
+        for package in &self.removed {
+            writeln!(f, "- {} {}", package.name, package.version)?;
+        }
+
+        for package in &self.removed_local {
+            let path = package.path.display();
+            writeln!(f, "- {} {} ({path})", package.name, package.version)?;
+        }
+
+        for package in &self.added {
+            writeln!(f, "+ {} {}", package.name, package.version)?;
+        }
+
+        for package in &self.added_local {
+            let path = package.path.display();
+            writeln!(f, "+ {} {} ({path})", package.name, package.version)?;
+        }
+
+        for (old, new) in &self.changed {
+            let fields = [
+                (
+                    "registry",
+                    old.registry.to_string(),
+                    new.registry.to_string(),
+                ),
+                ("repository", old.repository.clone(), new.repository.clone()),
+                ("digest", old.digest.to_string(), new.digest.to_string()),
+                ("manifest", manifest(old), manifest(new)),
+                (
+                    "dependencies",
+                    join(&old.dependencies),
+                    join(&new.dependencies),
+                ),
+                (
+                    "dependants",
+                    old.dependants.to_string(),
+                    new.dependants.to_string(),
+                ),
+                ("edition", edition(old), edition(new)),
+            ];
+
+            for (field, old_value, new_value) in fields {
+                if old_value != new_value {
+                    writeln!(
+                        f,
+                        "~ {} {} {field}: {old_value} -> {new_value}",
+                        new.name, new.version
+                    )?;
+                }
+            }
+
+            let (old_files, new_files) = (files(old), files(new));
+            let paths: BTreeSet<_> = old_files.keys().chain(new_files.keys()).collect();
+
+            for path in paths {
+                let old_value = old_files.get(path).map_or("none", String::as_str);
+                let new_value = new_files.get(path).map_or("none", String::as_str);
+
+                if old_value != new_value {
+                    writeln!(
+                        f,
+                        "~ {} {} file {}: {old_value} -> {new_value}",
+                        new.name,
+                        new.version,
+                        path.display()
+                    )?;
+                }
+            }
+        }
+
+        for (old, new) in &self.changed_local {
+            let fields = [
+                ("version", old.version.to_string(), new.version.to_string()),
+                ("digest", old.digest.to_string(), new.digest.to_string()),
+            ];
+
+            for (field, old_value, new_value) in fields {
+                if old_value != new_value {
+                    writeln!(
+                        f,
+                        "~ {} ({}) {field}: {old_value} -> {new_value}",
+                        new.name,
+                        new.path.display()
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// A requirement from a lockfile on a specific file being available in order to build the
 /// overall graph. It's expected that when a file is downloaded, it's made available to buffrs
 /// by setting the filename to the digest in whatever download directory.
 #[derive(Serialize, Clone, PartialEq, Eq)]
 pub struct FileRequirement {
     pub(crate) package: PackageName,
+    #[serde(skip)]
+    pub(crate) version: Version,
     pub(crate) url: Url,
     pub(crate) digest: Digest,
 }
@@ -702,21 +1513,28 @@ impl FileRequirement {
         version: &Version,
         digest: &Digest,
     ) -> Self {
-        let mut url = url.clone();
-        let new_path = format!(
-            "{}/{}/{}/{}-{}.tgz",
-            url.path(),
-            repository,
-            name,
-            name,
-            version
-        );
+        let kind = url.kind();
+        let mut url = url.base_url();
+
+        // OCI registries serve the archive as a content addressed blob
+        let new_path = match kind {
+            RegistryKind::Oci => format!("{}/{}/{}/blobs/{}", url.path(), repository, name, digest),
+            _ => format!(
+                "{}/{}/{}/{}-{}.tgz",
+                url.path(),
+                repository,
+                name,
+                name,
+                version
+            ),
+        };
 
         url.set_path(&new_path);
 
         Self {
             package: name.to_owned(),
-            url: url.into(),
+            version: version.to_owned(),
+            url,
             digest: digest.clone(),
         }
     }
@@ -750,13 +1568,14 @@ impl From<&LockedPackage> for FileRequirement {
 mod tests {
     use std::{collections::BTreeMap, str::FromStr};
 
-    use semver::Version;
+    use semver::{Version, VersionReq};
 
     use crate::{io::File, package::PackageName, registry::RegistryUri};
 
     use super::{
-        Digest, DigestAlgorithm, FileRequirement, LockedDependency, LockedPackage, PackageLockfile,
-        WorkspaceLockfile,
+        Digest, DigestAlgorithm, Edition, FileRequirement, LockedDependency, LockedFile,
+        LockedLocalPackage, LockedPackage, Lockfile, LockfileDiff, LockfileVersion,
+        PackageLockfile, WorkspaceLockfile,
     };
 
     fn simple_lockfile() -> PackageLockfile {
@@ -773,9 +1592,12 @@ mod tests {
                         .unwrap(),
                         registry: RegistryUri::from_str("http://my-registry.com").unwrap(),
                         repository: "my-repo".to_owned(),
+                        manifest: None,
                         version: Version::new(0, 1, 0),
                         dependencies: Default::default(),
                         dependants: 1,
+                        edition: None,
+                        files: vec![],
                     },
                 ),
                 (
@@ -789,9 +1611,12 @@ mod tests {
                         .unwrap(),
                         registry: RegistryUri::from_str("http://my-registry.com").unwrap(),
                         repository: "my-other-repo".to_owned(),
+                        manifest: None,
                         version: Version::new(0, 2, 0),
                         dependencies: Default::default(),
                         dependants: 1,
+                        edition: None,
+                        files: vec![],
                     },
                 ),
                 (
@@ -805,9 +1630,12 @@ mod tests {
                         .unwrap(),
                         registry: RegistryUri::from_str("http://your-registry.com").unwrap(),
                         repository: "your-repo".to_owned(),
+                        manifest: None,
                         version: Version::new(0, 2, 0),
                         dependencies: Default::default(),
                         dependants: 1,
+                        edition: None,
+                        files: vec![],
                     },
                 ),
                 (
@@ -821,12 +1649,17 @@ mod tests {
                         .unwrap(),
                         registry: RegistryUri::from_str("http://your-registry.com").unwrap(),
                         repository: "your-other-repo".to_owned(),
+                        manifest: None,
                         version: Version::new(0, 2, 0),
                         dependencies: Default::default(),
                         dependants: 1,
+                        edition: None,
+                        files: vec![],
                     },
                 ),
             ]),
+            local: BTreeMap::new(),
+            version: LockfileVersion::LATEST,
         }
     }
 
@@ -1001,6 +1834,7 @@ mod tests {
             version: Version::new(1, 0, 0),
             registry: RegistryUri::from_str("https://my-registry.com").unwrap(),
             repository: "test-repo".to_string(),
+            manifest: None,
             digest: Digest::from_parts(
                 DigestAlgorithm::SHA256,
                 "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122",
@@ -1011,6 +1845,8 @@ mod tests {
                 Version::new(1, 5, 0),
             )],
             dependants: 2,
+            edition: None,
+            files: vec![],
         };
 
         let pkg2 = LockedPackage {
@@ -1018,6 +1854,7 @@ mod tests {
             version: Version::new(1, 5, 0),
             registry: RegistryUri::from_str("https://my-registry.com").unwrap(),
             repository: "test-repo".to_string(),
+            manifest: None,
             digest: Digest::from_parts(
                 DigestAlgorithm::SHA256,
                 "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3",
@@ -1025,14 +1862,17 @@ mod tests {
             .unwrap(),
             dependencies: vec![], // Leaf package
             dependants: 1,
+            edition: None,
+            files: vec![],
         };
 
         let lockfile = WorkspaceLockfile::from_iter(vec![pkg1, pkg2]);
 
         // Serialize to TOML
-        let serialized = toml::to_string(&super::RawWorkspaceLockfile {
-            version: 1,
+        let serialized = toml::to_string(&super::RawLockfile {
+            version: LockfileVersion::V1,
             packages: lockfile.packages.values().cloned().collect(),
+            local: vec![],
         })
         .unwrap();
 
@@ -1049,8 +1889,8 @@ mod tests {
         assert!(serialized.contains("dependants = 1"));
 
         // Verify round-trip deserialization
-        let raw: super::RawWorkspaceLockfile = toml::from_str(&serialized).unwrap();
-        assert_eq!(raw.version, 1);
+        let raw: super::RawLockfile<LockedPackage> = toml::from_str(&serialized).unwrap();
+        assert_eq!(raw.version, LockfileVersion::V1);
         assert_eq!(raw.packages.len(), 2);
 
         let restored = WorkspaceLockfile::from_iter(raw.packages);
@@ -1065,6 +1905,154 @@ mod tests {
         assert_eq!(found.unwrap().dependencies.len(), 1);
     }
 
+    #[test]
+    fn test_v1_lockfile_output() {
+        let mut lockfile = simple_lockfile().with_version(LockfileVersion::V1);
+
+        lockfile
+            .packages
+            .get_mut(&PackageName::unchecked("package1"))
+            .unwrap()
+            .dependencies = vec![LockedDependency::qualified(
+            PackageName::unchecked("package2"),
+            Version::new(0, 2, 0),
+        )];
+
+        let raw = super::RawLockfile::new(
+            lockfile.version,
+            lockfile.packages.values(),
+            lockfile.local.values(),
+        );
+
+        // The format written by buffrs before the introduction of v2 is kept byte for byte
+        let expected = r#"version = 1
+
+[[packages]]
+name = "package1"
+version = "0.1.0"
+digest = "sha256:c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122"
+registry = "http://my-registry.com/"
+repository = "my-repo"
+dependencies = ["package2 0.2.0"]
+dependants = 1
+
+[[packages]]
+name = "package2"
+version = "0.2.0"
+digest = "sha256:c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3"
+registry = "http://my-registry.com/"
+repository = "my-other-repo"
+dependencies = []
+dependants = 1
+
+[[packages]]
+name = "package3"
+version = "0.2.0"
+digest = "sha256:c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3"
+registry = "http://your-registry.com/"
+repository = "your-repo"
+dependencies = []
+dependants = 1
+
+[[packages]]
+name = "package4"
+version = "0.2.0"
+digest = "sha256:c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3"
+registry = "http://your-registry.com/"
+repository = "your-other-repo"
+dependencies = []
+dependants = 1
+"#;
+
+        assert_eq!(raw.to_toml().unwrap(), expected);
+
+        // Local packages are appended as a section which older versions of buffrs ignore
+        let lockfile = lockfile.with_local_packages(vec![LockedLocalPackage {
+            name: PackageName::unchecked("local-lib"),
+            version: Version::new(0, 1, 0),
+            path: "libs/local-lib".into(),
+            digest: Digest::from_parts(
+                DigestAlgorithm::SHA256,
+                "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122",
+            )
+            .unwrap(),
+            git: None,
+        }]);
+
+        let raw = super::RawLockfile::new(
+            lockfile.version,
+            lockfile.packages.values(),
+            lockfile.local.values(),
+        );
+
+        let local = r#"
+[[local]]
+name = "local-lib"
+version = "0.1.0"
+path = "libs/local-lib"
+digest = "sha256:c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122"
+"#;
+
+        assert_eq!(raw.to_toml().unwrap(), format!("{expected}{local}"));
+    }
+
+    #[tokio::test]
+    async fn test_lockfile_versions_round_trip() {
+        use tempfile::TempDir;
+
+        let mut lockfile = simple_lockfile();
+
+        let package1 = lockfile
+            .packages
+            .get_mut(&PackageName::unchecked("package1"))
+            .unwrap();
+
+        package1.edition = Some(Edition::latest());
+        package1.dependencies = vec![
+            LockedDependency::named(PackageName::unchecked("package2")),
+            LockedDependency::qualified(PackageName::unchecked("package3"), Version::new(0, 2, 0)),
+        ];
+        package1.files = vec![LockedFile {
+            path: "package1.proto".into(),
+            digest: package1.digest.clone(),
+        }];
+
+        for version in [LockfileVersion::V1, LockfileVersion::V2] {
+            let temp_dir = TempDir::new().unwrap();
+            let lockfile = lockfile.clone().with_version(version);
+
+            lockfile.save_to(temp_dir.path()).await.unwrap();
+
+            let contents = tokio::fs::read_to_string(temp_dir.path().join("Proto.lock"))
+                .await
+                .unwrap();
+
+            assert!(contents.starts_with(&format!("version = {}\n", u16::from(version))));
+
+            let loaded = PackageLockfile::load_from(temp_dir.path().join("Proto.lock"))
+                .await
+                .unwrap();
+
+            assert_eq!(loaded, lockfile);
+        }
+
+        // Downgrading drops the information only recorded by v2
+        let legacy = lockfile.with_version(LockfileVersion::V1);
+        let package1 = legacy.get(&PackageName::unchecked("package1")).unwrap();
+
+        assert_eq!(package1.edition, None);
+        assert!(package1.files.is_empty());
+        assert_eq!(package1.dependencies.len(), 2);
+    }
+
+    #[test]
+    fn test_unsupported_lockfile_version() {
+        let result = super::RawLockfile::parse("version = 3\npackages = []\n");
+
+        assert!(result.is_err());
+        assert!(format!("{:?}", result.err().unwrap()).contains("unsupported lockfile version 3"));
+    }
+
     #[test]
     fn test_workspace_lockfile_supports_multiple_versions() {
         // Create two versions of the same package
@@ -1073,6 +2061,7 @@ mod tests {
             version: Version::new(1, 0, 0),
             registry: RegistryUri::from_str("https://my-registry.com").unwrap(),
             repository: "test-repo".to_string(),
+            manifest: None,
             digest: Digest::from_parts(
                 DigestAlgorithm::SHA256,
                 "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122",
@@ -1080,6 +2069,8 @@ mod tests {
             .unwrap(),
             dependencies: vec![],
             dependants: 1,
+            edition: None,
+            files: vec![],
         };
 
         let pkg_v2 = LockedPackage {
@@ -1087,6 +2078,7 @@ mod tests {
             version: Version::new(2, 0, 0),
             registry: RegistryUri::from_str("https://my-registry.com").unwrap(),
             repository: "test-repo".to_string(),
+            manifest: None,
             digest: Digest::from_parts(
                 DigestAlgorithm::SHA256,
                 "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3",
@@ -1094,6 +2086,8 @@ mod tests {
             .unwrap(),
             dependencies: vec![],
             dependants: 1,
+            edition: None,
+            files: vec![],
         };
 
         let lockfile = WorkspaceLockfile::from_iter(vec![pkg_v1, pkg_v2]);
@@ -1117,6 +2111,39 @@ mod tests {
         assert_eq!(v2.unwrap().version, Version::new(2, 0, 0));
     }
 
+    #[test]
+    fn test_lockfile_find_matching() {
+        let locked = |version: Version, hex: &str| LockedPackage {
+            name: PackageName::unchecked("remote-lib"),
+            version,
+            registry: RegistryUri::from_str("https://registry.com").unwrap(),
+            repository: "test-repo".to_string(),
+            manifest: None,
+            digest: Digest::from_parts(DigestAlgorithm::SHA256, &hex.repeat(64)).unwrap(),
+            dependencies: vec![],
+            dependants: 1,
+            edition: None,
+            files: vec![],
+        };
+
+        let lockfile = Lockfile::Workspace(WorkspaceLockfile::from_iter(vec![
+            locked(Version::new(1, 5, 0), "a"),
+            locked(Version::new(2, 0, 0), "b"),
+        ]));
+
+        let name = PackageName::unchecked("remote-lib");
+        let find = |requirement: &str| {
+            lockfile
+                .find_matching(&name, &VersionReq::parse(requirement).unwrap())
+                .map(|locked| locked.version.clone())
+        };
+
+        assert_eq!(find("^1.0.0"), Some(Version::new(1, 5, 0)));
+        assert_eq!(find("^2.0.0"), Some(Version::new(2, 0, 0)));
+        assert_eq!(find(">=1.0.0"), Some(Version::new(2, 0, 0)));
+        assert_eq!(find("^3.0.0"), None);
+    }
+
     #[test]
     fn test_lockfile_package_returns_file_requirement() {
         let lockfile = simple_lockfile();
@@ -1153,6 +2180,7 @@ mod tests {
             version: Version::new(1, 0, 0),
             registry: RegistryUri::from_str("https://registry.example.com").unwrap(),
             repository: "repo".to_string(),
+            manifest: None,
             digest: Digest::from_parts(
                 DigestAlgorithm::SHA256,
                 "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122",
@@ -1160,6 +2188,8 @@ mod tests {
             .unwrap(),
             dependencies: vec![],
             dependants: 1,
+            edition: None,
+            files: vec![],
         };
         let lockfile = WorkspaceLockfile::from_iter(vec![pkg]);
         let resolved = super::Lockfile::Workspace(lockfile);
@@ -1172,4 +2202,156 @@ mod tests {
         let result = resolved.get(&PackageName::unchecked("ws-pkg"), &Version::new(2, 0, 0));
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_lockfile_without_exclusive_dependencies() {
+        let mut lock = simple_lockfile();
+
+        let package1 = PackageName::unchecked("package1");
+        let package2 = PackageName::unchecked("package2");
+        let package3 = PackageName::unchecked("package3");
+
+        // package1 is the only dependant of package2, while package3 is also required elsewhere
+        let dependencies = [&package2, &package3].map(|name| {
+            LockedDependency::qualified(name.clone(), lock.get(name).unwrap().version.clone())
+        });
+
+        lock.packages.get_mut(&package1).unwrap().dependencies = dependencies.to_vec();
+        lock.packages.get_mut(&package3).unwrap().dependants = 2;
+
+        let unlocked = Lockfile::Package(lock).without(std::slice::from_ref(&package1));
+
+        let retained: Vec<_> = unlocked
+            .packages()
+            .map(|pkg| pkg.name.to_string())
+            .collect();
+        assert_eq!(retained, vec!["package3", "package4"]);
+    }
+
+    #[test]
+    fn test_lockfile_diff() {
+        let old = simple_lockfile();
+        let mut new = simple_lockfile();
+
+        // Identical lockfiles
+        let diff = LockfileDiff::new(
+            &Lockfile::Package(old.clone()),
+            &Lockfile::Package(new.clone()),
+        );
+        assert!(diff.is_empty());
+
+        let package1 = PackageName::unchecked("package1");
+        let package2 = PackageName::unchecked("package2");
+
+        new.packages.remove(&package1);
+        new.packages.get_mut(&package2).unwrap().dependants = 2;
+        new.packages.insert(
+            PackageName::unchecked("package5"),
+            LockedPackage {
+                name: PackageName::unchecked("package5"),
+                version: Version::new(1, 0, 0),
+                ..old.get(&package1).unwrap().clone()
+            },
+        );
+
+        let local = LockedLocalPackage {
+            name: PackageName::unchecked("local-lib"),
+            version: Version::new(0, 1, 0),
+            path: "libs/local-lib".into(),
+            digest: old.get(&package1).unwrap().digest.clone(),
+            git: None,
+        };
+
+        let old = old.with_local_packages(vec![local.clone()]);
+        let new = new.with_local_packages(vec![LockedLocalPackage {
+            digest: old.get(&package2).unwrap().digest.clone(),
+            ..local
+        }]);
+
+        let diff = LockfileDiff::new(&Lockfile::Package(old.clone()), &Lockfile::Package(new));
+
+        assert_eq!(diff.removed, vec![old.get(&package1).unwrap().clone()]);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.to_string(),
+            "- package1 0.1.0\n\
+             + package5 1.0.0\n\
+             ~ package2 0.2.0 dependants: 1 -> 2\n\
+             ~ local-lib (libs/local-lib) digest: sha256:c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122 -> sha256:c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_package_lockfiles() {
+        let package1 = PackageName::unchecked("package1");
+        let package2 = PackageName::unchecked("package2");
+
+        let ours = simple_lockfile();
+        let mut theirs = simple_lockfile().with_version(LockfileVersion::V1);
+
+        theirs.packages.remove(&package1);
+        theirs.packages.get_mut(&package2).unwrap().version = Version::new(0, 3, 0);
+
+        let merged = Lockfile::Package(ours)
+            .merge(Lockfile::Package(theirs))
+            .unwrap();
+
+        assert_eq!(merged.version(), LockfileVersion::V1);
+        assert_eq!(merged.packages().count(), 4);
+        assert!(merged.get(&package1, &Version::new(0, 1, 0)).is_some());
+        assert!(merged.get(&package2, &Version::new(0, 3, 0)).is_some());
+        assert!(merged.get(&package2, &Version::new(0, 2, 0)).is_none());
+    }
+
+    #[test]
+    fn test_merge_drops_conflicting_entries() {
+        let package1 = PackageName::unchecked("package1");
+        let package2 = PackageName::unchecked("package2");
+
+        let local = LockedLocalPackage {
+            name: PackageName::unchecked("local-lib"),
+            version: Version::new(0, 1, 0),
+            path: "libs/local-lib".into(),
+            digest: Digest::from_parts(
+                DigestAlgorithm::SHA256,
+                "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353c122",
+            )
+            .unwrap(),
+            git: None,
+        };
+
+        let ours = simple_lockfile().with_local_packages(vec![local.clone()]);
+        let mut theirs = simple_lockfile().with_local_packages(vec![LockedLocalPackage {
+            digest: Digest::from_parts(
+                DigestAlgorithm::SHA256,
+                "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3",
+            )
+            .unwrap(),
+            ..local
+        }]);
+
+        theirs.packages.get_mut(&package1).unwrap().digest = Digest::from_parts(
+            DigestAlgorithm::SHA256,
+            "c109c6b120c525e6ea7b2db98335d39a3272f572ac86ba7b2d65c765c353bce3",
+        )
+        .unwrap();
+
+        theirs.packages.get_mut(&package2).unwrap().repository = "my-repo".to_owned();
+
+        // Neither side is preferred, so the next installation locks the entries again
+        for (ours, theirs) in [
+            (ours.clone(), theirs.clone()),
+            (theirs.clone(), ours.clone()),
+        ] {
+            let merged = Lockfile::Package(ours)
+                .merge(Lockfile::Package(theirs))
+                .unwrap();
+
+            assert_eq!(merged.packages().count(), 2);
+            assert!(merged.get(&package1, &Version::new(0, 1, 0)).is_none());
+            assert!(merged.get(&package2, &Version::new(0, 2, 0)).is_none());
+            assert_eq!(merged.local_packages().count(), 0);
+        }
+    }
 }
@@ -0,0 +1,121 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use miette::{bail, ensure};
+
+const OURS: &str = "<<<<<<<";
+const BASE: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const THEIRS: &str = ">>>>>>>";
+
+/// Returns true if the contents contain git conflict markers
+pub(crate) fn has_markers(contents: &str) -> bool {
+    contents.lines().any(|line| line.starts_with(OURS))
+}
+
+/// Splits contents with git conflict markers into both sides of the conflict
+///
+/// Lines outside of conflicts are part of both sides. The common ancestor written by the
+/// `diff3` and `zdiff3` conflict styles is discarded.
+pub(crate) fn split(contents: &str) -> miette::Result<(String, String)> {
+    #[derive(PartialEq)]
+    enum Section {
+        Common,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut section = Section::Common;
+    let mut ours = String::new();
+    let mut theirs = String::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let unexpected = || miette::miette!("unexpected conflict marker on line {}", index + 1);
+
+        if line.starts_with(OURS) {
+            ensure!(section == Section::Common, unexpected());
+            section = Section::Ours;
+        } else if line.starts_with(BASE) {
+            ensure!(section == Section::Ours, unexpected());
+            section = Section::Base;
+        } else if line.starts_with(SEPARATOR) && section != Section::Common {
+            ensure!(
+                matches!(section, Section::Ours | Section::Base),
+                unexpected()
+            );
+            section = Section::Theirs;
+        } else if line.starts_with(THEIRS) {
+            ensure!(section == Section::Theirs, unexpected());
+            section = Section::Common;
+        } else {
+            match section {
+                Section::Common => {
+                    ours.push_str(line);
+                    ours.push('\n');
+                    theirs.push_str(line);
+                    theirs.push('\n');
+                }
+                Section::Ours => {
+                    ours.push_str(line);
+                    ours.push('\n');
+                }
+                Section::Base => {}
+                Section::Theirs => {
+                    theirs.push_str(line);
+                    theirs.push('\n');
+                }
+            }
+        }
+    }
+
+    if section != Section::Common {
+        bail!("unterminated conflict at the end of the file");
+    }
+
+    Ok((ours, theirs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_markers, split};
+
+    #[test]
+    fn splits_conflicts() {
+        let contents = "version = 2\n\
+                        <<<<<<< HEAD\n\
+                        a = 1\n\
+                        ||||||| base\n\
+                        a = 0\n\
+                        =======\n\
+                        a = 2\n\
+                        >>>>>>> branch\n\
+                        b = 3\n";
+
+        assert!(has_markers(contents));
+
+        let (ours, theirs) = split(contents).unwrap();
+
+        assert_eq!(ours, "version = 2\na = 1\nb = 3\n");
+        assert_eq!(theirs, "version = 2\na = 2\nb = 3\n");
+    }
+
+    #[test]
+    fn rejects_unterminated_conflicts() {
+        let contents = "<<<<<<< HEAD\na = 1\n=======\na = 2\n";
+
+        assert!(split(contents).is_err());
+        assert!(!has_markers("a = 1\n"));
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::{fmt, str::FromStr};
 
+use base64::{Engine, prelude::BASE64_STANDARD};
 use ring::digest;
 use serde::{Deserialize, Serialize, de::Visitor};
 use strum::{Display, EnumString};
@@ -22,7 +23,18 @@ use thiserror::Error;
 /// Supported types of digest algorithms.
 // Do not reorder variants; the ordering is significant, see #38 and #106.
 #[derive(
-    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, EnumString, Display,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    EnumString,
+    Display,
 )]
 pub enum DigestAlgorithm {
     /// SHA-2 with 256 bits
@@ -80,7 +92,7 @@ fn can_display_digest_algorithm() {
 /// assert_eq!(digest, format!("{digest}").parse().unwrap());
 /// ```
 // Do not reorder fields: the ordering is significant, see #38 and #106.
-#[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Debug)]
+#[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
 pub struct Digest {
     /// Algorithm used to create digest.
     algorithm: DigestAlgorithm,
@@ -104,6 +116,20 @@ impl Digest {
     pub fn as_bytes(&self) -> &[u8] {
         &self.digest
     }
+
+    /// Digest value as a hexadecimal string, without the algorithm.
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.digest)
+    }
+
+    /// Digest in the format of [Subresource Integrity](https://www.w3.org/TR/SRI/) metadata.
+    pub fn to_sri(&self) -> String {
+        format!(
+            "{}-{}",
+            self.algorithm,
+            BASE64_STANDARD.encode(&self.digest)
+        )
+    }
 }
 
 /// Error parsing a [`DigestAlgorithm`].
@@ -222,6 +248,16 @@ mod tests {
         assert_eq!(digest.to_string(), HELLO_DIGEST);
     }
 
+    #[test]
+    fn can_format_sri() {
+        let digest: Digest = HELLO_DIGEST.parse().unwrap();
+        assert_eq!(digest.to_hex(), &HELLO_DIGEST[7..]);
+        assert_eq!(
+            digest.to_sri(),
+            "sha256-LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ="
+        );
+    }
+
     #[test]
     fn can_serialize() {
         let digest: Digest = HELLO_DIGEST.parse().unwrap();
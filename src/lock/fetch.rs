@@ -0,0 +1,202 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write as _;
+
+use strum::{Display, EnumString};
+
+use super::FileRequirement;
+
+/// Output formats of the locked file requirements
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum FilesFormat {
+    /// JSON list of file requirements
+    #[default]
+    Json,
+    /// Nix function returning an attribute set of `fetchurl` derivations
+    Nix,
+    /// Starlark macro declaring `http_file` and `http_archive` repositories
+    Bazel,
+}
+
+/// Build file of the extracted packages, exposing their protocol buffer files
+const BAZEL_BUILD_FILE: &str = r#"filegroup(
+    name = "protos",
+    srcs = glob(["**/*.proto"]),
+    visibility = ["//visibility:public"],
+)
+"#;
+
+impl FileRequirement {
+    /// Identifies the package by name and version, which is stable across registries
+    fn identifier(&self) -> String {
+        format!("{}-{}", self.package, self.version)
+    }
+
+    /// Name of the package archive
+    fn file_name(&self) -> String {
+        format!("{}.tgz", self.identifier())
+    }
+
+    /// Name of the Bazel repository of the package, which may only contain word characters
+    fn repository_name(&self) -> String {
+        let name = format!("buffrs_{}_{}", self.package, self.version);
+
+        name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    /// Renders file requirements as a Nix function taking `fetchurl`
+    ///
+    /// Attributes are named after the package and version, so the result can be imported with
+    /// `callPackage` and the archives linked into a buffrs cache.
+    pub fn to_nix(requirements: &[Self]) -> String {
+        let mut out = String::from("{ fetchurl }:\n\n{\n");
+
+        for requirement in requirements {
+            let _ = writeln!(
+                out,
+                "  {} = fetchurl {{",
+                nix_string(&requirement.identifier())
+            );
+            let _ = writeln!(out, "    url = {};", nix_string(requirement.url.as_str()));
+            let _ = writeln!(
+                out,
+                "    sha256 = {};",
+                nix_string(&requirement.digest.to_hex())
+            );
+            let _ = writeln!(out, "  }};");
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders file requirements as a Starlark macro for Bazel
+    ///
+    /// Each package is declared twice: as an `http_file` repository holding the archive, which can
+    /// populate a buffrs cache, and as an `http_archive` repository exposing the extracted
+    /// protocol buffer files as the `protos` target.
+    pub fn to_bazel(requirements: &[Self]) -> String {
+        let mut out = String::from(
+            "load(\"@bazel_tools//tools/build_defs/repo:http.bzl\", \"http_archive\", \"http_file\")\n\n",
+        );
+
+        let _ = writeln!(out, "_BUILD_FILE = \"\"\"\n{BAZEL_BUILD_FILE}\"\"\"\n");
+
+        out.push_str("def buffrs_dependencies():\n");
+        out.push_str("    \"\"\"Declares the packages locked by Proto.lock\"\"\"\n");
+
+        if requirements.is_empty() {
+            out.push_str("    pass\n");
+        }
+
+        for requirement in requirements {
+            let name = requirement.repository_name();
+            let url = starlark_string(requirement.url.as_str());
+            let integrity = starlark_string(&requirement.digest.to_sri());
+
+            let _ = writeln!(out);
+            let _ = writeln!(out, "    http_file(");
+            let _ = writeln!(out, "        name = \"{name}_tgz\",");
+            let _ = writeln!(out, "        urls = [{url}],");
+            let _ = writeln!(out, "        integrity = {integrity},");
+            let _ = writeln!(
+                out,
+                "        downloaded_file_path = {},",
+                starlark_string(&requirement.file_name())
+            );
+            let _ = writeln!(out, "    )");
+            let _ = writeln!(out);
+            let _ = writeln!(out, "    http_archive(");
+            let _ = writeln!(out, "        name = \"{name}\",");
+            let _ = writeln!(out, "        urls = [{url}],");
+            let _ = writeln!(out, "        integrity = {integrity},");
+            let _ = writeln!(out, "        type = \"tar.gz\",");
+            let _ = writeln!(out, "        build_file_content = _BUILD_FILE,");
+            let _ = writeln!(out, "    )");
+        }
+
+        out
+    }
+}
+
+/// Quotes a string for use in Nix expressions
+fn nix_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "\\${");
+
+    format!("\"{escaped}\"")
+}
+
+/// Quotes a string for use in Starlark files
+fn starlark_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use semver::Version;
+
+    use super::*;
+    use crate::{
+        lock::{Digest, DigestAlgorithm},
+        package::PackageName,
+        registry::RegistryUri,
+    };
+
+    fn requirement() -> FileRequirement {
+        FileRequirement::new(
+            &RegistryUri::from_str("https://buffrs.local/artifactory").unwrap(),
+            &"test-repo".to_owned(),
+            &PackageName::unchecked("remote-lib"),
+            &Version::new(0, 1, 0),
+            &Digest::from_parts(
+                DigestAlgorithm::SHA256,
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn renders_nix() {
+        assert_eq!(
+            FileRequirement::to_nix(&[requirement()]),
+            "{ fetchurl }:\n\n\
+             {\n  \"remote-lib-0.1.0\" = fetchurl {\n    \
+             url = \"https://buffrs.local/artifactory/test-repo/remote-lib/remote-lib-0.1.0.tgz\";\n    \
+             sha256 = \"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\";\n  \
+             };\n}\n"
+        );
+    }
+
+    #[test]
+    fn renders_bazel() {
+        let bazel = FileRequirement::to_bazel(&[requirement()]);
+
+        assert!(bazel.contains("name = \"buffrs_remote_lib_0_1_0\","));
+        assert!(bazel.contains("name = \"buffrs_remote_lib_0_1_0_tgz\","));
+        assert!(
+            bazel.contains("integrity = \"sha256-LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=\",")
+        );
+        assert!(FileRequirement::to_bazel(&[]).ends_with("    pass\n"));
+    }
+}
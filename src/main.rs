@@ -12,17 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::num::NonZeroUsize;
+
 use clap::{Parser, Subcommand};
 use miette::{WrapErr, miette};
 use semver::Version;
 
 use buffrs::{
     command,
+    lock::FilesFormat,
     logs::BuffrsEventFormatter,
     manifest::{MANIFEST_FILE, Manifest},
-    operations::install::NetworkMode,
+    operations::{
+        install::NetworkMode,
+        tree::{GraphFormat, TreeOptions},
+    },
     package::{PackageName, PackageStore, PackageType},
     registry::RegistryUri,
+    resolver::DEFAULT_CONCURRENCY,
 };
 
 #[derive(Parser)]
@@ -74,7 +81,7 @@ enum Command {
 
     /// Adds dependencies to a manifest file
     Add {
-        /// Artifactory url (e.g. https://<domain>/artifactory)
+        /// Registry url (e.g. https://<domain>/artifactory, index+https://<domain>/<path>, oci://<domain>/<namespace>, s3://<bucket>/<prefix> or file:///<path>)
         #[clap(long)]
         registry: RegistryUri,
         /// Dependency to add (Format <repository>/<package>@<version>
@@ -110,7 +117,7 @@ enum Command {
 
     /// Packages and uploads this api to the registry
     Publish {
-        /// Artifactory url (e.g. https://<domain>/artifactory)
+        /// Registry url (e.g. https://<domain>/artifactory, index+https://<domain>/<path>, oci://<domain>/<namespace>, s3://<bucket>/<prefix> or file:///<path>)
         #[clap(long)]
         registry: RegistryUri,
         /// Destination repository for the release
@@ -146,6 +153,32 @@ enum Command {
         #[clap(long)]
         #[arg(default_value_t = false)]
         offline: bool,
+        /// Fail instead of modifying the lockfile.
+        ///
+        /// Useful in CI to detect lockfiles that are out of date with the manifest.
+        #[clap(long)]
+        locked: bool,
+        /// Equivalent to --locked and --offline.
+        #[clap(long)]
+        frozen: bool,
+        /// Maximum number of packages downloaded at the same time
+        #[clap(long, short = 'j', env = "BUFFRS_JOBS", default_value_t = DEFAULT_CONCURRENCY)]
+        jobs: NonZeroUsize,
+    },
+    /// Updates locked dependencies to the newest versions allowed by the manifest
+    Update {
+        /// Packages to update (defaults to all locked packages)
+        packages: Vec<PackageName>,
+        /// Print the changes without modifying the lockfile
+        #[clap(long)]
+        dry_run: bool,
+        /// Indicate whether access time information is preserved when installing a local.
+        #[clap(long)]
+        #[arg(default_value_t = true)]
+        preserve_local_mtime: bool,
+        /// Maximum number of packages downloaded at the same time
+        #[clap(long, short = 'j', env = "BUFFRS_JOBS", default_value_t = DEFAULT_CONCURRENCY)]
+        jobs: NonZeroUsize,
     },
     /// Uninstalls dependencies
     Uninstall,
@@ -154,15 +187,63 @@ enum Command {
     #[clap(alias = "ls")]
     List,
 
+    /// Prints the resolved dependency graph as a tree
+    Tree {
+        /// Print the packages depending on the given package instead
+        #[clap(long, short = 'i')]
+        invert: Option<PackageName>,
+        /// Maximum depth of the printed tree
+        #[clap(long, short = 'd')]
+        depth: Option<usize>,
+        /// Output format: text, dot, mermaid or json
+        #[clap(long, short = 'f', default_value = "text")]
+        format: GraphFormat,
+    },
+    /// Explains why a package is part of the dependency graph
+    Why {
+        /// Package to print the dependency paths of
+        package: PackageName,
+    },
+
+    /// Searches a registry for packages
+    Search {
+        /// Term contained in the names of the packages
+        term: String,
+        /// Registry url (e.g. https://<domain>/artifactory, index+https://<domain>/<path>, oci://<domain>/<namespace>, s3://<bucket>/<prefix> or file:///<path>)
+        #[clap(long)]
+        registry: RegistryUri,
+        /// Only search this repository
+        #[clap(long)]
+        repository: Option<String>,
+        /// Print the results as JSON
+        #[clap(long)]
+        json: bool,
+    },
+    /// Prints the published manifest and files of a package
+    Info {
+        /// Package to describe (Format <repository>/<package>[@<version>])
+        package: String,
+        /// Registry url (e.g. https://<domain>/artifactory, index+https://<domain>/<path>, oci://<domain>/<namespace>, s3://<bucket>/<prefix> or file:///<path>)
+        #[clap(long)]
+        registry: RegistryUri,
+        /// Print the package information as JSON
+        #[clap(long)]
+        json: bool,
+    },
+
     /// Logs you in for a registry
+    ///
+    /// Credentials are accepted for every kind of registry except filesystem registries. Only
+    /// Artifactory tokens are verified when logging in, the credentials of other registries are
+    /// stored without verification.
     Login {
-        /// Artifactory url (e.g. https://<domain>/artifactory)
+        /// Registry url (e.g. https://<domain>/artifactory, oci://<host> or s3://<bucket>)
         #[clap(long)]
         registry: RegistryUri,
     },
     /// Logs you out from a registry
     Logout {
-        /// Artifactory url (e.g. https://<domain>/artifactory)
+        /// Registry url (e.g. https://<domain>/artifactory, oci://<host> or s3://<bucket>)
         #[clap(long)]
         registry: RegistryUri,
     },
@@ -176,10 +257,28 @@ enum Command {
 
 #[derive(Subcommand)]
 enum LockfileCommand {
-    /// Prints the file requirements derived from the lockfile serialized as JSON
+    /// Prints the file requirements derived from the lockfile
+    ///
+    /// This is useful for consumption of the lockfile in other programs and build systems.
+    PrintFiles {
+        /// Output format: json, nix or bazel
+        #[clap(long, short = 'f', default_value = "json")]
+        format: FilesFormat,
+    },
+    /// Upgrades the lockfile to the latest format version
+    ///
+    /// Locked packages are read from the cache or downloaded to record the information missing
+    /// from older formats. Resolved versions are not changed.
+    Migrate,
+    /// Verifies the cache and the vendored packages against the lockfile
+    ///
+    /// Reports tampered, missing and extra files and fails if there are any.
+    Verify,
+    /// Resolves git merge conflicts in the lockfile
     ///
-    /// This is useful for consumption of the lockfile in other programs.
-    PrintFiles,
+    /// Merges the packages locked by both sides of the conflicts, drops the entries the manifest
+    /// no longer depends on and writes back a clean lockfile.
+    ResolveConflicts,
 }
 
 #[tokio::main(flavor = "current_thread")]
@@ -279,26 +378,77 @@ async fn main() -> miette::Result<()> {
         Command::Install {
             preserve_local_mtime,
             offline,
+            locked,
+            frozen,
+            jobs,
         } => {
-            let network_mode = if offline {
+            let network_mode = if offline || frozen {
                 NetworkMode::Offline
             } else {
                 NetworkMode::Online
             };
-            command::install(preserve_local_mtime, network_mode)
+            command::install(preserve_local_mtime, network_mode, jobs, locked || frozen)
                 .await
                 .wrap_err(miette!("failed to install dependencies for `{package}`"))
         }
+        Command::Update {
+            packages,
+            dry_run,
+            preserve_local_mtime,
+            jobs,
+        } => command::update(packages, dry_run, preserve_local_mtime, jobs)
+            .await
+            .wrap_err(miette!("failed to update dependencies for `{package}`")),
         Command::Uninstall => command::uninstall()
             .await
             .wrap_err(miette!("failed to uninstall dependencies for `{package}`")),
         Command::List => command::list().await.wrap_err(miette!(
             "failed to list installed protobuf files for `{package}`"
         )),
-        Command::Lock { command } => match command {
-            LockfileCommand::PrintFiles => command::lock::print_files().await.wrap_err(miette!(
-                "failed to print locked file requirements of `{package}`"
+        Command::Tree {
+            invert,
+            depth,
+            format,
+        } => command::tree(TreeOptions { invert, depth }, format)
+            .await
+            .wrap_err(miette!(
+                "failed to print the dependency tree of `{package}`"
             )),
+        Command::Why { package: name } => command::why(&name).await.wrap_err(miette!(
+            "failed to explain why `{name}` is a dependency of `{package}`"
+        )),
+        Command::Search {
+            term,
+            registry,
+            repository,
+            json,
+        } => command::search(registry.to_owned(), &term, repository, json)
+            .await
+            .wrap_err(miette!("failed to search `{registry}` for `{term}`")),
+        Command::Info {
+            package: locator,
+            registry,
+            json,
+        } => command::info(registry.to_owned(), &locator, json)
+            .await
+            .wrap_err(miette!(
+                "failed to retrieve information about `{locator}` from `{registry}`"
+            )),
+        Command::Lock { command } => match command {
+            LockfileCommand::PrintFiles { format } => {
+                command::lock::print_files(format).await.wrap_err(miette!(
+                    "failed to print locked file requirements of `{package}`"
+                ))
+            }
+            LockfileCommand::Migrate => command::lock::migrate()
+                .await
+                .wrap_err(miette!("failed to migrate the lockfile of `{package}`")),
+            LockfileCommand::Verify => command::lock::verify()
+                .await
+                .wrap_err(miette!("failed to verify the installation of `{package}`")),
+            LockfileCommand::ResolveConflicts => command::lock::resolve_conflicts().await.wrap_err(
+                miette!("failed to resolve the lockfile conflicts of `{package}`"),
+            ),
         },
     }
 }
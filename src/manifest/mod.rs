@@ -41,7 +41,7 @@ pub const MANIFEST_FILE: &str = "Proto.toml";
 pub const CANARY_EDITION: &str = concat!("0.", env!("CARGO_PKG_VERSION_MINOR"));
 
 /// Edition of the buffrs manifest
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
 #[serde(into = "&str", from = "&str")]
 pub enum Edition {
     /// The canary edition of manifests
@@ -146,6 +146,14 @@ impl Manifest {
         }
     }
 
+    /// Returns the dependency patches declared at the root of the project
+    pub fn patches(&self) -> &[Dependency] {
+        match self {
+            Manifest::Package(manifest) => manifest.patches(),
+            Manifest::Workspace(manifest) => manifest.patches(),
+        }
+    }
+
     /// Returns the packages manifest if correct type, errs otherwise
     pub fn to_package_manifest(self) -> miette::Result<PackagesManifest> {
         match self {
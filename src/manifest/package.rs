@@ -23,6 +23,7 @@ use async_trait::async_trait;
 use miette::{Context, IntoDiagnostic};
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
+use url::Url;
 
 use super::Edition;
 use super::MANIFEST_FILE;
@@ -31,6 +32,7 @@ use crate::{
     ManagedFile,
     errors::DeserializationError,
     io::File,
+    lock::Digest,
     package::{PackageName, PackageType},
     registry::RegistryUri,
 };
@@ -44,6 +46,10 @@ pub struct PackagesManifest {
     pub package: Option<PackageManifest>,
     /// List of packages the root package depends on
     pub dependencies: Option<Vec<Dependency>>,
+    /// Packages replacing every dependency of the same name in the dependency graph
+    ///
+    /// Only honored in the manifest that dependencies are resolved for.
+    pub patch: Option<Vec<Dependency>>,
 }
 
 impl PackagesManifest {
@@ -53,6 +59,7 @@ impl PackagesManifest {
             edition: Edition::latest(),
             package: None,
             dependencies: None,
+            patch: None,
         }
     }
 
@@ -65,6 +72,11 @@ impl PackagesManifest {
             .collect()
     }
 
+    /// Returns the dependency patches declared in the manifest
+    pub fn patches(&self) -> &[Dependency] {
+        self.patch.as_deref().unwrap_or_default()
+    }
+
     /// Clones the Manifest but replaces the dependencies with a given Vec
     pub fn with_dependencies(&self, dependencies: Vec<Dependency>) -> Self {
         Self {
@@ -80,7 +92,7 @@ impl PackagesManifest {
 
     /// Gets a list of all local dependencies
     pub fn get_remote_dependencies(&self) -> Vec<Dependency> {
-        self.get_dependencies_of_type(|d| !d.manifest.is_local())
+        self.get_dependencies_of_type(|d| !d.manifest.is_local() && !d.manifest.is_git())
     }
 
     /// Gets a list of all dependencies
@@ -155,6 +167,7 @@ pub struct PackagesManifestBuilder {
     edition: Edition,
     package: Option<PackageManifest>,
     dependencies: Option<Vec<Dependency>>,
+    patch: Option<Vec<Dependency>>,
 }
 
 impl PackagesManifestBuilder {
@@ -176,12 +189,19 @@ impl PackagesManifestBuilder {
         self
     }
 
+    /// Sets the dependency patches
+    pub fn patch(mut self, patch: Vec<Dependency>) -> Self {
+        self.patch = Some(patch);
+        self
+    }
+
     /// Builds the PackagesManifest
     pub fn build(self) -> PackagesManifest {
         PackagesManifest {
             edition: self.edition,
             package: self.package,
             dependencies: self.dependencies,
+            patch: self.patch,
         }
     }
 }
@@ -295,6 +315,13 @@ impl Display for Dependency {
                 "{}/{}@{}",
                 manifest.repository, self.package, manifest.version
             ),
+            DependencyManifest::Git(manifest) => match &manifest.rev {
+                Some(rev) => write!(f, "{}@{}#{}", self.package, manifest.git, rev),
+                None => write!(f, "{}@{}", self.package, manifest.git),
+            },
+            DependencyManifest::Tarball(manifest) => {
+                write!(f, "{}@{}", self.package, manifest.url)
+            }
             DependencyManifest::Local(manifest) => {
                 write!(f, "{}@{}", self.package, manifest.path.display())
             }
@@ -311,6 +338,12 @@ pub type DependencyMap = HashMap<PackageName, DependencyManifest>;
 pub enum DependencyManifest {
     /// A remote dependency from artifactory
     Remote(RemoteDependencyManifest),
+    /// A dependency located in a git repository
+    ///
+    /// Declared before local dependencies, which only require a `path`.
+    Git(GitDependencyManifest),
+    /// A dependency downloaded as a tarball, verified by its digest
+    Tarball(TarballDependencyManifest),
     /// A local dependency located on the filesystem
     Local(LocalDependencyManifest),
 }
@@ -319,6 +352,14 @@ impl DependencyManifest {
     pub(crate) fn is_local(&self) -> bool {
         matches!(self, DependencyManifest::Local(_))
     }
+
+    pub(crate) fn is_git(&self) -> bool {
+        matches!(self, DependencyManifest::Git(_))
+    }
+
+    pub(crate) fn is_tarball(&self) -> bool {
+        matches!(self, DependencyManifest::Tarball(_))
+    }
 }
 
 /// Manifest format for dependencies
@@ -351,6 +392,81 @@ impl From<LocalDependencyManifest> for DependencyManifest {
     }
 }
 
+/// Manifest format for dependencies located in a git repository
+#[derive(Debug, Clone, Hash, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GitDependencyManifest {
+    /// URL of the git repository
+    #[serde(deserialize_with = "deserialize_git_url")]
+    pub git: String,
+    /// Branch, tag or commit to check out, defaults to the default branch of the repository
+    #[serde(
+        default,
+        deserialize_with = "deserialize_git_rev",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub rev: Option<String>,
+    /// Path of the buffrs package inside the repository, defaults to its root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl From<GitDependencyManifest> for DependencyManifest {
+    fn from(value: GitDependencyManifest) -> Self {
+        Self::Git(value)
+    }
+}
+
+/// Rejects git URLs which would be passed to git as an option
+fn deserialize_git_url<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let url = String::deserialize(deserializer)?;
+
+    if url.starts_with('-') {
+        return Err(serde::de::Error::custom(format!(
+            "git URL {url} must not start with '-'"
+        )));
+    }
+
+    Ok(url)
+}
+
+/// Rejects git revisions which would be passed to git as an option
+fn deserialize_git_rev<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let rev = Option::<String>::deserialize(deserializer)?;
+
+    if let Some(rev) = rev.as_ref().filter(|rev| rev.starts_with('-')) {
+        return Err(serde::de::Error::custom(format!(
+            "git revision {rev} must not start with '-'"
+        )));
+    }
+
+    Ok(rev)
+}
+
+/// Manifest format for dependencies downloaded as a tarball
+#[derive(Debug, Clone, Hash, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TarballDependencyManifest {
+    /// URL of the tarball
+    pub url: Url,
+    /// Digest the tarball has to match, e.g. `sha256:...`
+    pub digest: Digest,
+    /// Version of the package, only required for tarballs without a manifest whose file name
+    /// does not contain it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+}
+
+impl From<TarballDependencyManifest> for DependencyManifest {
+    fn from(value: TarballDependencyManifest) -> Self {
+        Self::Tarball(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -428,6 +544,7 @@ mod tests {
             edition: Edition::Canary,
             package: None,
             dependencies: None,
+            patch: None,
         };
         assert_eq!(manifest.get_dependency_package_names(), vec![]);
     }
@@ -554,6 +671,177 @@ mod tests {
         assert_eq!(manifest.dependencies.as_ref().unwrap().len(), 1);
     }
 
+    #[test]
+    fn packages_manifest_from_str_with_git_dependencies() {
+        let toml = r#"
+                edition = "0.12"
+
+                [package]
+                type = "api"
+                name = "test"
+                version = "1.0.0"
+
+                [dependencies]
+                pinned = { git = "https://example.com/protos.git", rev = "abc123", path = "pinned" }
+                head = { git = "https://example.com/protos.git" }
+                local = { path = "../local" }
+            "#;
+
+        let manifest = PackagesManifest::from_str(toml).expect("should parse");
+
+        let mut dependencies = manifest.dependencies.clone().unwrap();
+        dependencies.sort_by(|a, b| a.package.cmp(&b.package));
+
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|d| d.manifest.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                GitDependencyManifest {
+                    git: "https://example.com/protos.git".to_string(),
+                    rev: None,
+                    path: None,
+                }
+                .into(),
+                LocalDependencyManifest {
+                    path: PathBuf::from("../local"),
+                }
+                .into(),
+                GitDependencyManifest {
+                    git: "https://example.com/protos.git".to_string(),
+                    rev: Some("abc123".to_string()),
+                    path: Some(PathBuf::from("pinned")),
+                }
+                .into(),
+            ]
+        );
+
+        assert_eq!(manifest.get_local_dependencies().len(), 1);
+        assert!(manifest.get_remote_dependencies().is_empty());
+
+        let serialized: String = manifest.clone().try_into().expect("should serialize");
+        let reparsed = PackagesManifest::from_str(&serialized).expect("should parse");
+
+        let mut reparsed = reparsed.dependencies.unwrap();
+        reparsed.sort_by(|a, b| a.package.cmp(&b.package));
+
+        assert_eq!(reparsed, dependencies);
+    }
+
+    #[test]
+    fn packages_manifest_rejects_git_urls_starting_with_dash() {
+        let toml = r#"
+                edition = "0.12"
+
+                [dependencies]
+                evil = { git = "--upload-pack=touch /tmp/pwned" }
+            "#;
+
+        assert!(PackagesManifest::from_str(toml).is_err());
+    }
+
+    #[test]
+    fn packages_manifest_rejects_git_revisions_starting_with_dash() {
+        let toml = r#"
+                edition = "0.12"
+
+                [dependencies]
+                evil = { git = "https://example.com/protos.git", rev = "--output=/tmp/pwned" }
+            "#;
+
+        assert!(PackagesManifest::from_str(toml).is_err());
+    }
+
+    #[test]
+    fn packages_manifest_from_str_with_tarball_dependencies() {
+        let digest = "sha256:c2f2bd85d2bb2bc12bd3d6c6e4ddf7d1c7ed1b4a1ec5dba2f0a84d8e2b71f38b";
+        let toml = format!(
+            r#"
+                edition = "0.12"
+
+                [package]
+                type = "api"
+                name = "test"
+                version = "1.0.0"
+
+                [dependencies]
+                vendor = {{ url = "https://example.com/vendor-1.2.0.tgz", digest = "{digest}" }}
+            "#
+        );
+
+        let manifest = PackagesManifest::from_str(&toml).expect("should parse");
+
+        assert_eq!(
+            manifest.dependencies.as_ref().unwrap()[0].manifest,
+            TarballDependencyManifest {
+                url: Url::parse("https://example.com/vendor-1.2.0.tgz").unwrap(),
+                digest: Digest::from_str(digest).unwrap(),
+                version: None,
+            }
+            .into()
+        );
+
+        assert!(manifest.get_local_dependencies().is_empty());
+        assert_eq!(manifest.get_remote_dependencies().len(), 1);
+
+        let serialized: String = manifest.clone().try_into().expect("should serialize");
+        let reparsed = PackagesManifest::from_str(&serialized).expect("should parse");
+
+        assert_eq!(reparsed.dependencies, manifest.dependencies);
+    }
+
+    #[test]
+    fn packages_manifest_from_str_with_patch() {
+        let toml = r#"
+                edition = "0.12"
+
+                [package]
+                type = "api"
+                name = "test"
+                version = "1.0.0"
+
+                [dependencies]
+
+                [patch]
+                local = { path = "../local" }
+
+                [patch.remote]
+                version = "=2.0.0"
+                registry = "https://registry.example.com"
+                repository = "fork"
+            "#;
+
+        let manifest = PackagesManifest::from_str(toml).expect("should parse");
+
+        let mut patches = manifest.patches().to_vec();
+        patches.sort_by(|a, b| a.package.cmp(&b.package));
+
+        assert_eq!(
+            patches,
+            vec![
+                Dependency {
+                    package: PackageName::from_str("local").unwrap(),
+                    manifest: LocalDependencyManifest {
+                        path: PathBuf::from("../local"),
+                    }
+                    .into(),
+                },
+                Dependency::new(
+                    RegistryUri::from_str("https://registry.example.com").unwrap(),
+                    "fork".to_string(),
+                    PackageName::from_str("remote").unwrap(),
+                    VersionReq::from_str("=2.0.0").unwrap(),
+                ),
+            ]
+        );
+
+        let serialized: String = manifest.clone().try_into().expect("should serialize");
+        let reparsed = PackagesManifest::from_str(&serialized).expect("should parse");
+
+        assert_eq!(reparsed.patches().len(), 2);
+    }
+
     #[test]
     fn packages_manifest_to_raw_manifest() {
         let manifest = PackagesManifest::builder()
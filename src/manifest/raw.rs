@@ -50,6 +50,8 @@ pub enum RawManifest {
         dependencies: Option<DependencyMap>,
         /// The optional workspace
         workspace: Option<Workspace>,
+        /// The optional dependency patches
+        patch: Option<DependencyMap>,
     },
     /// A raw manifest with an unknown canary
     Unknown {
@@ -59,6 +61,8 @@ pub enum RawManifest {
         dependencies: Option<DependencyMap>,
         /// The optional workspace
         workspace: Option<Workspace>,
+        /// The optional dependency patches
+        patch: Option<DependencyMap>,
     },
 }
 
@@ -78,14 +82,18 @@ impl RawManifest {
     }
 
     pub(crate) fn dependencies_as_vec(&self) -> Option<Vec<Dependency>> {
-        self.dependencies().map(|deps| {
-            deps.iter()
-                .map(|(package, manifest)| Dependency {
-                    package: package.to_owned(),
-                    manifest: manifest.to_owned(),
-                })
-                .collect()
-        })
+        self.dependencies().map(dependency_map_as_vec)
+    }
+
+    pub(crate) fn patch(&self) -> Option<&DependencyMap> {
+        match self {
+            Self::Canary { patch, .. } => patch.as_ref(),
+            Self::Unknown { patch, .. } => patch.as_ref(),
+        }
+    }
+
+    pub(crate) fn patch_as_vec(&self) -> Option<Vec<Dependency>> {
+        self.patch().map(dependency_map_as_vec)
     }
 
     pub(crate) fn edition(&self) -> Edition {
@@ -103,6 +111,24 @@ impl RawManifest {
     }
 }
 
+fn dependency_map_as_vec(dependencies: &DependencyMap) -> Vec<Dependency> {
+    dependencies
+        .iter()
+        .map(|(package, manifest)| Dependency {
+            package: package.to_owned(),
+            manifest: manifest.to_owned(),
+        })
+        .collect()
+}
+
+/// Converts a list of dependencies into their map representation
+fn dependency_vec_as_map(dependencies: Vec<Dependency>) -> DependencyMap {
+    dependencies
+        .into_iter()
+        .map(|dep| (dep.package, dep.manifest))
+        .collect()
+}
+
 mod serializer {
     use super::*;
     use serde::{Serializer, ser::SerializeStruct};
@@ -117,23 +143,27 @@ mod serializer {
                     ref package,
                     ref dependencies,
                     ref workspace,
+                    ref patch,
                 } => {
-                    let mut s = serializer.serialize_struct("Canary", 3)?;
+                    let mut s = serializer.serialize_struct("Canary", 5)?;
                     s.serialize_field("edition", CANARY_EDITION)?;
                     s.serialize_field("package", package)?;
                     s.serialize_field("dependencies", dependencies)?;
                     s.serialize_field("workspace", workspace)?;
+                    s.serialize_field("patch", patch)?;
                     s.end()
                 }
                 RawManifest::Unknown {
                     ref package,
                     ref dependencies,
                     ref workspace,
+                    ref patch,
                 } => {
-                    let mut s = serializer.serialize_struct("Unknown", 2)?;
+                    let mut s = serializer.serialize_struct("Unknown", 4)?;
                     s.serialize_field("package", package)?;
                     s.serialize_field("dependencies", dependencies)?;
                     s.serialize_field("workspace", workspace)?;
+                    s.serialize_field("patch", patch)?;
                     s.end()
                 }
             }
@@ -154,7 +184,7 @@ mod deserializer {
         where
             D: Deserializer<'de>,
         {
-            static FIELDS: &[&str] = &["package", "dependencies", "workspace"];
+            static FIELDS: &[&str] = &["package", "dependencies", "workspace", "patch"];
 
             struct ManifestVisitor;
 
@@ -173,6 +203,7 @@ mod deserializer {
                     let mut package: Option<PackageManifest> = None;
                     let mut dependencies: Option<HashMap<PackageName, DependencyManifest>> = None;
                     let mut workspace: Option<Workspace> = None;
+                    let mut patch: Option<HashMap<PackageName, DependencyManifest>> = None;
 
                     while let Some(key) = map.next_key::<String>()? {
                         match key.as_str() {
@@ -180,6 +211,7 @@ mod deserializer {
                             "dependencies" => dependencies = Some(map.next_value()?),
                             "edition" => edition = Some(map.next_value()?),
                             "workspace" => workspace = Some(map.next_value()?),
+                            "patch" => patch = Some(map.next_value()?),
                             _ => return Err(de::Error::unknown_field(&key, FIELDS)),
                         }
                     }
@@ -189,6 +221,7 @@ mod deserializer {
                             package,
                             dependencies,
                             workspace,
+                            patch,
                         });
                     };
 
@@ -203,6 +236,7 @@ mod deserializer {
                             package,
                             dependencies,
                             workspace,
+                            patch,
                         }),
                         Edition::Unknown => Err(de::Error::custom(format!(
                             "unsupported manifest edition, supported editions of {} are: {CANARY_EDITION}",
@@ -283,6 +317,7 @@ impl From<WorkspaceManifest> for RawManifest {
             package: None,
             dependencies: None,
             workspace: Some(workspace_manifest.workspace),
+            patch: workspace_manifest.patch.map(dependency_vec_as_map),
         }
     }
 }
@@ -298,16 +333,13 @@ impl From<Manifest> for RawManifest {
 
 impl From<PackagesManifest> for RawManifest {
     fn from(package_manifest: PackagesManifest) -> Self {
-        let dependencies = package_manifest.dependencies.map(|deps| {
-            deps.into_iter()
-                .map(|dep| (dep.package, dep.manifest))
-                .collect()
-        });
+        let dependencies = package_manifest.dependencies.map(dependency_vec_as_map);
         // Always write as Canary - Unknown manifests get upgraded when written
         RawManifest::Canary {
             package: package_manifest.package,
             dependencies,
             workspace: None,
+            patch: package_manifest.patch.map(dependency_vec_as_map),
         }
     }
 }
@@ -322,9 +354,10 @@ impl TryFrom<RawManifest> for WorkspaceManifest {
 
         match raw.workspace() {
             None => bail!("Manifest has no workspace manifest"),
-            Some(workspace_manifest) => Ok(WorkspaceManifest::builder()
-                .workspace(workspace_manifest.clone())
-                .build()),
+            Some(workspace_manifest) => Ok(WorkspaceManifest {
+                workspace: workspace_manifest.clone(),
+                patch: raw.patch_as_vec(),
+            }),
         }
     }
 }
@@ -337,6 +370,7 @@ impl TryFrom<RawManifest> for PackagesManifest {
             edition: raw.edition(),
             package: raw.package().cloned(),
             dependencies: raw.dependencies_as_vec(),
+            patch: raw.patch_as_vec(),
         })
     }
 }
@@ -393,6 +427,7 @@ mod tests {
             package: Some(pkg.clone()),
             dependencies: Some(HashMap::new()),
             workspace: None,
+            patch: None,
         };
 
         assert_eq!(raw.package(), Some(&pkg));
@@ -414,6 +449,7 @@ mod tests {
             package: Some(pkg.clone()),
             dependencies: None,
             workspace: None,
+            patch: None,
         };
 
         assert_eq!(raw.package(), Some(&pkg));
@@ -427,6 +463,7 @@ mod tests {
             package: None,
             dependencies: Some(HashMap::new()),
             workspace: None,
+            patch: None,
         };
 
         assert_eq!(raw.dependencies_as_vec(), Some(vec![]));
@@ -448,6 +485,7 @@ mod tests {
             package: None,
             dependencies: Some(deps),
             workspace: None,
+            patch: None,
         };
 
         let vec_deps = raw.dependencies_as_vec().unwrap();
@@ -507,6 +545,7 @@ mod tests {
                 package: None,
                 dependencies: Some(HashMap::new()),
                 workspace: None,
+                patch: None,
             };
             let result = Manifest::try_from(raw);
             assert!(result.is_ok());
@@ -522,6 +561,7 @@ mod tests {
                     members: vec!["pkg1".to_string()],
                     exclude: None,
                 }),
+                patch: None,
             };
             let result = Manifest::try_from(raw);
             assert!(result.is_ok());
@@ -537,6 +577,7 @@ mod tests {
                     members: vec!["pkg1".to_string()],
                     exclude: None,
                 }),
+                patch: None,
             };
             let result = Manifest::try_from(raw);
             assert!(result.is_err());
@@ -555,6 +596,7 @@ mod tests {
                 }),
                 dependencies: None,
                 workspace: None,
+                patch: None,
             };
             let result = Manifest::try_from(raw);
             assert!(result.is_ok());
@@ -567,6 +609,7 @@ mod tests {
                 package: None,
                 dependencies: None,
                 workspace: None,
+                patch: None,
             };
             let result = Manifest::try_from(raw);
             assert!(result.is_err());
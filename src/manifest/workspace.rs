@@ -19,6 +19,7 @@ use miette::{Context, IntoDiagnostic};
 use serde::{Deserialize, Serialize};
 
 use super::MANIFEST_FILE;
+use super::package::{Dependency, DependencyManifest, LocalDependencyManifest, PackagesManifest};
 use super::raw::RawManifest;
 use crate::{ManagedFile, errors::DeserializationError, io::File};
 
@@ -27,6 +28,8 @@ use crate::{ManagedFile, errors::DeserializationError, io::File};
 pub struct WorkspaceManifest {
     /// Definition of a buffrs workspace
     pub workspace: Workspace,
+    /// Packages replacing every dependency of the same name in all workspace members
+    pub patch: Option<Vec<Dependency>>,
 }
 
 impl WorkspaceManifest {
@@ -34,8 +37,49 @@ impl WorkspaceManifest {
     pub fn builder() -> WorkspaceManifestBuilder<NoWorkspace> {
         WorkspaceManifestBuilder {
             workspace: NoWorkspace,
+            patch: None,
         }
     }
+
+    /// Returns the dependency patches declared in the manifest
+    pub fn patches(&self) -> &[Dependency] {
+        self.patch.as_deref().unwrap_or_default()
+    }
+
+    /// Applies the patches of the workspace to the manifest of a workspace member
+    ///
+    /// Patches are only honored at the root of a project, so patches declared by the member
+    /// itself are replaced. Local patch paths are rebased onto `root`, the workspace directory.
+    pub fn patch_member(&self, member: PackagesManifest, root: &Path) -> PackagesManifest {
+        if member.patch.is_some()
+            && let Some(package) = &member.package
+        {
+            tracing::warn!(
+                "[warn] ignoring patches of workspace member {}, only workspace patches are honored",
+                package.name
+            );
+        }
+
+        let patch = self.patch.as_ref().map(|patch| {
+            patch
+                .iter()
+                .map(|dependency| match &dependency.manifest {
+                    DependencyManifest::Local(local) => Dependency {
+                        package: dependency.package.clone(),
+                        manifest: LocalDependencyManifest {
+                            path: root.join(&local.path),
+                        }
+                        .into(),
+                    },
+                    DependencyManifest::Remote(_)
+                    | DependencyManifest::Git(_)
+                    | DependencyManifest::Tarball(_) => dependency.clone(),
+                })
+                .collect()
+        });
+
+        PackagesManifest { patch, ..member }
+    }
 }
 
 /// NoWorkspace Type used for the workspace typestate builder pattern
@@ -45,12 +89,24 @@ pub struct NoWorkspace;
 /// Builder for constructing a WorkspaceManifest
 pub struct WorkspaceManifestBuilder<W> {
     workspace: W,
+    patch: Option<Vec<Dependency>>,
 }
 
 impl WorkspaceManifestBuilder<NoWorkspace> {
     /// Set the workspace and transition to a WorkspaceManifestBuilder<Workspace>
     pub fn workspace(self, workspace: Workspace) -> WorkspaceManifestBuilder<Workspace> {
-        WorkspaceManifestBuilder { workspace }
+        WorkspaceManifestBuilder {
+            workspace,
+            patch: self.patch,
+        }
+    }
+}
+
+impl<W> WorkspaceManifestBuilder<W> {
+    /// Sets the dependency patches
+    pub fn patch(mut self, patch: Vec<Dependency>) -> Self {
+        self.patch = Some(patch);
+        self
     }
 }
 
@@ -59,6 +115,7 @@ impl WorkspaceManifestBuilder<Workspace> {
     pub fn build(self) -> WorkspaceManifest {
         WorkspaceManifest {
             workspace: self.workspace,
+            patch: self.patch,
         }
     }
 }
@@ -192,12 +249,50 @@ mod tests {
         assert!(serialized.contains("pkg2"));
     }
 
+    #[test]
+    fn workspace_manifest_patch_member_rebases_local_paths() {
+        let toml = r#"
+                [workspace]
+                members = ["pkg1"]
+
+                [patch]
+                lib = { path = "libs/lib" }
+            "#;
+
+        let workspace = WorkspaceManifest::from_str(toml).expect("should parse");
+
+        let member = PackagesManifest::builder()
+            .dependencies(vec![])
+            .patch(vec![Dependency {
+                package: "ignored".parse().unwrap(),
+                manifest: LocalDependencyManifest {
+                    path: "ignored".into(),
+                }
+                .into(),
+            }])
+            .build();
+
+        let patched = workspace.patch_member(member, Path::new("/workspace"));
+
+        assert_eq!(
+            patched.patches(),
+            &[Dependency {
+                package: "lib".parse().unwrap(),
+                manifest: LocalDependencyManifest {
+                    path: "/workspace/libs/lib".into(),
+                }
+                .into(),
+            }]
+        );
+    }
+
     #[test]
     fn workspace_manifest_try_from_raw_missing_workspace_errors() {
         let raw = RawManifest::Canary {
             package: None,
             dependencies: Some(HashMap::new()),
             workspace: None,
+            patch: None,
         };
 
         let result = WorkspaceManifest::try_from(raw);
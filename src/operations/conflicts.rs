@@ -0,0 +1,161 @@
+// (c) Copyright 2025 Helsing GmbH. All rights reserved.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    path::Path,
+};
+
+use semver::{Version, VersionReq};
+
+use crate::{
+    lock::{LockedDependency, LockedPackage, Lockfile},
+    manifest::{Dependency, DependencyManifest, Manifest, PackagesManifest},
+    package::PackageName,
+};
+
+/// Resolves the git merge conflicts in the contents of a lockfile
+///
+/// Both sides of the conflicts are merged and checked against the manifest. Direct dependencies
+/// select the highest locked version matching their requirement, entries which are no longer
+/// reachable from the manifest are dropped and dependants are counted again for the merged graph.
+pub async fn resolve(cwd: &Path, manifest: &Manifest, contents: &str) -> miette::Result<Lockfile> {
+    let (ours, theirs) = Lockfile::parse_conflicts(contents, manifest)?;
+    let merged = ours.merge(theirs)?;
+
+    // 1. Collect the manifests declaring dependencies, identified like during installation
+    let mut roots = Vec::new();
+
+    let patches: BTreeMap<&PackageName, &DependencyManifest> = match manifest {
+        Manifest::Package(package) => {
+            roots.push((label(package, cwd), package.clone()));
+
+            package.patches()
+        }
+        Manifest::Workspace(workspace) => {
+            for member in workspace.workspace.members(cwd)? {
+                let member_cwd = cwd.join(member);
+                let package = Manifest::require_package_manifest(&member_cwd).await?;
+                let package = workspace.patch_member(package, cwd);
+
+                roots.push((label(&package, &member_cwd), package));
+            }
+
+            workspace.patches()
+        }
+    }
+    .iter()
+    .map(|patch| (&patch.package, &patch.manifest))
+    .collect();
+
+    // Git packages are checked out again during installation
+    for local in merged.local_packages().filter(|local| local.git.is_none()) {
+        match Manifest::require_package_manifest(&cwd.join(&local.path)).await {
+            Ok(package) => roots.push((local.name.to_string(), package)),
+            Err(_) => tracing::warn!(
+                "[warn] local package {} not found at {}",
+                local.name,
+                local.path.display()
+            ),
+        }
+    }
+
+    let requirement = |name: &PackageName, manifest: &DependencyManifest| -> Option<VersionReq> {
+        match patches.get(name).copied().unwrap_or(manifest) {
+            DependencyManifest::Remote(remote) => Some(remote.version.clone()),
+            DependencyManifest::Local(_)
+            | DependencyManifest::Git(_)
+            | DependencyManifest::Tarball(_) => None,
+        }
+    };
+
+    // 2. Select the locked versions of direct dependencies
+    let mut dependants: BTreeMap<(PackageName, Version), BTreeSet<String>> = BTreeMap::new();
+    let mut queue = VecDeque::new();
+
+    for (label, root) in &roots {
+        for Dependency { package, manifest } in root.dependencies.iter().flatten() {
+            let Some(requirement) = requirement(package, manifest) else {
+                continue;
+            };
+
+            let Some(locked) = merged.find_matching(package, &requirement) else {
+                tracing::warn!(
+                    "[warn] no locked version of {package} matches {requirement}, run `buffrs install` to resolve it"
+                );
+                continue;
+            };
+
+            dependants
+                .entry((locked.name.clone(), locked.version.clone()))
+                .or_default()
+                .insert(label.clone());
+
+            queue.push_back(locked);
+        }
+    }
+
+    // 3. Follow the locked dependencies to find every reachable package
+    let mut reached: BTreeMap<(PackageName, Version), &LockedPackage> = BTreeMap::new();
+
+    while let Some(package) = queue.pop_front() {
+        let key = (package.name.clone(), package.version.clone());
+
+        if reached.insert(key, package).is_some() {
+            continue;
+        }
+
+        for dependency in &package.dependencies {
+            let dependency = match dependency {
+                LockedDependency::Qualified { name, version } => merged
+                    .packages()
+                    .find(|locked| &locked.name == name && &locked.version == version),
+                LockedDependency::Named { name } => match patches.get(name) {
+                    Some(
+                        DependencyManifest::Local(_)
+                        | DependencyManifest::Git(_)
+                        | DependencyManifest::Tarball(_),
+                    ) => continue,
+                    Some(DependencyManifest::Remote(remote)) => {
+                        merged.find_matching(name, &remote.version)
+                    }
+                    None => merged.find_matching(name, &VersionReq::STAR),
+                },
+            };
+
+            let Some(dependency) = dependency else {
+                tracing::warn!(
+                    "[warn] a dependency of {} {} is not locked, run `buffrs install` to resolve it",
+                    package.name,
+                    package.version
+                );
+                continue;
+            };
+
+            dependants
+                .entry((dependency.name.clone(), dependency.version.clone()))
+                .or_default()
+                .insert(package.name.to_string());
+
+            queue.push_back(dependency);
+        }
+    }
+
+    // 4. Lock the reachable packages with the dependants of the merged graph
+    let packages: Vec<_> = reached
+        .into_iter()
+        .map(|(key, package)| LockedPackage {
+            dependants: dependants.get(&key).map_or(0, BTreeSet::len),
+            ..package.clone()
+        })
+        .collect();
+
+    Ok(merged.with_packages(packages))
+}
+
+/// Identifies a manifest the same way as installation, by package name or location
+fn label(manifest: &PackagesManifest, cwd: &Path) -> String {
+    match &manifest.package {
+        Some(package) => package.name.to_string(),
+        None => cwd.display().to_string(),
+    }
+}
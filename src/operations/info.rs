@@ -0,0 +1,135 @@
+// (c) Copyright 2025 Helsing GmbH. All rights reserved.
+
+use std::fmt::{self, Display};
+
+use semver::Version;
+use serde::Serialize;
+
+use crate::{
+    lock::{Digest, DigestAlgorithm, LockedFile},
+    manifest::{Dependency, DependencyManifest},
+    operations::tree,
+    package::{Package, PackageName, PackageType},
+    registry::RegistryUri,
+};
+
+/// The published manifest and contents of a package
+///
+/// Serialized as JSON, this is the schema emitted by `buffrs info --json`. Displayed, it is the
+/// human readable summary printed by `buffrs info`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageInfo {
+    /// Name of the package
+    pub name: PackageName,
+    /// Published version of the package
+    pub version: Version,
+    /// Type of the package
+    #[serde(rename = "type")]
+    pub kind: PackageType,
+    /// Description of the package
+    pub description: Option<String>,
+    /// Registry the package is published to
+    pub registry: RegistryUri,
+    /// Repository the package is published to
+    pub repository: String,
+    /// Digest of the package archive, as recorded in lockfiles
+    pub digest: Digest,
+    /// Dependencies declared by the published manifest
+    pub dependencies: Vec<DependencyInfo>,
+    /// Protocol buffer files of the package, sorted by path
+    pub files: Vec<LockedFile>,
+}
+
+/// A dependency of a [`PackageInfo`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyInfo {
+    /// Name of the dependency
+    pub name: PackageName,
+    /// Source and version requirement of the dependency
+    #[serde(flatten)]
+    pub manifest: DependencyManifest,
+}
+
+impl PackageInfo {
+    /// Describes a package downloaded from a repository of a registry
+    pub fn new(
+        package: &Package,
+        registry: RegistryUri,
+        repository: String,
+    ) -> miette::Result<Self> {
+        let manifest = package
+            .manifest
+            .package
+            .as_ref()
+            .ok_or_else(|| miette::miette!("published manifest has no package declaration"))?;
+
+        let mut dependencies: Vec<DependencyInfo> = package
+            .manifest
+            .dependencies
+            .iter()
+            .flatten()
+            .map(|dependency| DependencyInfo {
+                name: dependency.package.clone(),
+                manifest: dependency.manifest.clone(),
+            })
+            .collect();
+
+        dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            kind: manifest.kind,
+            description: manifest.description.clone(),
+            registry,
+            repository,
+            digest: package.digest(DigestAlgorithm::SHA256),
+            dependencies,
+            files: package.file_digests(DigestAlgorithm::SHA256)?,
+        })
+    }
+}
+
+impl Display for PackageInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}@{} ({})",
+            self.name,
+            self.version,
+            tree::kind_label(self.kind)
+        )?;
+
+        if let Some(description) = &self.description {
+            writeln!(f, "{description}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "registry:   {}", self.registry)?;
+        writeln!(f, "repository: {}", self.repository)?;
+        writeln!(f, "digest:     {}", self.digest)?;
+
+        if !self.dependencies.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "dependencies:")?;
+
+            for dependency in &self.dependencies {
+                let dependency = Dependency {
+                    package: dependency.name.clone(),
+                    manifest: dependency.manifest.clone(),
+                };
+
+                writeln!(f, "  {dependency}")?;
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "files:")?;
+
+        for file in &self.files {
+            writeln!(f, "  {}", file.path.display())?;
+        }
+
+        Ok(())
+    }
+}
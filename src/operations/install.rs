@@ -1,41 +1,64 @@
 // (c) Copyright 2025 Helsing GmbH. All rights reserved.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
-use miette::{Context as _, IntoDiagnostic, bail, ensure};
-use semver::VersionReq;
+use miette::{Context as _, Diagnostic, IntoDiagnostic, ensure};
+use semver::Version;
 
 use crate::io::File;
-use crate::lock::{DigestAlgorithm, LockedDependency};
+use crate::lock::{
+    Digest, DigestAlgorithm, LockedDependency, LockedGitSource, LockedLocalPackage, LockfileDiff,
+};
 use crate::{
-    cache::{Cache, Entry as CacheEntry},
     credentials::Credentials,
     lock::{LOCKFILE, LockedPackage, Lockfile, PackageLockfile, WorkspaceLockfile},
     manifest::{
         Manifest,
-        package::{Dependency, DependencyManifest, PackagesManifest, RemoteDependencyManifest},
+        package::{DependencyManifest, PackagesManifest},
         workspace::WorkspaceManifest,
     },
     package::{Package, PackageName, PackageStore},
-    registry::{Artifactory, RegistryUri},
-    resolver::{DependencyError, DependencyGraph, DependencySource},
+    registry::RegistryUri,
+    resolver::{DEFAULT_CONCURRENCY, DependencyGraph, DependencySource},
+    util,
 };
 
 /// Trait for types that can install their dependencies
 #[async_trait]
 pub trait Install {
     /// Installs dependencies and returns the resulting locked packages
-    async fn install(&self, ctx: &InstallationContext) -> miette::Result<Vec<LockedPackage>>;
+    async fn install(&self, ctx: &InstallationContext) -> miette::Result<InstalledPackages>;
+}
+
+/// The lockfile entries of installed dependencies
+#[derive(Debug, Clone, Default)]
+pub struct InstalledPackages {
+    /// Packages downloaded from a registry
+    pub remote: Vec<LockedPackage>,
+    /// Local packages, with paths relative to the installation directory
+    pub local: Vec<LockedLocalPackage>,
+    /// Packages depending on each remote package, identified by name and version
+    ///
+    /// Workspace members share packages, so dependants are only counted once all members are
+    /// installed.
+    dependants: BTreeMap<(PackageName, Version), BTreeSet<Dependant>>,
 }
 
+/// A package depending on a remote package, identified by its name and version
+///
+/// Manifests without a package declaration are identified by their location instead.
+type Dependant = (String, Option<Version>);
+
 /// A resolved remote package with its registry metadata
 #[derive(Debug, Clone)]
 struct ResolvedRemotePackage {
     package: Package,
     registry: RegistryUri,
     repository: String,
+    manifest: Option<Digest>,
 }
 
 /// Controls whether network requests are allowed during installation.
@@ -47,16 +70,28 @@ pub enum NetworkMode {
     Offline,
 }
 
+/// The dependencies resolved by a locked installation differ from the lockfile
+#[derive(thiserror::Error, Diagnostic, Debug)]
+#[error("{LOCKFILE} needs to be updated, but installation is locked")]
+#[diagnostic(help(
+    "the following lockfile entries would change:\n{diff}\nrun `buffrs install` without --locked or --frozen and commit {LOCKFILE}"
+))]
+struct LockfileOutdated {
+    diff: LockfileDiff,
+}
+
 /// Context carrying shared state for package installation
 #[derive(Debug, Clone)]
 pub struct InstallationContext {
     cwd: PathBuf,
     credentials: Credentials,
-    cache: Cache,
     store: PackageStore,
     lock: Lockfile,
     preserve_mtime: bool,
     network_mode: NetworkMode,
+    concurrency: NonZeroUsize,
+    locked: bool,
+    dry_run: bool,
 }
 
 impl InstallationContext {
@@ -70,20 +105,20 @@ impl InstallationContext {
 
         let credentials = Credentials::load().await?;
 
-        let cache = Cache::open().await?;
-
         let store = PackageStore::open(&cwd).await?;
 
-        let lock = Lockfile::load_from_or_infer(&cwd).await?;
+        let lock = Lockfile::read_from_or_infer(&cwd).await?;
 
         Ok(Self {
             cwd,
             credentials,
-            cache,
             store,
             lock,
             preserve_mtime,
             network_mode,
+            concurrency: DEFAULT_CONCURRENCY,
+            locked: false,
+            dry_run: false,
         })
     }
 
@@ -98,6 +133,65 @@ impl InstallationContext {
         })
     }
 
+    /// Replaces the lockfile used to select dependency versions
+    pub fn with_lock(self, lock: Lockfile) -> Self {
+        Self { lock, ..self }
+    }
+
+    /// Sets how many remote packages are fetched at a time while resolving dependencies
+    pub fn with_concurrency(self, concurrency: NonZeroUsize) -> Self {
+        Self {
+            concurrency,
+            ..self
+        }
+    }
+
+    /// Forbids changes to the lockfile
+    ///
+    /// Installing fails if the resolved dependencies differ from the lockfile, which is left
+    /// untouched.
+    pub fn with_locked(self, locked: bool) -> Self {
+        Self { locked, ..self }
+    }
+
+    /// Resolves and fetches dependencies without installing them
+    ///
+    /// The package stores and the lockfile are left untouched, so the returned packages preview
+    /// the result of a real installation.
+    pub fn with_dry_run(self, dry_run: bool) -> Self {
+        Self { dry_run, ..self }
+    }
+
+    /// Fails if the context is locked and the resolved lockfile differs from the current one
+    ///
+    /// Installations check their lockfile before touching any package store, so a refused
+    /// installation leaves the vendored packages as they were.
+    fn check_lockfile(&self, lock: &Lockfile) -> miette::Result<()> {
+        if !self.locked {
+            return Ok(());
+        }
+
+        let lock = lock.clone().with_version(self.lock.version());
+        let diff = LockfileDiff::new(&self.lock, &lock);
+
+        ensure!(diff.is_empty(), LockfileOutdated { diff });
+
+        Ok(())
+    }
+
+    /// Persists the lockfile resolved during installation, unless the context is locked or a dry
+    /// run
+    async fn write_lockfile(&self, lock: Lockfile) -> miette::Result<()> {
+        if self.locked || self.dry_run {
+            return Ok(());
+        }
+
+        // Existing lockfiles keep their format until they are migrated explicitly
+        let lock = lock.with_version(self.lock.version());
+
+        lock.save_to(&self.cwd).await
+    }
+
     /// Creates a new installation context rooted at the current working directory
     pub async fn cwd(preserve_mtime: bool, network_mode: NetworkMode) -> miette::Result<Self> {
         let cwd = std::env::current_dir().into_diagnostic()?;
@@ -108,7 +202,25 @@ impl InstallationContext {
 
 #[async_trait]
 impl Install for Manifest {
-    async fn install(&self, ctx: &InstallationContext) -> miette::Result<Vec<LockedPackage>> {
+    async fn install(&self, ctx: &InstallationContext) -> miette::Result<InstalledPackages> {
+        for patch in self.patches() {
+            let replacement = match &patch.manifest {
+                DependencyManifest::Local(local) => format!("path {}", local.path.display()),
+                DependencyManifest::Git(git) => format!("git repository {}", git.git),
+                DependencyManifest::Tarball(tarball) => format!("tarball {}", tarball.url),
+                DependencyManifest::Remote(remote) => format!(
+                    "{}/{}@{} from {}",
+                    remote.repository, patch.package, remote.version, remote.registry
+                ),
+            };
+
+            tracing::warn!(
+                "[warn] patch active: every dependency on {} is replaced by {}",
+                patch.package,
+                replacement
+            );
+        }
+
         match self {
             Manifest::Package(pkg) => pkg.install(ctx).await,
             Manifest::Workspace(wrk) => wrk.install(ctx).await,
@@ -118,77 +230,137 @@ impl Install for Manifest {
 
 #[async_trait]
 impl Install for PackagesManifest {
-    async fn install(&self, ctx: &InstallationContext) -> miette::Result<Vec<LockedPackage>> {
-        // 1. Clear the package store
-        ctx.store.clear().await?;
+    async fn install(&self, ctx: &InstallationContext) -> miette::Result<InstalledPackages> {
+        let resolution = self.resolve(ctx).await?;
 
-        // 2. Install the current package
-        if let Some(ref pkg) = self.package {
-            ctx.store.populate(pkg).await?;
+        // Check the lockfile before the package store is modified
+        let lock = if ctx.lock.is_package_lockfile() {
+            let lock: PackageLockfile = resolution.installed.remote.clone().try_into()?;
+            let lock = lock.with_local_packages(resolution.installed.local.clone());
 
-            tracing::info!("installed {}@{}", pkg.name, pkg.version);
+            Some(Lockfile::Package(lock))
+        } else {
+            None
+        };
+
+        if let Some(lock) = &lock {
+            ctx.check_lockfile(lock)?;
+        }
+
+        if !ctx.dry_run {
+            self.unpack(&resolution, ctx).await?;
+        }
+
+        if let Some(lock) = lock {
+            ctx.write_lockfile(lock).await?;
         }
 
-        // 3. Build the dependency graph
-        let graph = DependencyGraph::build(
-            &self,
+        Ok(resolution.installed)
+    }
+}
+
+/// The dependencies of a package manifest, resolved but not yet installed
+struct Resolution {
+    /// Packages to unpack into the package store, in installation order
+    packages: Vec<(PackageName, Package)>,
+    /// The lockfile entries of the resolved packages
+    installed: InstalledPackages,
+}
+
+impl PackagesManifest {
+    /// Resolves and fetches all dependencies without modifying the package store
+    async fn resolve(&self, ctx: &InstallationContext) -> miette::Result<Resolution> {
+        // 1. Build the dependency graph
+        let graph = DependencyGraph::build_with_concurrency(
+            self,
             &ctx.cwd,
             &ctx.credentials,
             Some(ctx.lock.clone()),
             ctx.network_mode,
+            ctx.concurrency,
         )
         .await?;
 
         let dependencies = graph.ordered_dependencies()?;
 
-        // 4. Install all dependencies and track resolved remote packages
+        // 2. Fetch all dependencies and track resolved remote packages
+        let mut packages = Vec::new();
         let mut remote: HashMap<PackageName, ResolvedRemotePackage> = HashMap::new();
+        let mut local = Vec::new();
 
         for dependency in dependencies {
             let package = match dependency.node.source {
-                // 4.a. Install local dependencies by publishing and unpacking
+                // 2.a. Install local dependencies by publishing and unpacking
                 DependencySource::Local { path } => {
-                    let manifest = Manifest::require_package_manifest(&path).await?;
+                    let (package, locked) =
+                        utils::release_local(&path, util::relative(&path, &ctx.cwd), None, ctx)
+                            .await?;
 
-                    let store = PackageStore::open(path).await?;
+                    // 2.a.1. Lock the contents of the local package
+                    local.push(locked);
 
-                    store.release(&manifest, ctx.preserve_mtime).await?
+                    package
+                }
+                // 2.b. Install git dependencies like local ones, from their checkout
+                DependencySource::Git { path, root, source } => {
+                    let (package, locked) = utils::release_local(
+                        &path,
+                        util::relative(&path, &root),
+                        Some(source),
+                        ctx,
+                    )
+                    .await?;
+
+                    // 2.b.1. Lock the contents and the commit of the git package
+                    local.push(locked);
+
+                    package
                 }
-                // 4.b. Install remote dependencies by downloading
+                // 2.c. Install tarball dependencies from the cache populated during resolution.
+                // They are not locked, since every dependant has to declare the same digest,
+                // which pins their contents already
+                DependencySource::Tarball { manifest } => {
+                    crate::tarball::fetch(
+                        &dependency.node.name,
+                        &manifest,
+                        ctx.network_mode == NetworkMode::Online,
+                    )
+                    .await?
+                }
+                // 2.d. Install remote dependencies as fetched while building the graph
                 DependencySource::Remote {
                     registry,
                     repository,
+                    package,
+                    manifest,
                 } => {
-                    let name = &dependency.node.name;
-                    let version = &dependency.node.version;
-
-                    let installed =
-                        utils::install(&registry, &repository, name, version, ctx).await?;
-
-                    // 4.b.1. Track this resolved remote package
+                    // 2.d.1. Track this resolved remote package
                     remote.insert(
-                        name.clone(),
+                        dependency.node.name.clone(),
                         ResolvedRemotePackage {
-                            package: installed.clone(),
-                            registry: registry.clone(),
-                            repository: repository.clone(),
+                            package: package.clone(),
+                            registry,
+                            repository,
+                            manifest,
                         },
                     );
 
-                    installed
+                    package
                 }
             };
 
-            ctx.store
-                .unpack(&package)
-                .await
-                .wrap_err_with(|| format!("failed to unpack package {}", package.name()))?;
-
-            tracing::info!("installed {}@{}", dependency.name, package.version());
+            packages.push((dependency.name, package));
         }
 
-        // 5. Lock packages with dependency information
+        // 3. Lock packages with dependency information
         let mut locked = Vec::new();
+        let mut dependants = BTreeMap::new();
+
+        // Manifests without a package declaration are identified by their location
+        let root: Dependant = match &self.package {
+            Some(package) => (package.name.to_string(), Some(package.version.clone())),
+            None => (ctx.cwd.display().to_string(), None),
+        };
 
         for (name, resolved) in &remote {
             let node = graph
@@ -196,7 +368,7 @@ impl Install for PackagesManifest {
                 .get(name)
                 .ok_or_else(|| miette::miette!("Package {name} not found in dependency graph"))?;
 
-            // 5.1 Map dependency names to their resolved versions (only remote ones)
+            // 3.1 Map dependency names to their resolved versions (only remote ones)
             let deps: Vec<LockedDependency> = node
                 .dependencies
                 .iter()
@@ -210,35 +382,94 @@ impl Install for PackagesManifest {
                 })
                 .collect();
 
-            // 5.2 Create WorkspaceLockedPackage with dependencies
-            locked.push(LockedPackage {
+            // 3.2 Collect the packages depending on this one, including the root manifest
+            let mut package_dependants: BTreeSet<Dependant> = graph
+                .dependants_of(name)
+                .map(|dependant| {
+                    let version = graph
+                        .nodes
+                        .get(dependant)
+                        .and_then(|node| node.resolved_version.clone());
+
+                    (dependant.to_string(), version)
+                })
+                .collect();
+
+            if self
+                .dependencies
+                .iter()
+                .flatten()
+                .any(|d| &d.package == name)
+            {
+                package_dependants.insert(root.clone());
+            }
+
+            // 3.3 Create LockedPackage with dependencies
+            let package = LockedPackage {
                 name: resolved.package.name().clone(),
                 version: resolved.package.version().clone(),
                 digest: DigestAlgorithm::SHA256.digest(&resolved.package.tgz),
                 registry: resolved.registry.clone(),
                 repository: resolved.repository.clone(),
+                manifest: resolved.manifest.clone(),
                 dependencies: deps,
-                // NOTE: For workspace lockfiles, set dependants=1 (this package needs it)
-                // Aggregation will sum them up across all workspace members
-                //
-                // TODO(mara): revisit
-                dependants: 1,
-            });
+                dependants: package_dependants.len(),
+                edition: Some(resolved.package.manifest.edition.clone()),
+                files: resolved.package.file_digests(DigestAlgorithm::SHA256)?,
+            };
+
+            dependants.insert(
+                (package.name.clone(), package.version.clone()),
+                package_dependants,
+            );
+
+            locked.push(package);
         }
 
-        // 6. Write lockfile if context is a package lockfile
-        if ctx.lock.is_package_lockfile() {
-            let lock: PackageLockfile = locked.clone().try_into()?;
-            lock.save_to(&ctx.cwd).await?;
+        Ok(Resolution {
+            packages,
+            installed: InstalledPackages {
+                remote: locked,
+                local,
+                dependants,
+            },
+        })
+    }
+
+    /// Replaces the contents of the package store with the current package and its resolved
+    /// dependencies
+    async fn unpack(
+        &self,
+        resolution: &Resolution,
+        ctx: &InstallationContext,
+    ) -> miette::Result<()> {
+        // 1. Clear the package store
+        ctx.store.clear().await?;
+
+        // 2. Install the current package
+        if let Some(ref pkg) = self.package {
+            ctx.store.populate(pkg).await?;
+
+            tracing::info!("installed {}@{}", pkg.name, pkg.version);
         }
 
-        Ok(locked)
+        // 3. Install all dependencies
+        for (name, package) in &resolution.packages {
+            ctx.store
+                .unpack(package)
+                .await
+                .wrap_err_with(|| format!("failed to unpack package {}", package.name()))?;
+
+            tracing::info!("installed {}@{}", name, package.version());
+        }
+
+        Ok(())
     }
 }
 
 #[async_trait]
 impl Install for WorkspaceManifest {
-    async fn install(&self, ctx: &InstallationContext) -> miette::Result<Vec<LockedPackage>> {
+    async fn install(&self, ctx: &InstallationContext) -> miette::Result<InstalledPackages> {
         let packages = self.workspace.members(&ctx.cwd)?;
 
         tracing::info!(
@@ -246,11 +477,15 @@ impl Install for WorkspaceManifest {
             packages.len()
         );
 
-        // 1. Install all workspace member and collect locked packages
+        // 1. Resolve all workspace members and collect locked packages
+        let mut members = vec![];
         let mut locked = vec![];
+        let mut local = vec![];
+        let mut dependants: BTreeMap<_, BTreeSet<Dependant>> = BTreeMap::new();
 
         for package in packages {
             let manifest = Manifest::require_package_manifest(&package).await?;
+            let manifest = self.patch_member(manifest, &ctx.cwd);
 
             if PackageLockfile::exists_at(&package).await? {
                 tracing::warn!(
@@ -261,335 +496,105 @@ impl Install for WorkspaceManifest {
 
             tracing::info!("running install for package: {}", package.display());
 
-            let member_ctx = ctx.child(ctx.cwd.join(&package)).await?;
-            let new = manifest.install(&member_ctx).await?;
-
-            locked.extend_from_slice(&new);
-        }
+            let member_cwd = ctx.cwd.join(&package);
+            let member_ctx = ctx.child(member_cwd.clone()).await?;
+            let resolution = manifest.resolve(&member_ctx).await?;
+            let new = resolution.installed.clone();
 
-        tracing::info!("workspace install complete using existing lockfile");
+            members.push((manifest, member_ctx, resolution));
 
-        // 2. Write lockfile if context is a workspace lockfile
-        if ctx.lock.is_workspace_lockfile() {
-            let lock: WorkspaceLockfile = locked.clone().try_into()?;
+            locked.extend_from_slice(&new.remote);
 
-            lock.save_to(&ctx.cwd).await?;
+            for (package, names) in new.dependants {
+                dependants.entry(package).or_default().extend(names);
+            }
 
-            tracing::info!(
-                "wrote workspace lockfile at {}",
-                ctx.cwd.join(LOCKFILE).display()
-            );
+            // Paths of local packages are relative to the workspace lockfile, while paths of git
+            // packages are relative to their repository
+            local.extend(new.local.into_iter().map(|package| match package.git {
+                Some(_) => package,
+                None => LockedLocalPackage {
+                    path: util::relative(&member_cwd.join(&package.path), &ctx.cwd),
+                    ..package
+                },
+            }));
         }
 
-        Ok(locked)
-    }
-}
-
-mod utils {
-    use super::*;
-
-    /// Finds a matching version in the workspace lockfile
-    pub fn find_matching_workspace_locked<'a>(
-        lockfile: &'a Lockfile,
-        name: &PackageName,
-        requirement: &VersionReq,
-    ) -> Option<&'a LockedPackage> {
-        lockfile
-            .packages()
-            .filter(|pkg| pkg.name == *name)
-            .filter(|pkg| requirement.matches(&pkg.version))
-            .max_by_key(|pkg| &pkg.version) // Highest matching version (tiebreaker)
-    }
-
-    /// Downloads the exact version specified
-    pub async fn download_exact(
-        package_name: &PackageName,
-        registry: &RegistryUri,
-        repository: &str,
-        version: &semver::Version,
-        ctx: &InstallationContext,
-    ) -> miette::Result<Package> {
-        let version_req = VersionReq::parse(&format!("={}", version))
-            .into_diagnostic()
-            .wrap_err("failed to create exact version requirement")?;
-
-        download(package_name, registry, repository, &version_req, ctx).await
-    }
-
-    /// Downloads a package from the registry and caches it.
-    ///
-    /// Returns an error if `ctx.network_mode` is [`NetworkMode::Offline`].
-    pub async fn download(
-        package_name: &PackageName,
-        registry: &RegistryUri,
-        repository: &str,
-        version: &VersionReq,
-        ctx: &InstallationContext,
-    ) -> miette::Result<Package> {
-        if ctx.network_mode == NetworkMode::Offline {
-            bail!(DependencyError::Offline {
-                name: package_name.clone(),
-                version: version.clone(),
-            });
+        // A package shared by several members is counted once per distinct dependant
+        for package in &mut locked {
+            if let Some(names) = dependants.get(&(package.name.clone(), package.version.clone())) {
+                package.dependants = names.len();
+            }
         }
 
-        // 1. Download the package from artifactory
-        let artifactory = Artifactory::new(registry.clone(), &ctx.credentials)
-            .wrap_err_with(|| format!("failed to initialize registry {}", registry))?;
-
-        let dependency = Dependency {
-            package: package_name.clone(),
-            manifest: DependencyManifest::Remote(RemoteDependencyManifest {
-                registry: registry.clone(),
-                repository: repository.to_string(),
-                version: version.clone(),
-            }),
-        };
-
-        let downloaded_package = artifactory.download(dependency).await?;
-
-        // 2. Cache the downloaded package for future installs
-        let cache_key = CacheEntry::from(&downloaded_package);
-
-        ctx.cache
-            .put(cache_key, downloaded_package.tgz.clone())
-            .await
-            .ok();
-
-        Ok(downloaded_package)
-    }
+        // 2. Check the lockfile before the package store of any member is modified
+        let lock = if ctx.lock.is_workspace_lockfile() {
+            let lock: WorkspaceLockfile = locked.clone().try_into()?;
+            let lock = lock.with_local_packages(local.clone());
 
-    pub async fn install(
-        registry: &RegistryUri,
-        repository: &str,
-        name: &PackageName,
-        version: &VersionReq,
-        ctx: &InstallationContext,
-    ) -> miette::Result<Package> {
-        let Some(locked) = utils::find_matching_workspace_locked(&ctx.lock, name, version) else {
-            return utils::download(name, registry, repository, version, ctx).await;
+            Some(Lockfile::Workspace(lock))
+        } else {
+            None
         };
 
-        ensure!(
-            registry == &locked.registry,
-            "registry mismatch for {}: manifest specifies {} but workspace lockfile requires {}",
-            name,
-            registry,
-            locked.registry
-        );
-
-        tracing::debug!(
-            "using locked packaged version for {}@{}",
-            name,
-            locked.version
-        );
-
-        // Try to get from cache
-        if let Some(cached) = ctx.cache.get(locked.clone().into()).await? {
-            locked.validate(&cached)?;
-
-            return Ok(cached);
-        }
-
-        utils::download_exact(name, registry, repository, &locked.version, ctx).await
-    }
-
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use crate::lock::{Digest, DigestAlgorithm, LockedPackage};
-        use semver::Version;
-        use std::collections::HashMap;
-        use std::str::FromStr;
-        use tempfile::TempDir;
-
-        fn create_lockfile_with_package(
-            package_name: &str,
-            version: &str,
-            registry: &str,
-            repository: &str,
-        ) -> PackageLockfile {
-            let locked_pkg = LockedPackage {
-                name: PackageName::unchecked(package_name),
-                version: Version::parse(version).unwrap(),
-                registry: RegistryUri::from_str(registry).unwrap(),
-                repository: repository.to_string(),
-                digest: "sha256:0000000000000000000000000000000000000000000000000000000000000000"
-                    .parse()
-                    .unwrap(),
-                dependencies: Default::default(),
-                dependants: 1,
-            };
-            PackageLockfile::from_iter(vec![locked_pkg])
+        if let Some(lock) = &lock {
+            ctx.check_lockfile(lock)?;
         }
 
-        #[tokio::test]
-        async fn test_registry_mismatch_fails() {
-            let tmp = TempDir::new().unwrap();
-
-            let lockfile =
-                create_lockfile_with_package("test-pkg", "1.0.0", "https://registry-a.com", "repo");
-
-            // Construct InstallationContext directly to test utils::install independently
-            // of DependencyGraph::build (which eagerly downloads packages)
-            let ctx = InstallationContext {
-                cwd: tmp.path().to_path_buf(),
-                credentials: Credentials {
-                    registry_tokens: HashMap::new(),
-                },
-                cache: Cache::open().await.unwrap(),
-                store: PackageStore::open(tmp.path()).await.unwrap(),
-                lock: Lockfile::Package(lockfile),
-                preserve_mtime: false,
-                network_mode: NetworkMode::Online,
-            };
-
-            let pkg_name = PackageName::unchecked("test-pkg");
-            let registry_b = RegistryUri::from_str("https://registry-b.com").unwrap();
-            let version = VersionReq::parse("=1.0.0").unwrap();
-
-            let result = install(&registry_b, "repo", &pkg_name, &version, &ctx).await;
+        // 3. Install all workspace members
+        if !ctx.dry_run {
+            for (manifest, member_ctx, resolution) in &members {
+                manifest.unpack(resolution, member_ctx).await?;
+            }
 
-            // Should fail with registry mismatch error
-            assert!(result.is_err(), "expected error but got success");
-            let err_msg = format!("{:?}", result.unwrap_err());
-            assert!(
-                err_msg.contains("registry mismatch"),
-                "expected 'registry mismatch' in error, got: {}",
-                err_msg
-            );
+            tracing::info!("workspace install complete using existing lockfile");
         }
 
-        #[tokio::test]
-        async fn test_online_mode_allows_download() {
-            let tmp = TempDir::new().unwrap();
-
-            // Create a context with Online mode and no lockfile
-            let ctx = InstallationContext {
-                cwd: tmp.path().to_path_buf(),
-                credentials: Credentials {
-                    registry_tokens: HashMap::new(),
-                },
-                cache: Cache::open().await.unwrap(),
-                store: PackageStore::open(tmp.path()).await.unwrap(),
-                lock: Lockfile::Package(PackageLockfile::default()),
-                preserve_mtime: false,
-                network_mode: NetworkMode::Online,
-            };
+        // 4. Write lockfile if context is a workspace lockfile
+        if let Some(lock) = lock {
+            ctx.write_lockfile(lock).await?;
 
-            let pkg_name = PackageName::unchecked("test-pkg");
-            let registry = RegistryUri::from_str("https://registry.example.com").unwrap();
-            let version = VersionReq::parse("=1.0.0").unwrap();
-
-            // Attempt download in online mode (will fail due to no actual registry, but should not fail with offline error)
-            let result = download(&pkg_name, &registry, "repo", &version, &ctx).await;
-
-            // Should fail with connection/registry error, not offline error
-            assert!(result.is_err(), "expected error due to no actual registry");
-            let err_msg = format!("{:?}", result.unwrap_err());
-            assert!(
-                !err_msg.contains("offline"),
-                "should not fail with offline error in online mode, got: {}",
-                err_msg
-            );
+            if !ctx.locked && !ctx.dry_run {
+                tracing::info!(
+                    "wrote workspace lockfile at {}",
+                    ctx.cwd.join(LOCKFILE).display()
+                );
+            }
         }
 
-        #[tokio::test]
-        async fn test_offline_mode_prevents_download() {
-            let tmp = TempDir::new().unwrap();
-
-            // Create a context with Offline mode
-            let ctx = InstallationContext {
-                cwd: tmp.path().to_path_buf(),
-                credentials: Credentials {
-                    registry_tokens: HashMap::new(),
-                },
-                cache: Cache::open().await.unwrap(),
-                store: PackageStore::open(tmp.path()).await.unwrap(),
-                lock: Lockfile::Package(PackageLockfile::default()),
-                preserve_mtime: false,
-                network_mode: NetworkMode::Offline,
-            };
-
-            let pkg_name = PackageName::unchecked("test-pkg");
-            let registry = RegistryUri::from_str("https://registry.example.com").unwrap();
-            let version = VersionReq::parse("=1.0.0").unwrap();
-
-            // Attempt download in offline mode
-            let result = download(&pkg_name, &registry, "repo", &version, &ctx).await;
-
-            // Should fail with offline error
-            assert!(result.is_err(), "expected error in offline mode");
-            let err_msg = format!("{:?}", result.unwrap_err());
-            assert!(
-                err_msg.contains("offline"),
-                "expected 'offline' in error message, got: {}",
-                err_msg
-            );
-        }
+        Ok(InstalledPackages {
+            remote: locked,
+            local,
+            dependants,
+        })
+    }
+}
 
-        #[test]
-        fn test_find_matching_workspace_locked() {
-            // Create workspace lockfile with multiple versions
-            let pkg_v1 = LockedPackage {
-                name: PackageName::unchecked("remote-lib"),
-                version: Version::new(1, 5, 0),
-                digest: Digest::from_parts(
-                    DigestAlgorithm::SHA256,
-                    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
-                )
-                .unwrap(),
-                registry: RegistryUri::from_str("https://registry.com").unwrap(),
-                repository: "test-repo".to_string(),
-                dependencies: vec![],
-                dependants: 1,
-            };
+mod utils {
+    use super::*;
 
-            let pkg_v2 = LockedPackage {
-                name: PackageName::unchecked("remote-lib"),
-                version: Version::new(2, 0, 0),
-                digest: Digest::from_parts(
-                    DigestAlgorithm::SHA256,
-                    "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
-                )
-                .unwrap(),
-                registry: RegistryUri::from_str("https://registry.com").unwrap(),
-                repository: "test-repo".to_string(),
-                dependencies: vec![],
-                dependants: 1,
-            };
+    /// Releases a package from a local directory and locks its contents
+    pub async fn release_local(
+        path: &PathBuf,
+        locked_path: PathBuf,
+        git: Option<LockedGitSource>,
+        ctx: &InstallationContext,
+    ) -> miette::Result<(Package, LockedLocalPackage)> {
+        let manifest = Manifest::require_package_manifest(path).await?;
 
-            let lockfile = Lockfile::Workspace(WorkspaceLockfile::from_iter(vec![pkg_v1, pkg_v2]));
+        let store = PackageStore::open(path).await?;
 
-            // Test finding version matching ^1.0.0
-            let req_v1 = VersionReq::parse("^1.0.0").unwrap();
-            let found = find_matching_workspace_locked(
-                &lockfile,
-                &PackageName::unchecked("remote-lib"),
-                &req_v1,
-            );
-            assert!(found.is_some());
-            assert_eq!(found.unwrap().version, Version::new(1, 5, 0));
+        let package = store.release(&manifest, ctx.preserve_mtime).await?;
 
-            // Test finding version matching ^2.0.0
-            let req_v2 = VersionReq::parse("^2.0.0").unwrap();
-            let found = find_matching_workspace_locked(
-                &lockfile,
-                &PackageName::unchecked("remote-lib"),
-                &req_v2,
-            );
-            assert!(found.is_some());
-            assert_eq!(found.unwrap().version, Version::new(2, 0, 0));
+        let locked = LockedLocalPackage {
+            name: package.name().clone(),
+            version: package.version().clone(),
+            path: locked_path,
+            digest: store.digest(&manifest).await?,
+            git,
+        };
 
-            // Test not finding version matching ^3.0.0
-            let req_v3 = VersionReq::parse("^3.0.0").unwrap();
-            let found = find_matching_workspace_locked(
-                &lockfile,
-                &PackageName::unchecked("remote-lib"),
-                &req_v3,
-            );
-            assert!(found.is_none());
-        }
+        Ok((package, locked))
     }
 }
 
@@ -615,8 +620,11 @@ mod tests {
             .unwrap(),
             registry: RegistryUri::from_str("https://registry.com").unwrap(),
             repository: "test-repo".to_string(),
+            manifest: None,
             dependencies: vec![],
-            dependants: 1,
+            dependants: 2,
+            edition: None,
+            files: vec![],
         };
 
         let pkg_v2 = LockedPackage {
@@ -629,8 +637,11 @@ mod tests {
             .unwrap(),
             registry: RegistryUri::from_str("https://registry.com").unwrap(),
             repository: "test-repo".to_string(),
+            manifest: None,
             dependencies: vec![],
             dependants: 1,
+            edition: None,
+            files: vec![],
         };
 
         // Also add the same version from two different members
@@ -639,13 +650,19 @@ mod tests {
             version: Version::new(1, 0, 0),
             registry: RegistryUri::from_str("https://registry.com").unwrap(),
             repository: "test-repo".to_string(),
+            manifest: None,
             digest: Digest::from_parts(
                 DigestAlgorithm::SHA256,
                 "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
             )
             .unwrap(),
-            dependencies: vec![],
-            dependants: 1,
+            dependencies: vec![LockedDependency::qualified(
+                PackageName::unchecked("other-lib"),
+                Version::new(1, 0, 0),
+            )],
+            dependants: 2,
+            edition: None,
+            files: vec![],
         };
 
         let locked_packages = vec![pkg_v1, pkg_v2, pkg_v1_dup];
@@ -655,7 +672,7 @@ mod tests {
         // Should have 2 entries (v1.0.0 and v2.0.0), not 3
         assert_eq!(workspace_lockfile.packages().count(), 2);
 
-        // v1.0.0 should have dependants=2 (merged from two members)
+        // v1.0.0 was counted across the workspace by both members, so counts are not summed
         let v1 = workspace_lockfile
             .get(
                 &PackageName::unchecked("remote-lib"),
@@ -663,7 +680,8 @@ mod tests {
             )
             .expect("v1.0.0 should exist");
         assert_eq!(v1.version, Version::new(1, 0, 0));
-        assert_eq!(v1.dependants, 2); // Summed from two members
+        assert_eq!(v1.dependants, 2);
+        assert_eq!(v1.dependencies.len(), 1);
 
         // v2.0.0 should have dependants=1
         let v2 = workspace_lockfile
@@ -675,4 +693,36 @@ mod tests {
         assert_eq!(v2.version, Version::new(2, 0, 0));
         assert_eq!(v2.dependants, 1);
     }
+
+    #[test]
+    fn test_aggregate_workspace_lockfile_rejects_conflicting_sources() {
+        use crate::lock::{Digest, DigestAlgorithm};
+
+        let locked = |digest: &str| LockedPackage {
+            name: PackageName::unchecked("remote-lib"),
+            version: Version::new(1, 0, 0),
+            digest: Digest::from_parts(DigestAlgorithm::SHA256, &digest.repeat(64)).unwrap(),
+            registry: RegistryUri::from_str("https://registry.com").unwrap(),
+            repository: "test-repo".to_string(),
+            manifest: None,
+            dependencies: vec![],
+            dependants: 1,
+            edition: None,
+            files: vec![],
+        };
+
+        // Members disagreeing on a package are reported with both candidates
+        let error = WorkspaceLockfile::try_from(vec![locked("a"), locked("b")]).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "remote-lib 1.0.0 is locked from different sources: \
+                 test-repo in https://registry.com/ (sha256:{}) and \
+                 test-repo in https://registry.com/ (sha256:{})",
+                "a".repeat(64),
+                "b".repeat(64)
+            )
+        );
+    }
 }
@@ -2,7 +2,15 @@
 
 //! Algorithms to deal with complicated operations
 
+/// Resolution of merge conflicts in lockfiles
+pub mod conflicts;
+/// Summaries of published packages
+pub mod info;
 /// Installation system
 pub mod install;
 /// Publishing system
 pub mod publish;
+/// Dependency tree rendering
+pub mod tree;
+/// Verification of installed packages
+pub mod verify;
@@ -6,8 +6,9 @@ use std::path::Path;
 #[cfg(feature = "git")]
 use std::process::Stdio;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use miette::{Context as _, IntoDiagnostic, bail, miette};
+use miette::{Context as _, IntoDiagnostic, bail, ensure, miette};
 use semver::{Version, VersionReq};
 
 use crate::{
@@ -18,7 +19,7 @@ use crate::{
     },
     operations::install::NetworkMode,
     package::PackageStore,
-    registry::{Artifactory, RegistryUri},
+    registry::{self, Registry, RegistryUri},
     resolver::{DependencyGraph, DependencySource},
 };
 
@@ -26,7 +27,7 @@ use crate::{
 pub struct Publisher {
     registry: RegistryUri,
     repository: String,
-    artifactory: Artifactory,
+    backend: Arc<dyn Registry>,
     preserve_mtime: bool,
     /// Mapping from local dependency paths to their remote published locations
     manifest_mappings: HashMap<LocalDependencyManifest, RemoteDependencyManifest>,
@@ -48,18 +49,37 @@ impl Publisher {
         let credentials = Credentials::load().await?;
         tracing::debug!("credentials loaded successfully");
 
-        tracing::debug!("creating artifactory client for registry: {}", registry);
-        let artifactory = Artifactory::new(registry.clone(), &credentials)?;
-        tracing::debug!("artifactory client created successfully");
+        tracing::debug!("creating registry client for registry: {}", registry);
+        let backend = registry::connect(&registry, &credentials)?;
+        tracing::debug!("registry client created successfully");
 
+        Ok(Self::with_registry(
+            registry,
+            repository,
+            backend,
+            preserve_mtime,
+        ))
+    }
+
+    /// Creates a new Publisher instance uploading to the given registry backend
+    ///
+    /// The registry URI is still recorded in the manifests of published packages, so it should
+    /// point to the location the backend serves packages from.
+    pub fn with_registry(
+        registry: RegistryUri,
+        repository: String,
+        backend: Arc<dyn Registry>,
+        preserve_mtime: bool,
+    ) -> Self {
         tracing::debug!("publisher instance created successfully");
-        Ok(Self {
+
+        Self {
             registry,
             repository,
-            artifactory,
+            backend,
             preserve_mtime,
             manifest_mappings: HashMap::new(),
-        })
+        }
     }
 
     /// Checks git status and ensures repository is clean before publishing
@@ -143,6 +163,18 @@ impl Publisher {
         tracing::debug!("  registry: {}", self.registry);
         tracing::debug!("  repository: {}", self.repository);
 
+        let patched: Vec<String> = manifest
+            .patches()
+            .iter()
+            .map(|patch| patch.package.to_string())
+            .collect();
+
+        ensure!(
+            patched.is_empty(),
+            "cannot publish while dependencies are patched: {}. Remove the [patch] section from {MANIFEST_FILE} first",
+            patched.join(", ")
+        );
+
         if dry_run {
             tracing::warn!("aborting upload due to dry run");
             return Ok(());
@@ -303,7 +335,7 @@ impl Publisher {
             .wrap_err("current dir could not be retrieved")?;
         tracing::debug!("  workspace root path: {}", root_path.display());
 
-        let packages = manifest.workspace.members(root_path)?;
+        let packages = manifest.workspace.members(&root_path)?;
         tracing::debug!("  resolved {} workspace members", packages.len());
 
         tracing::info!(
@@ -340,6 +372,9 @@ impl Publisher {
                 .await?
                 .with_version(version.clone());
 
+            // Patches of members are never honored, and the workspace has none
+            let member_manifest = manifest.patch_member(member_manifest, &root_path);
+
             // Skip dependency-only members early
             if member_manifest.package.is_none() {
                 tracing::debug!(
@@ -610,7 +645,7 @@ impl Publisher {
             self.repository
         );
 
-        self.artifactory
+        self.backend
             .publish(package.clone(), self.repository.clone())
             .await
             .wrap_err_with(|| format!("publishing of package {} failed", package.name()))?;
@@ -660,6 +695,21 @@ impl Publisher {
         tracing::debug!("replace_local_with_remote_dependencies() called");
         tracing::debug!("  base_path: {}", base_path.display());
 
+        // Git dependencies cannot be resolved by consumers of the registry
+        let git: Vec<String> = manifest
+            .dependencies
+            .iter()
+            .flatten()
+            .filter(|d| d.manifest.is_git())
+            .map(|d| d.package.to_string())
+            .collect();
+
+        ensure!(
+            git.is_empty(),
+            "cannot publish packages with git dependencies: {}",
+            git.join(", ")
+        );
+
         // Manifest may contain references to other local dependencies that need to be replaced by their remote locations
         // The topological order of `ordered_dependencies` guarantees that all dependant packages have been published at this point
         // Keep remote dependencies
@@ -782,6 +832,7 @@ mod tests {
     use crate::credentials::Credentials;
     use crate::manifest::{LocalDependencyManifest, RemoteDependencyManifest};
     use crate::package::PackageName;
+    use crate::registry::Artifactory;
     use semver::VersionReq;
     use std::collections::HashMap;
     use std::fs;
@@ -793,12 +844,12 @@ mod tests {
         let credentials = Credentials {
             registry_tokens: HashMap::new(),
         };
-        let artifactory = Artifactory::new(registry.clone(), &credentials).unwrap();
+        let backend = Arc::new(Artifactory::new(registry.clone(), &credentials).unwrap());
 
         Publisher {
             registry,
             repository: "test-repo".to_string(),
-            artifactory,
+            backend,
             preserve_mtime: false,
             manifest_mappings: HashMap::new(),
         }
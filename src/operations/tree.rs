@@ -0,0 +1,555 @@
+// (c) Copyright 2025 Helsing GmbH. All rights reserved.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use semver::Version;
+use serde::Serialize;
+use strum::{Display, EnumString};
+use url::Url;
+
+use crate::{
+    lock::Digest,
+    manifest::{Dependency, PackagesManifest},
+    package::{PackageName, PackageType},
+    registry::RegistryUri,
+    resolver::{DependencyGraph, DependencyNode, DependencySource},
+    util::{normalize, relative},
+};
+
+/// Output formats of the dependency graph
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum GraphFormat {
+    /// Human readable tree
+    #[default]
+    Text,
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// JSON document following the schema of [`ExportedGraph`]
+    Json,
+}
+
+/// Options controlling how a dependency tree is rendered
+#[derive(Debug, Clone, Default)]
+pub struct TreeOptions {
+    /// Render the packages depending on this package instead of the dependencies of the root
+    pub invert: Option<PackageName>,
+    /// Maximum depth of the rendered tree, unlimited if not set
+    pub depth: Option<usize>,
+}
+
+/// A vertex of the rendered tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Vertex<'a> {
+    /// The package the graph was resolved for
+    Root,
+    /// A dependency in the graph
+    Package(&'a PackageName),
+}
+
+/// A resolved dependency graph together with the manifest it was resolved for
+#[derive(Debug)]
+pub struct DependencyTree<'a> {
+    manifest: &'a PackagesManifest,
+    graph: &'a DependencyGraph,
+    base_path: PathBuf,
+}
+
+impl<'a> DependencyTree<'a> {
+    /// Creates a new dependency tree
+    ///
+    /// Paths of local dependencies are displayed relative to `base_path`.
+    pub fn new(
+        manifest: &'a PackagesManifest,
+        graph: &'a DependencyGraph,
+        base_path: impl AsRef<Path>,
+    ) -> Self {
+        Self {
+            manifest,
+            graph,
+            base_path: base_path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns whether a package is part of the dependency graph
+    pub fn contains(&self, name: &PackageName) -> bool {
+        self.graph.nodes.contains_key(name)
+    }
+
+    /// Renders the dependency tree in a cargo-tree-like format
+    ///
+    /// Subtrees that have already been printed are marked with `(*)` and not expanded again.
+    pub fn render(&self, options: &TreeOptions) -> String {
+        let root = match &options.invert {
+            Some(name) => Vertex::Package(name),
+            None => Vertex::Root,
+        };
+
+        let mut out = String::new();
+        let mut expanded = HashSet::new();
+
+        self.render_vertex(&mut out, root, "", 0, &mut expanded, options);
+
+        out
+    }
+
+    /// Returns the label of a package, if it is part of the dependency graph
+    pub fn package_label(&self, name: &PackageName) -> Option<String> {
+        self.graph.nodes.get(name).map(|node| self.node_label(node))
+    }
+
+    /// Returns every path from the root to a package, ordered like the inverted tree
+    ///
+    /// Each hop is printed as the dependency declared by its dependant, so the requirement
+    /// that pulled the package in is visible, e.g. `app v1.0.0 [api] -> protos/lib-a@^1.0`.
+    pub fn paths_to(&self, name: &PackageName) -> Vec<String> {
+        let mut paths = Vec::new();
+
+        if self.contains(name) {
+            self.collect_paths(Vertex::Package(name), &mut vec![], &mut paths);
+        }
+
+        paths
+    }
+
+    fn collect_paths(&self, vertex: Vertex<'a>, hops: &mut Vec<String>, paths: &mut Vec<String>) {
+        let Vertex::Package(name) = vertex else {
+            let path = std::iter::once(self.label(vertex))
+                .chain(hops.iter().rev().cloned())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            paths.push(path);
+            return;
+        };
+
+        // The graph is acyclic once built, so walking the dependants always ends at the root
+        for dependant in self.children(vertex, true) {
+            let hop = match self.declaration(dependant, name) {
+                Some(dependency) => dependency.to_string(),
+                None => name.to_string(),
+            };
+
+            hops.push(hop);
+            self.collect_paths(dependant, hops, paths);
+            hops.pop();
+        }
+    }
+
+    /// Returns the dependency on `name` as declared in the manifest of a vertex
+    fn declaration(&self, vertex: Vertex, name: &PackageName) -> Option<&'a Dependency> {
+        let mut declarations = match vertex {
+            Vertex::Root => self.manifest.dependencies.as_deref().unwrap_or_default(),
+            Vertex::Package(package) => self.graph.nodes.get(package)?.declarations.as_slice(),
+        }
+        .iter();
+
+        declarations.find(|dependency| &dependency.package == name)
+    }
+
+    fn render_vertex(
+        &self,
+        out: &mut String,
+        vertex: Vertex<'a>,
+        prefix: &str,
+        depth: usize,
+        expanded: &mut HashSet<Vertex<'a>>,
+        options: &TreeOptions,
+    ) {
+        let label = self.label(vertex);
+        let children = self.children(vertex, options.invert.is_some());
+
+        if children.is_empty() || options.depth.is_some_and(|max| depth >= max) {
+            let _ = writeln!(out, "{label}");
+            return;
+        }
+
+        if !expanded.insert(vertex) {
+            let _ = writeln!(out, "{label} (*)");
+            return;
+        }
+
+        let _ = writeln!(out, "{label}");
+
+        for (index, child) in children.iter().enumerate() {
+            let last = index + 1 == children.len();
+
+            let (connector, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+
+            out.push_str(prefix);
+            out.push_str(connector);
+
+            self.render_vertex(
+                out,
+                *child,
+                &format!("{prefix}{indent}"),
+                depth + 1,
+                expanded,
+                options,
+            );
+        }
+    }
+
+    /// Returns the dependencies of a vertex, or its dependants if the tree is inverted
+    ///
+    /// Packages are sorted by name to keep the output stable.
+    fn children(&self, vertex: Vertex<'a>, inverted: bool) -> Vec<Vertex<'a>> {
+        let mut packages: Vec<&PackageName> = match (vertex, inverted) {
+            (Vertex::Root, false) => self
+                .manifest
+                .dependencies
+                .iter()
+                .flatten()
+                .map(|dependency| &dependency.package)
+                .collect(),
+            (Vertex::Package(name), false) => self
+                .graph
+                .nodes
+                .get(name)
+                .into_iter()
+                .flat_map(|node| node.dependencies.iter())
+                .collect(),
+            (Vertex::Root, true) => vec![],
+            (Vertex::Package(name), true) => self
+                .graph
+                .nodes
+                .values()
+                .filter(|node| node.dependencies.contains(name))
+                .map(|node| &node.name)
+                .collect(),
+        };
+
+        packages.sort();
+
+        let packages = packages.into_iter().map(Vertex::Package);
+
+        match (vertex, inverted) {
+            (Vertex::Package(name), true) => {
+                let root = self
+                    .manifest
+                    .dependencies
+                    .iter()
+                    .flatten()
+                    .any(|dependency| &dependency.package == name)
+                    .then_some(Vertex::Root);
+
+                root.into_iter().chain(packages).collect()
+            }
+            _ => packages.collect(),
+        }
+    }
+
+    /// Returns the exported node of a vertex
+    fn export_node(&self, vertex: Vertex) -> Option<ExportedNode> {
+        match vertex {
+            Vertex::Root => {
+                let package = self.manifest.package.as_ref()?;
+
+                Some(ExportedNode {
+                    id: format!("{}@{}", package.name, package.version),
+                    name: package.name.clone(),
+                    version: Some(package.version.clone()),
+                    kind: Some(package.kind),
+                    source: ExportedSource::Root,
+                })
+            }
+            Vertex::Package(name) => {
+                let node = self.graph.nodes.get(name)?;
+
+                let source = match &node.source {
+                    DependencySource::Remote {
+                        registry,
+                        repository,
+                        ..
+                    } => ExportedSource::Remote {
+                        registry: registry.clone(),
+                        repository: repository.clone(),
+                    },
+                    DependencySource::Local { path } => {
+                        let path = normalize(path);
+
+                        ExportedSource::Local {
+                            path: path
+                                .strip_prefix(&self.base_path)
+                                .unwrap_or(&path)
+                                .to_path_buf(),
+                        }
+                    }
+                    DependencySource::Git { path, root, source } => ExportedSource::Git {
+                        url: source.url.clone(),
+                        commit: source.commit.clone(),
+                        path: relative(path, root),
+                    },
+                    DependencySource::Tarball { manifest } => ExportedSource::Tarball {
+                        url: manifest.url.clone(),
+                        digest: manifest.digest.clone(),
+                    },
+                };
+
+                let id = match &node.resolved_version {
+                    Some(version) => format!("{}@{version}", node.name),
+                    None => node.name.to_string(),
+                };
+
+                Some(ExportedNode {
+                    id,
+                    name: node.name.clone(),
+                    version: node.resolved_version.clone(),
+                    kind: node.package_type,
+                    source,
+                })
+            }
+        }
+    }
+
+    fn label(&self, vertex: Vertex) -> String {
+        match vertex {
+            Vertex::Root => match &self.manifest.package {
+                Some(package) => {
+                    format!(
+                        "{} v{} [{}]",
+                        package.name,
+                        package.version,
+                        kind_label(package.kind)
+                    )
+                }
+                None => "root".to_string(),
+            },
+            Vertex::Package(name) => match self.graph.nodes.get(name) {
+                Some(node) => self.node_label(node),
+                None => name.to_string(),
+            },
+        }
+    }
+
+    fn node_label(&self, node: &DependencyNode) -> String {
+        let mut label = match &node.resolved_version {
+            Some(version) => format!("{} v{version}", node.name),
+            None => format!("{} {}", node.name, node.version),
+        };
+
+        if let Some(kind) = node.package_type {
+            let _ = write!(label, " [{}]", kind_label(kind));
+        }
+
+        match &node.source {
+            DependencySource::Remote {
+                registry,
+                repository,
+                ..
+            } => {
+                let _ = write!(label, " ({registry}/{repository})");
+            }
+            DependencySource::Local { path } => {
+                let path = normalize(path);
+                let path = path.strip_prefix(&self.base_path).unwrap_or(&path);
+                let _ = write!(label, " ({})", path.display());
+            }
+            DependencySource::Tarball { manifest } => {
+                let _ = write!(label, " ({})", manifest.url);
+            }
+            DependencySource::Git { source, .. } => {
+                let _ = write!(
+                    label,
+                    " ({}#{})",
+                    source.url,
+                    &source.commit[..7.min(source.commit.len())]
+                );
+            }
+        }
+
+        label
+    }
+}
+
+/// A dependency graph in a tool-agnostic representation
+///
+/// Serialized as JSON, this is the stable schema emitted by `buffrs tree --format json`.
+/// Nodes and edges are sorted to keep the output deterministic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExportedGraph {
+    /// All packages of the graph
+    pub nodes: Vec<ExportedNode>,
+    /// Dependencies between packages, pointing from dependant to dependency
+    pub edges: Vec<ExportedEdge>,
+}
+
+/// A package in an [`ExportedGraph`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ExportedNode {
+    /// Unique identifier of the node, formatted as `name@version`
+    pub id: String,
+    /// Name of the package
+    pub name: PackageName,
+    /// Resolved version of the package
+    pub version: Option<Version>,
+    /// Type of the package
+    #[serde(rename = "type")]
+    pub kind: Option<PackageType>,
+    /// Where the package comes from
+    pub source: ExportedSource,
+}
+
+/// The source of an [`ExportedNode`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportedSource {
+    /// The package the graph was resolved for, e.g. a workspace member
+    Root,
+    /// A local dependency
+    Local {
+        /// Path of the package, relative to the current directory
+        path: PathBuf,
+    },
+    /// A dependency checked out from a git repository
+    Git {
+        /// URL of the repository
+        url: String,
+        /// The checked out commit
+        commit: String,
+        /// Path of the package inside the repository
+        path: PathBuf,
+    },
+    /// A dependency downloaded as a tarball
+    Tarball {
+        /// URL of the tarball
+        url: Url,
+        /// Digest of the tarball
+        digest: Digest,
+    },
+    /// A dependency downloaded from a registry
+    Remote {
+        /// Registry of the package
+        registry: RegistryUri,
+        /// Repository of the package
+        repository: String,
+    },
+}
+
+/// A dependency edge in an [`ExportedGraph`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ExportedEdge {
+    /// Identifier of the dependant node
+    pub from: String,
+    /// Identifier of the dependency node
+    pub to: String,
+}
+
+impl ExportedGraph {
+    /// Merges the graphs of one or more dependency trees, e.g. of all workspace members
+    pub fn new<'a>(trees: impl IntoIterator<Item = &'a DependencyTree<'a>>) -> Self {
+        let mut nodes = BTreeMap::new();
+        let mut edges = BTreeSet::new();
+
+        for tree in trees {
+            let vertices =
+                std::iter::once(Vertex::Root).chain(tree.graph.nodes.keys().map(Vertex::Package));
+
+            for vertex in vertices {
+                let Some(node) = tree.export_node(vertex) else {
+                    continue;
+                };
+
+                for child in tree.children(vertex, false) {
+                    if let Some(dependency) = tree.export_node(child) {
+                        edges.insert(ExportedEdge {
+                            from: node.id.clone(),
+                            to: dependency.id,
+                        });
+                    }
+                }
+
+                nodes.entry(node.id.clone()).or_insert(node);
+            }
+        }
+
+        Self {
+            nodes: nodes.into_values().collect(),
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// Renders the graph in the Graphviz DOT language
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+
+        for node in &self.nodes {
+            let _ = writeln!(
+                out,
+                "    \"{}\" [label=\"{}\"];",
+                node.id,
+                node.label("\\n")
+            );
+        }
+
+        for edge in &self.edges {
+            let _ = writeln!(out, "    \"{}\" -> \"{}\";", edge.from, edge.to);
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the graph as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        // Mermaid identifiers cannot contain dots, so nodes are referred to by index
+        let ids: BTreeMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id.as_str(), index))
+            .collect();
+
+        let mut out = String::from("graph TD\n");
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(out, "    n{index}[\"{}\"]", node.label("<br>"));
+        }
+
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "    n{} --> n{}",
+                ids[edge.from.as_str()],
+                ids[edge.to.as_str()]
+            );
+        }
+
+        out
+    }
+}
+
+impl ExportedNode {
+    /// Formats the name, version and type of the package, separating the name from the rest
+    fn label(&self, separator: &str) -> String {
+        let mut label = self.name.to_string();
+
+        if let Some(version) = &self.version {
+            let _ = write!(label, "{separator}v{version}");
+        }
+
+        if let Some(kind) = self.kind {
+            let _ = write!(label, " [{}]", kind_label(kind));
+        }
+
+        label
+    }
+}
+
+/// Formats a package type the way it is declared in manifests
+pub(crate) fn kind_label(kind: PackageType) -> &'static str {
+    match kind {
+        PackageType::Lib => "lib",
+        PackageType::Api => "api",
+    }
+}
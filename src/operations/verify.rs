@@ -0,0 +1,284 @@
+// (c) Copyright 2025 Helsing GmbH. All rights reserved.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use miette::{Context, IntoDiagnostic};
+use semver::Version;
+use tokio::fs;
+use walkdir::WalkDir;
+
+use crate::{
+    cache::{Cache, Entry as CacheEntry},
+    lock::{
+        DigestAlgorithm, FileRequirement, LOCKFILE, LockedFile, LockedPackage, Lockfile,
+        LockfileVersion,
+    },
+    manifest::{Dependency, MANIFEST_FILE, Manifest, PackagesManifest},
+    package::{PackageName, PackageStore},
+    util,
+};
+
+/// A difference between the lockfile and the files on disk
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finding {
+    /// A file whose contents do not match the locked digest
+    Tampered(PathBuf),
+    /// A locked file which is not present
+    Missing(PathBuf),
+    /// A file which is present, but not locked
+    Extra(PathBuf),
+    /// A locked package which is not installed
+    NotInstalled {
+        /// The name of the package
+        name: PackageName,
+        /// The locked version of the package
+        version: Version,
+    },
+    /// An installed package which is not locked
+    NotLocked(PathBuf),
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tampered(path) => write!(f, "tampered: {}", path.display()),
+            Self::Missing(path) => write!(f, "missing: {}", path.display()),
+            Self::Extra(path) => write!(f, "extra: {}", path.display()),
+            Self::NotInstalled { name, version } => {
+                write!(f, "missing: {name} {version} is not installed")
+            }
+            Self::NotLocked(path) => write!(f, "extra: {} is not locked", path.display()),
+        }
+    }
+}
+
+/// Verifies the cache entries and vendored packages of a project against its lockfile
+///
+/// Cache entries are optional, so only entries present in the cache are checked. The protocol
+/// buffer files of vendored packages are compared to the digests recorded by the lockfile. As v1
+/// lockfiles do not record these, the files of cached packages are used instead.
+pub async fn verify(
+    cwd: &Path,
+    manifest: &Manifest,
+    lock: &Lockfile,
+    cache: &Cache,
+) -> miette::Result<Vec<Finding>> {
+    let mut findings = BTreeSet::new();
+    let mut expected = BTreeMap::new();
+
+    // 1. Re-hash the cache entries of all locked packages
+    for locked in lock.packages() {
+        let requirement = FileRequirement::from(locked);
+        let path = cache.path().join(CacheEntry::from(&requirement).filename());
+
+        let package = match cache.get(requirement).await {
+            Ok(Some(package)) if package.digest(DigestAlgorithm::SHA256) == locked.digest => {
+                Some(package)
+            }
+            Ok(Some(_)) | Err(_) => {
+                findings.insert(Finding::Tampered(path));
+                None
+            }
+            Ok(None) => None,
+        };
+
+        let files = if lock.version() >= LockfileVersion::V2 {
+            Some(locked.files.clone())
+        } else {
+            package
+                .map(|package| package.file_digests(DigestAlgorithm::SHA256))
+                .transpose()?
+        };
+
+        match files {
+            Some(files) => {
+                expected.insert((locked.name.clone(), locked.version.clone()), files);
+            }
+            None => tracing::warn!(
+                "[warn] cannot verify the files of {} {}: they are not recorded by {} lockfiles and the package is not cached",
+                locked.name,
+                locked.version,
+                lock.version()
+            ),
+        }
+    }
+
+    // 2. Compare the vendored packages of the project or all workspace members
+    let roots = match manifest {
+        Manifest::Package(_) => vec![cwd.to_path_buf()],
+        Manifest::Workspace(workspace) => workspace
+            .workspace
+            .members(cwd)?
+            .into_iter()
+            .map(|member| cwd.join(member))
+            .collect(),
+    };
+
+    let local: BTreeSet<&PackageName> = lock.local_packages().map(|local| &local.name).collect();
+    let mut installed = BTreeSet::new();
+
+    for root in roots {
+        let root_manifest = Manifest::require_package_manifest(&root).await?;
+        let own = root_manifest.package.as_ref().map(|package| &package.name);
+
+        let vendor = root.join(PackageStore::PROTO_VENDOR_PATH);
+
+        if !vendor.is_dir() {
+            continue;
+        }
+
+        let mut entries = fs::read_dir(&vendor)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to read {}", vendor.display()))?;
+
+        let mut directories = Vec::new();
+
+        while let Some(entry) = entries.next_entry().await.into_diagnostic()? {
+            if entry.path().is_dir() {
+                directories.push(entry);
+            }
+        }
+
+        // Tarball dependencies are pinned by the digest declared by their dependant instead
+        let mut tarballs = tarball_dependencies(&root_manifest, manifest.patches());
+
+        for entry in &directories {
+            if let Ok(vendored) =
+                Manifest::require_package_manifest(&entry.path().join(MANIFEST_FILE)).await
+            {
+                tarballs.extend(tarball_dependencies(&vendored, &[]));
+            }
+        }
+
+        for entry in directories {
+            let directory = entry.path();
+
+            let Ok(name) = PackageName::new(entry.file_name().to_string_lossy()) else {
+                findings.insert(Finding::NotLocked(util::relative(&directory, cwd)));
+                continue;
+            };
+
+            // The package itself, local and tarball dependencies are not locked by digest
+            if own == Some(&name) || local.contains(&name) || tarballs.contains(&name) {
+                continue;
+            }
+
+            let Some(locked) = find_installed(lock, &name, directory.join(MANIFEST_FILE)).await
+            else {
+                findings.insert(Finding::NotLocked(util::relative(&directory, cwd)));
+                continue;
+            };
+
+            installed.insert((locked.name.clone(), locked.version.clone()));
+
+            if let Some(files) = expected.get(&(locked.name.clone(), locked.version.clone())) {
+                findings.extend(compare(&directory, files, cwd).await?);
+            }
+        }
+    }
+
+    for locked in lock.packages() {
+        if !installed.contains(&(locked.name.clone(), locked.version.clone())) {
+            findings.insert(Finding::NotInstalled {
+                name: locked.name.clone(),
+                version: locked.version.clone(),
+            });
+        }
+    }
+
+    Ok(findings.into_iter().collect())
+}
+
+/// Collects the names of the tarball dependencies declared by a manifest or its patches
+fn tarball_dependencies(
+    manifest: &PackagesManifest,
+    patches: &[Dependency],
+) -> BTreeSet<PackageName> {
+    manifest
+        .dependencies
+        .iter()
+        .flatten()
+        .chain(manifest.patches())
+        .chain(patches)
+        .filter(|dependency| dependency.manifest.is_tarball())
+        .map(|dependency| dependency.package.clone())
+        .collect()
+}
+
+/// Finds the locked package vendored in a directory, using the version of its manifest
+async fn find_installed<'a>(
+    lock: &'a Lockfile,
+    name: &PackageName,
+    manifest: PathBuf,
+) -> Option<&'a LockedPackage> {
+    let version = Manifest::require_package_manifest(&manifest)
+        .await
+        .ok()
+        .and_then(|manifest| manifest.package)
+        .map(|package| package.version);
+
+    lock.packages()
+        .filter(|locked| &locked.name == name)
+        .find(|locked| {
+            version
+                .as_ref()
+                .is_none_or(|version| &locked.version == version)
+        })
+}
+
+/// Compares the protocol buffer files of a vendored package with the locked files
+async fn compare(
+    directory: &Path,
+    files: &[LockedFile],
+    cwd: &Path,
+) -> miette::Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+
+    let mut actual: BTreeSet<PathBuf> = WalkDir::new(directory)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "proto")
+        })
+        .collect();
+
+    for file in files {
+        let path = directory.join(&file.path);
+
+        if !actual.remove(&path) {
+            findings.push(Finding::Missing(util::relative(&path, cwd)));
+            continue;
+        }
+
+        let contents = fs::read(&path)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+
+        if DigestAlgorithm::SHA256.digest(&contents) != file.digest {
+            findings.push(Finding::Tampered(util::relative(&path, cwd)));
+        }
+    }
+
+    findings.extend(
+        actual
+            .into_iter()
+            .map(|path| Finding::Extra(util::relative(&path, cwd))),
+    );
+
+    Ok(findings)
+}
+
+/// Summary of a failed verification
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+#[error("{count} differences between the installed packages and {LOCKFILE}", count = .0.len())]
+#[diagnostic(help("run `buffrs install` to restore the locked packages"))]
+pub struct VerificationFailed(pub Vec<Finding>);
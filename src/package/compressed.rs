@@ -27,7 +27,7 @@ use tokio::fs;
 use crate::{
     ManagedFile,
     errors::{DeserializationError, SerializationError},
-    lock::{Digest, DigestAlgorithm, LockedPackage},
+    lock::{Digest, DigestAlgorithm, LockedFile, LockedPackage},
     manifest::{self, Edition, MANIFEST_FILE, PackagesManifest},
     package::PackageName,
     package::store::Entry,
@@ -177,6 +177,17 @@ impl Package {
 
     /// Load a package from a precompressed archive.
     pub(crate) fn parse(tgz: Bytes) -> miette::Result<Self> {
+        Self::parse_or_else(tgz, || Err(miette!("missing manifest")))
+    }
+
+    /// Load a package from a precompressed archive, which may lack a manifest.
+    ///
+    /// The manifest of archives without one is provided by `fallback`. The archive itself is
+    /// left untouched, so the digest of the package remains the digest of the archive.
+    pub(crate) fn parse_or_else(
+        tgz: Bytes,
+        fallback: impl FnOnce() -> miette::Result<PackagesManifest>,
+    ) -> miette::Result<Self> {
         let mut tar = Vec::new();
 
         let mut gz = flate2::read::GzDecoder::new(tgz.clone().reader());
@@ -202,8 +213,14 @@ impl Package {
                     // .filter(|path| path.is_file())
                     .filter(|path| path.ends_with(manifest::MANIFEST_FILE))
                     .is_some()
-            })
-            .ok_or_else(|| miette!("missing manifest"))?;
+            });
+
+        let Some(manifest) = manifest else {
+            return Ok(Self {
+                manifest: fallback()?,
+                tgz,
+            });
+        };
 
         let manifest = BufReader::new(manifest);
 
@@ -252,13 +269,72 @@ impl Package {
         algorithm.digest(&self.tgz)
     }
 
+    /// Calculates the digests of the protocol buffer files in this package, sorted by path
+    pub fn file_digests(&self, algorithm: DigestAlgorithm) -> miette::Result<Vec<LockedFile>> {
+        let mut tar = Vec::new();
+        let mut gz = flate2::read::GzDecoder::new(self.tgz.clone().reader());
+
+        gz.read_to_end(&mut tar)
+            .into_diagnostic()
+            .wrap_err(miette!("failed to decompress package {}", self.name()))?;
+
+        let mut tar = tar::Archive::new(Bytes::from(tar).reader());
+        let mut files = Vec::new();
+
+        let entries = tar
+            .entries()
+            .into_diagnostic()
+            .wrap_err(miette!("corrupted tar package"))?;
+
+        for entry in entries {
+            let mut entry = entry
+                .into_diagnostic()
+                .wrap_err(miette!("corrupted tar package"))?;
+
+            let path = entry
+                .path()
+                .into_diagnostic()
+                .wrap_err(miette!("corrupted tar package"))?
+                .into_owned();
+
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "proto")
+            {
+                continue;
+            }
+
+            let mut contents = Vec::new();
+
+            entry
+                .read_to_end(&mut contents)
+                .into_diagnostic()
+                .wrap_err({
+                    miette!(
+                        "failed to read {} from package {}",
+                        path.display(),
+                        self.name()
+                    )
+                })?;
+
+            files.push(LockedFile {
+                digest: algorithm.digest(&contents),
+                path,
+            });
+        }
+
+        files.sort();
+
+        Ok(files)
+    }
+
     /// Lock this package
     pub fn lock(
         &self,
         registry: RegistryUri,
         repository: String,
         dependants: usize,
-    ) -> LockedPackage {
+    ) -> miette::Result<LockedPackage> {
         LockedPackage::lock(self, registry, repository, dependants)
     }
 }
@@ -24,6 +24,7 @@ use tokio::fs;
 use walkdir::WalkDir;
 
 use crate::{
+    lock::{Digest, DigestAlgorithm},
     manifest::{MANIFEST_FILE, Manifest, PackageManifest, PackagesManifest},
     package::{Package, PackageName},
 };
@@ -254,6 +255,27 @@ impl PackageStore {
         manifest: &PackagesManifest,
         preserve_mtime: bool,
     ) -> miette::Result<Package> {
+        let entries = self.entries().await?;
+
+        let package = Package::create(manifest.clone(), entries, preserve_mtime)?;
+
+        tracing::info!("packaged {}@{}", package.name(), package.version());
+
+        Ok(package)
+    }
+
+    /// Computes the digest of a release without modification times
+    ///
+    /// Unlike the digest of a release preserving modification times, this digest only changes
+    /// when the contents of the package change.
+    pub async fn digest(&self, manifest: &PackagesManifest) -> miette::Result<Digest> {
+        let package = Package::create(manifest.clone(), self.entries().await?, false)?;
+
+        Ok(package.digest(DigestAlgorithm::SHA256))
+    }
+
+    /// Collects the files of a release from the `proto/` directory
+    async fn entries(&self) -> miette::Result<BTreeMap<PathBuf, Entry>> {
         let pkg_path = self.proto_path();
         let mut entries = BTreeMap::new();
 
@@ -270,11 +292,7 @@ impl PackageStore {
             );
         }
 
-        let package = Package::create(manifest.clone(), entries, preserve_mtime)?;
-
-        tracing::info!("packaged {}@{}", package.name(), package.version());
-
-        Ok(package)
+        Ok(entries)
     }
 
     /// Returns the installation directory path for a package
@@ -12,15 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::RegistryUri;
+use super::{
+    Registry, RegistryUri, SearchResult,
+    http::{self, RequestBuilder, ValidatedResponse},
+};
 use crate::{
     credentials::Credentials,
     lock::DigestAlgorithm,
     manifest::{Dependency, DependencyManifest},
     package::{Package, PackageName},
 };
+use async_trait::async_trait;
 use miette::{Context, IntoDiagnostic, ensure, miette};
-use reqwest::{Body, Method, Response};
+use reqwest::Method;
 use semver::Version;
 use serde::Deserialize;
 use url::Url;
@@ -43,7 +47,7 @@ impl Artifactory {
         tracing::debug!("  has authentication token: {}", has_token);
 
         tracing::debug!("creating reqwest client with no redirect policy");
-        let client = reqwest::Client::builder()
+        let client = http::client()
             .redirect(reqwest::redirect::Policy::none())
             .build()
             .into_diagnostic()?;
@@ -67,37 +71,12 @@ impl Artifactory {
         request_builder
     }
 
-    /// Pings artifactory to ensure registry access is working
-    pub async fn ping(&self) -> miette::Result<()> {
-        let repositories_url: Url = {
-            let mut uri = self.registry.to_owned();
-            let path = &format!("{}/api/repositories", uri.path());
-            uri.set_path(path);
-            uri.into()
-        };
-
-        self.new_request(Method::GET, repositories_url)
-            .send()
-            .await
-            .map(|_| ())
-    }
-
-    /// Retrieves the latest version of a package by querying artifactory. Returns an error if no artifact could be found
-    pub async fn get_latest_version(
-        &self,
-        repository: String,
-        name: PackageName,
-    ) -> miette::Result<Version> {
-        tracing::debug!("Artifactory::get_latest_version() called");
-        tracing::debug!("  package name: {}", name);
-        tracing::debug!("  repository: {}", repository);
-        tracing::debug!("  registry: {}", self.registry);
-
-        // First retrieve all packages matching the given name
+    /// Queries the artifact search API, returning the URIs of the matching artifacts
+    async fn search_artifacts(&self, query: &str) -> miette::Result<Vec<String>> {
         let search_query_url: Url = {
             let mut url = self.registry.clone();
             url.set_path("artifactory/api/search/artifact");
-            url.set_query(Some(&format!("name={name}&repos={repository}")));
+            url.set_query(Some(query));
             url.into()
         };
 
@@ -139,63 +118,105 @@ impl Artifactory {
             ))?;
 
         tracing::debug!(
-            "found {} artifacts matching the name: {:?}",
+            "found {} artifacts matching the query: {:?}",
             parsed_response.results.len(),
             parsed_response
         );
 
-        // Then from all package names retrieved from artifactory, extract the highest version number
-        tracing::debug!("extracting version numbers from artifact URIs");
-        let highest_version = parsed_response
+        Ok(parsed_response
             .results
+            .into_iter()
+            .map(|result| result.uri)
+            .collect())
+    }
+
+    /// Pings artifactory to ensure registry access is working
+    pub async fn ping(&self) -> miette::Result<()> {
+        let repositories_url: Url = {
+            let mut uri = self.registry.to_owned();
+            let path = &format!("{}/api/repositories", uri.path());
+            uri.set_path(path);
+            uri.into()
+        };
+
+        self.new_request(Method::GET, repositories_url)
+            .send()
+            .await
+            .map(|_| ())
+    }
+}
+
+#[async_trait]
+impl Registry for Artifactory {
+    /// Lists all published versions of a package by querying artifactory
+    ///
+    /// The returned versions are sorted in ascending order. An empty list is returned if the
+    /// package has not been published to the given repository.
+    async fn list_versions(
+        &self,
+        repository: String,
+        name: PackageName,
+    ) -> miette::Result<Vec<Version>> {
+        tracing::debug!("Artifactory::list_versions() called");
+        tracing::debug!("  package name: {}", name);
+        tracing::debug!("  repository: {}", repository);
+        tracing::debug!("  registry: {}", self.registry);
+
+        // First retrieve all packages matching the given name
+        let artifacts = self
+            .search_artifacts(&format!("name={name}&repos={repository}"))
+            .await?;
+
+        // Then from all package names retrieved from artifactory, extract the version numbers
+        tracing::debug!("extracting version numbers from artifact URIs");
+        let mut versions: Vec<Version> = artifacts
             .iter()
-            .filter_map(|artifact_search_result| {
-                let uri = artifact_search_result.to_owned().uri;
+            .filter_map(|uri| {
                 tracing::debug!("  processing artifact URI: {}", uri);
 
-                let full_artifact_name = uri
-                    .split('/')
-                    .next_back()
-                    .map(|name_tgz| name_tgz.trim_end_matches(".tgz"));
+                let version = artifact_version(&name, uri);
 
-                if let Some(artifact_name) = full_artifact_name {
-                    tracing::debug!("    artifact name: {}", artifact_name);
-                }
-
-                let artifact_version = full_artifact_name
-                    .and_then(|name| name.split('-').next_back())
-                    .and_then(|version_str| {
-                        tracing::debug!("    parsing version string: {}", version_str);
-                        Version::parse(version_str).ok()
-                    });
-
-                // we double check that the artifact name matches exactly
-                let expected_artifact_name =
-                    artifact_version.clone().map(|av| format!("{name}-{av}"));
-                if full_artifact_name.is_some_and(|actual| {
-                    expected_artifact_name.is_some_and(|expected| expected == actual)
-                }) {
-                    if let Some(ref version) = artifact_version {
-                        tracing::debug!("    valid version found: {}", version);
+                match version {
+                    Some(ref version) => tracing::debug!("    valid version found: {}", version),
+                    None => {
+                        tracing::debug!("    artifact name doesn't match expected format, skipping")
                     }
-                    artifact_version
-                } else {
-                    tracing::debug!("    artifact name doesn't match expected format, skipping");
-                    None
                 }
+
+                version
             })
-            .max();
+            .collect();
 
-        tracing::debug!("highest version for artifact: {:?}", highest_version);
+        versions.sort();
+        versions.dedup();
 
-        highest_version.ok_or_else(|| {
-            tracing::error!("no version could be found for package {} in repository {}", name, repository);
-            miette!("no version could be found on artifactory for this artifact name. Does it exist in this registry and repository?")
-        })
+        tracing::debug!("versions for artifact: {:?}", versions);
+
+        Ok(versions)
+    }
+
+    /// Searches for packages with the artifact search API
+    ///
+    /// Artifacts are matched by the wildcard pattern `*<term>*.tgz`, in all repositories unless
+    /// one is given.
+    async fn search(
+        &self,
+        term: &str,
+        repository: Option<String>,
+    ) -> miette::Result<Vec<SearchResult>> {
+        let mut query = format!("name=*{term}*.tgz");
+
+        if let Some(repository) = repository {
+            query.push_str(&format!("&repos={repository}"));
+        }
+
+        let artifacts = self.search_artifacts(&query).await?;
+
+        Ok(super::search_results(term, artifacts))
     }
 
     /// Downloads a package from artifactory
-    pub async fn download(&self, dependency: Dependency) -> miette::Result<Package> {
+    async fn download(&self, dependency: Dependency) -> miette::Result<Package> {
         tracing::debug!("Artifactory::download() called");
         tracing::debug!("  package name: {}", dependency.package);
 
@@ -265,7 +286,7 @@ impl Artifactory {
     }
 
     /// Publishes a package to artifactory
-    pub async fn publish(&self, package: Package, repository: String) -> miette::Result<()> {
+    async fn publish(&self, package: Package, repository: String) -> miette::Result<()> {
         tracing::debug!("Artifactory::publish() called");
         tracing::debug!("  package name: {}", package.name());
         tracing::debug!("  package version: {}", package.version());
@@ -413,58 +434,71 @@ impl Artifactory {
     }
 }
 
-struct RequestBuilder(reqwest::RequestBuilder);
+/// Extracts the version from the uri of a published artifact
+///
+/// Artifacts are published as `<repository>/<name>/<name>-<version>.tgz`. We double check that
+/// the artifact name matches exactly, since artifactory also returns artifacts of packages whose
+/// name merely starts with the requested one.
+pub(super) fn artifact_version(name: &PackageName, uri: &str) -> Option<Version> {
+    let artifact_name = uri.split('/').next_back()?.strip_suffix(".tgz")?;
 
-impl RequestBuilder {
-    fn new(client: reqwest::Client, method: reqwest::Method, url: Url) -> Self {
-        Self(client.request(method, url))
-    }
+    let version = artifact_name
+        .strip_prefix(&**name)
+        .and_then(|rest| rest.strip_prefix('-'))?;
 
-    fn auth(mut self, token: String) -> Self {
-        self.0 = self.0.bearer_auth(token);
-        self
-    }
+    Version::parse(version).ok()
+}
 
-    fn body(mut self, payload: impl Into<Body>) -> Self {
-        self.0 = self.0.body(payload);
-        self
-    }
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+struct ArtifactSearchResponse {
+    results: Vec<ArtifactSearchResult>,
+}
 
-    async fn send(self) -> miette::Result<ValidatedResponse> {
-        tracing::debug!("sending HTTP request");
-        let response = self.0.send().await.into_diagnostic()?;
-        tracing::debug!("HTTP response received, status: {}", response.status());
-        response.try_into()
-    }
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+struct ArtifactSearchResult {
+    uri: String,
 }
 
-#[derive(Debug)]
-struct ValidatedResponse(reqwest::Response);
+#[cfg(test)]
+mod tests {
+    use semver::Version;
 
-impl TryFrom<Response> for ValidatedResponse {
-    type Error = miette::Report;
+    use super::artifact_version;
+    use crate::package::PackageName;
 
-    fn try_from(value: Response) -> Result<Self, Self::Error> {
-        ensure!(
-            !value.status().is_redirection(),
-            "remote server attempted to redirect request - is this registry URL valid?"
-        );
+    #[test]
+    fn extracts_artifact_versions() {
+        let name = PackageName::unchecked("my-package");
 
-        ensure!(
-            value.status() != 401,
-            "unauthorized - please provide registry credentials with `buffrs login`"
+        assert_eq!(
+            artifact_version(
+                &name,
+                "https://example.com/artifactory/api/storage/repo/my-package/my-package-1.2.3.tgz"
+            ),
+            Some(Version::new(1, 2, 3))
         );
 
-        value.error_for_status().into_diagnostic().map(Self)
+        assert_eq!(
+            artifact_version(&name, "repo/my-package/my-package-1.0.0-rc.1.tgz"),
+            Some(Version::parse("1.0.0-rc.1").unwrap())
+        );
     }
-}
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
-struct ArtifactSearchResponse {
-    results: Vec<ArtifactSearchResult>,
-}
+    #[test]
+    fn rejects_foreign_artifacts() {
+        let name = PackageName::unchecked("my-package");
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
-struct ArtifactSearchResult {
-    uri: String,
+        assert_eq!(
+            artifact_version(&name, "repo/my-package-ext/my-package-ext-1.2.3.tgz"),
+            None
+        );
+        assert_eq!(
+            artifact_version(&name, "repo/my-package/my-package-1.2.3.zip"),
+            None
+        );
+        assert_eq!(
+            artifact_version(&name, "repo/my-package/my-package-latest.tgz"),
+            None
+        );
+    }
 }
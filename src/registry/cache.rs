@@ -12,32 +12,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use async_trait::async_trait;
 use bytes::Bytes;
-use miette::{Context, IntoDiagnostic, miette};
+use miette::{Context, IntoDiagnostic, ensure, miette};
+use semver::Version;
 use tokio::fs;
 
+use super::{Registry, SearchResult};
 use crate::{
     manifest::{Dependency, DependencyManifest},
-    package::Package,
+    package::{Package, PackageName},
 };
 
-/// A registry that stores and retries packages from a local file system.
-/// This registry is intended primarily for testing.
+/// A registry that stores and retrieves packages from a local file system
+///
+/// Packages are stored as `<repository>/<name>/<name>-<version>.tgz` below the base directory.
 #[derive(Debug, Clone)]
 pub struct LocalRegistry {
     base_dir: PathBuf,
 }
 
 impl LocalRegistry {
-    #[allow(dead_code)]
+    /// Creates a registry storing packages below the given directory
     pub fn new(base_dir: PathBuf) -> Self {
         LocalRegistry { base_dir }
     }
+}
 
+#[async_trait]
+impl Registry for LocalRegistry {
     /// "Downloads" a package from the local filesystem
-    pub async fn download(&self, dependency: Dependency) -> miette::Result<Package> {
+    async fn download(&self, dependency: Dependency) -> miette::Result<Package> {
         let DependencyManifest::Remote(ref manifest) = dependency.manifest else {
             return Err(miette!(
                 "unable to serialize version of local dependency ({})",
@@ -55,10 +62,10 @@ impl LocalRegistry {
         tracing::debug!("downloaded dependency {dependency} from {:?}", path);
 
         let bytes = Bytes::from(
-            fs::read(path)
+            fs::read(&path)
                 .await
                 .into_diagnostic()
-                .wrap_err(miette!("could not read file"))?,
+                .wrap_err(miette!("could not read file: {}", path.display()))?,
         );
 
         Package::try_from(bytes).wrap_err(miette!(
@@ -67,8 +74,76 @@ impl LocalRegistry {
         ))
     }
 
+    /// Lists all versions of a package stored in the local filesystem, in ascending order
+    async fn list_versions(
+        &self,
+        repository: String,
+        name: PackageName,
+    ) -> miette::Result<Vec<Version>> {
+        let path = self.base_dir.join(&repository).join(&*name);
+
+        let mut entries = match fs::read_dir(&path).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err)
+                    .into_diagnostic()
+                    .wrap_err(miette!("could not read directory: {}", path.display()));
+            }
+        };
+
+        let prefix = format!("{name}-");
+        let mut versions = Vec::new();
+
+        while let Some(entry) = entries.next_entry().await.into_diagnostic()? {
+            let file_name = entry.file_name();
+
+            let version = file_name
+                .to_str()
+                .and_then(|file_name| file_name.strip_suffix(".tgz"))
+                .and_then(|artifact| artifact.strip_prefix(&prefix))
+                .and_then(|version| Version::parse(version).ok());
+
+            versions.extend(version);
+        }
+
+        versions.sort();
+
+        Ok(versions)
+    }
+
+    /// Searches the package directories of the local filesystem
+    async fn search(
+        &self,
+        term: &str,
+        repository: Option<String>,
+    ) -> miette::Result<Vec<SearchResult>> {
+        let repositories = match repository {
+            Some(repository) => vec![repository],
+            None => subdirectories(&self.base_dir).await?,
+        };
+
+        let mut artifacts = Vec::new();
+
+        for repository in repositories {
+            let path = self.base_dir.join(&repository);
+
+            for name in subdirectories(&path).await? {
+                let mut entries = fs::read_dir(path.join(&name)).await.into_diagnostic()?;
+
+                while let Some(entry) = entries.next_entry().await.into_diagnostic()? {
+                    if let Some(file_name) = entry.file_name().to_str() {
+                        artifacts.push(format!("{repository}/{name}/{file_name}"));
+                    }
+                }
+            }
+        }
+
+        Ok(super::search_results(term, artifacts))
+    }
+
     /// "Publishes" or stores a package in the local store
-    pub async fn publish(&self, package: Package, repository: String) -> miette::Result<()> {
+    async fn publish(&self, package: Package, repository: String) -> miette::Result<()> {
         let path = self.base_dir.join(PathBuf::from(format!(
             "{}/{}/{}-{}.tgz",
             repository,
@@ -77,6 +152,24 @@ impl LocalRegistry {
             package.version(),
         )));
 
+        // Published packages are immutable, like in remote registries
+        if let Ok(existing) = fs::read(&path).await {
+            ensure!(
+                existing == package.tgz,
+                "unable to publish {}: package is already published with a different hash",
+                package.name()
+            );
+
+            tracing::info!(
+                "{}/{}@{} is already published, skipping",
+                repository,
+                package.name(),
+                package.version()
+            );
+
+            return Ok(());
+        }
+
         fs::create_dir_all(path.parent().unwrap())
             .await
             .into_diagnostic()?;
@@ -98,12 +191,35 @@ impl LocalRegistry {
     }
 }
 
+/// Lists the names of the directories in a directory, which may not exist
+async fn subdirectories(path: &Path) -> miette::Result<Vec<String>> {
+    let mut entries = match fs::read_dir(path).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err)
+                .into_diagnostic()
+                .wrap_err(miette!("could not read directory: {}", path.display()));
+        }
+    };
+
+    let mut directories = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await.into_diagnostic()? {
+        if entry.file_type().await.into_diagnostic()?.is_dir() {
+            directories.extend(entry.file_name().to_str().map(str::to_owned));
+        }
+    }
+
+    Ok(directories)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         manifest::{Dependency, PackageManifest, PackagesManifest},
         package::{Package, PackageType},
-        registry::cache::LocalRegistry,
+        registry::{Registry, cache::LocalRegistry},
     };
     use bytes::Bytes;
     use std::{env, path::PathBuf};
@@ -168,4 +284,71 @@ mod tests {
         assert_eq!(fetched.manifest, manifest);
         assert_eq!(fetched.tgz, package_bytes);
     }
+
+    #[tokio::test]
+    async fn can_list_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = LocalRegistry::new(dir.path().to_path_buf());
+
+        let package_dir = dir.path().join("test-repo/test-api");
+        fs::create_dir_all(&package_dir).await.unwrap();
+
+        for file in [
+            "test-api-0.2.0.tgz",
+            "test-api-0.1.0.tgz",
+            "test-api-1.0.0-rc.1.tgz",
+            "test-api-ext-3.0.0.tgz",
+            "README.md",
+        ] {
+            fs::write(package_dir.join(file), b"").await.unwrap();
+        }
+
+        let versions = registry
+            .list_versions("test-repo".into(), "test-api".parse().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            versions,
+            vec![
+                "0.1.0".parse().unwrap(),
+                "0.2.0".parse().unwrap(),
+                "1.0.0-rc.1".parse().unwrap()
+            ]
+        );
+
+        let latest = registry
+            .get_latest_version("test-repo".into(), "test-api".parse().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(latest, "1.0.0-rc.1".parse().unwrap());
+
+        let missing = registry
+            .list_versions("test-repo".into(), "missing-api".parse().unwrap())
+            .await
+            .unwrap();
+
+        assert!(missing.is_empty());
+
+        let results = registry.search("api", None).await.unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].repository, "test-repo");
+        assert_eq!(results[0].versions, versions);
+
+        assert!(
+            registry
+                .search("api", Some("other-repo".into()))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            registry
+                .get_latest_version("test-repo".into(), "missing-api".parse().unwrap())
+                .await
+                .is_err()
+        );
+    }
 }
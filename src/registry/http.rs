@@ -0,0 +1,315 @@
+// Copyright 2023 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    str::FromStr,
+    sync::LazyLock,
+    time::{Duration, Instant, SystemTime},
+};
+
+use miette::{IntoDiagnostic, ensure};
+use rand::Rng;
+use reqwest::{Body, Method, Response, StatusCode, header};
+use url::Url;
+
+/// Environment variable overriding how often failed idempotent requests are retried
+const RETRIES_ENV_VAR: &str = "BUFFRS_HTTP_RETRIES";
+/// Environment variable overriding the time in seconds a request may take, including its body
+const TIMEOUT_ENV_VAR: &str = "BUFFRS_HTTP_TIMEOUT";
+/// Environment variable overriding the time in seconds establishing a connection may take
+const CONNECT_TIMEOUT_ENV_VAR: &str = "BUFFRS_HTTP_CONNECT_TIMEOUT";
+/// Environment variable overriding the time in seconds after which failed requests are no longer retried
+const RETRY_DEADLINE_ENV_VAR: &str = "BUFFRS_HTTP_RETRY_DEADLINE";
+
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_RETRY_DEADLINE: Duration = Duration::from_secs(600);
+
+/// Delay before the first retry, which doubles with every further retry
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound of the delay between retries
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Upper bound of delays requested by servers with `Retry-After`
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+/// Settings shared by all HTTP clients, read from the environment once
+#[derive(Debug, Clone, Copy)]
+struct Settings {
+    retries: u32,
+    timeout: Duration,
+    connect_timeout: Duration,
+    retry_deadline: Duration,
+}
+
+static SETTINGS: LazyLock<Settings> = LazyLock::new(|| Settings {
+    retries: from_env(RETRIES_ENV_VAR).unwrap_or(DEFAULT_RETRIES),
+    timeout: from_env(TIMEOUT_ENV_VAR)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_TIMEOUT),
+    connect_timeout: from_env(CONNECT_TIMEOUT_ENV_VAR)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+    retry_deadline: from_env(RETRY_DEADLINE_ENV_VAR)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_DEADLINE),
+});
+
+fn from_env<T: FromStr>(name: &str) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+
+    let parsed = value.parse().ok();
+
+    if parsed.is_none() {
+        tracing::warn!("[warn] ignoring invalid value {value:?} of {name}");
+    }
+
+    parsed
+}
+
+/// Creates a builder for HTTP clients with the configured timeouts
+pub(crate) fn client() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(SETTINGS.timeout)
+        .connect_timeout(SETTINGS.connect_timeout)
+}
+
+/// Sends a request, retrying idempotent requests which failed transiently
+///
+/// GET and HEAD requests are retried after connection errors, timeouts, 429 and 5xx responses
+/// with exponential backoff and jitter. 429 and 503 responses may ask for a different delay with
+/// `Retry-After`, which is honored up to a limit. No retry is started if it would not begin before
+/// the retry deadline, measured from the first attempt.
+pub(crate) async fn execute(
+    client: &reqwest::Client,
+    request: reqwest::Request,
+) -> reqwest::Result<Response> {
+    let idempotent = matches!(*request.method(), Method::GET | Method::HEAD);
+    let mut retries = 0;
+    let started = Instant::now();
+
+    loop {
+        // The last attempt consumes the request, as do requests with streamed bodies
+        let attempt = match idempotent && retries < SETTINGS.retries {
+            true => request.try_clone(),
+            false => None,
+        };
+
+        let Some(attempt) = attempt else {
+            return client.execute(request).await;
+        };
+
+        let result = client.execute(attempt).await;
+
+        let (reason, requested) = match &result {
+            Ok(response) if is_transient(response.status()) => {
+                (response.status().to_string(), retry_after(response))
+            }
+            Err(error) if error.is_connect() || error.is_timeout() || error.is_request() => {
+                (error.to_string(), None)
+            }
+            _ => return result,
+        };
+
+        let delay = requested.unwrap_or_else(|| backoff(retries + 1));
+
+        if started.elapsed().saturating_add(delay) > SETTINGS.retry_deadline {
+            tracing::info!(
+                "giving up on {} {} after {reason}: retry deadline of {}s exceeded",
+                request.method(),
+                request.url(),
+                SETTINGS.retry_deadline.as_secs()
+            );
+
+            return result;
+        }
+
+        retries += 1;
+
+        tracing::info!(
+            "retrying {} {} in {:.1}s after {reason} (retry {retries} of {})",
+            request.method(),
+            request.url(),
+            delay.as_secs_f64(),
+            SETTINGS.retries
+        );
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Whether a response status indicates a failure that may not occur again
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+}
+
+/// The delay requested by a 429 or 503 response
+fn retry_after(response: &Response) -> Option<Duration> {
+    if !matches!(
+        response.status(),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    ) {
+        return None;
+    }
+
+    let value = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?;
+
+    parse_retry_after(value, SystemTime::now()).map(|delay| delay.min(MAX_RETRY_AFTER))
+}
+
+/// Parses a `Retry-After` header, which is either a number of seconds or an HTTP date
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// The delay before a retry, between half and all of the exponential backoff
+fn backoff(retry: u32) -> Duration {
+    let delay = INITIAL_BACKOFF
+        .saturating_mul(1 << retry.saturating_sub(1).min(16))
+        .min(MAX_BACKOFF);
+
+    rand::rng().random_range(delay / 2..=delay)
+}
+
+/// Builder for requests to HTTP based registries
+pub(super) struct RequestBuilder(reqwest::RequestBuilder);
+
+impl RequestBuilder {
+    pub(super) fn new(client: reqwest::Client, method: reqwest::Method, url: Url) -> Self {
+        Self(client.request(method, url))
+    }
+
+    pub(super) fn auth(mut self, token: String) -> Self {
+        self.0 = self.0.bearer_auth(token);
+        self
+    }
+
+    pub(super) fn body(mut self, payload: impl Into<Body>) -> Self {
+        self.0 = self.0.body(payload);
+        self
+    }
+
+    pub(super) async fn send(self) -> miette::Result<ValidatedResponse> {
+        let response = self.execute().await?;
+        response.try_into()
+    }
+
+    /// Sends the request, returning `None` if the resource does not exist
+    pub(super) async fn send_optional(self) -> miette::Result<Option<ValidatedResponse>> {
+        let response = self.execute().await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        response.try_into().map(Some)
+    }
+
+    async fn execute(self) -> miette::Result<Response> {
+        let (client, request) = self.0.build_split();
+
+        tracing::debug!("sending HTTP request");
+        let response = execute(&client, request.into_diagnostic()?)
+            .await
+            .into_diagnostic()?;
+        tracing::debug!("HTTP response received, status: {}", response.status());
+
+        Ok(response)
+    }
+}
+
+/// A successful response of an HTTP based registry
+#[derive(Debug)]
+pub(super) struct ValidatedResponse(pub(super) reqwest::Response);
+
+impl TryFrom<Response> for ValidatedResponse {
+    type Error = miette::Report;
+
+    fn try_from(value: Response) -> Result<Self, Self::Error> {
+        ensure!(
+            !value.status().is_redirection(),
+            "remote server attempted to redirect request - is this registry URL valid?"
+        );
+
+        ensure!(
+            value.status() != 401,
+            "unauthorized - please provide registry credentials with `buffrs login`"
+        );
+
+        value.error_for_status().into_diagnostic().map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_transient_failures() {
+        assert!(is_transient(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_transient(StatusCode::BAD_GATEWAY));
+        assert!(is_transient(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient(StatusCode::GATEWAY_TIMEOUT));
+
+        assert!(!is_transient(StatusCode::NOT_IMPLEMENTED));
+        assert!(!is_transient(StatusCode::NOT_FOUND));
+        assert!(!is_transient(StatusCode::UNAUTHORIZED));
+        assert!(!is_transient(StatusCode::OK));
+    }
+
+    #[test]
+    fn parses_retry_after() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn backs_off_exponentially_with_jitter() {
+        for (retry, max) in [(1, 500), (2, 1_000), (3, 2_000), (7, 30_000), (40, 30_000)] {
+            let max = Duration::from_millis(max);
+
+            for _ in 0..16 {
+                let delay = backoff(retry);
+
+                assert!(
+                    delay >= max / 2 && delay <= max,
+                    "retry {retry} waits {delay:?}"
+                );
+            }
+        }
+    }
+}
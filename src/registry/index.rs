@@ -0,0 +1,327 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use miette::{Context, IntoDiagnostic, bail, ensure, miette};
+use reqwest::Method;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    Registry, RegistryUri,
+    http::{self, RequestBuilder, ValidatedResponse},
+};
+use crate::{
+    credentials::Credentials,
+    lock::{Digest, DigestAlgorithm},
+    manifest::{Dependency, DependencyManifest},
+    package::{Package, PackageName},
+};
+
+/// The registry implementation for static web servers with a JSON version index
+///
+/// Packages are stored as `<repository>/<name>/<name>-<version>.tgz`, like in Artifactory, and
+/// the versions of every package are listed in `index/<repository>/<name>.json`. Publishing
+/// uploads both with `PUT` requests, so concurrent publishes of the same package may lose
+/// index entries.
+#[derive(Debug, Clone)]
+pub struct IndexRegistry {
+    registry: RegistryUri,
+    base: Url,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+/// The published versions of a package
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct PackageIndex {
+    versions: Vec<IndexEntry>,
+}
+
+/// A published version of a package
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct IndexEntry {
+    version: Version,
+    digest: Digest,
+    /// Yanked versions are not selected by version requirements, but remain downloadable
+    #[serde(default)]
+    yanked: bool,
+}
+
+impl PackageIndex {
+    fn get(&self, version: &Version) -> Option<&IndexEntry> {
+        self.versions.iter().find(|entry| &entry.version == version)
+    }
+
+    /// Lists the versions which have not been yanked, in ascending order
+    fn published_versions(&self) -> Vec<Version> {
+        let mut versions: Vec<_> = self
+            .versions
+            .iter()
+            .filter(|entry| !entry.yanked)
+            .map(|entry| entry.version.clone())
+            .collect();
+
+        versions.sort();
+        versions.dedup();
+        versions
+    }
+}
+
+impl IndexRegistry {
+    /// Creates a new instance of an index registry client
+    pub fn new(registry: RegistryUri, credentials: &Credentials) -> miette::Result<Self> {
+        let client = http::client()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .into_diagnostic()?;
+
+        Ok(Self {
+            base: registry.base_url(),
+            token: credentials.registry_tokens.get(&registry).cloned(),
+            registry,
+            client,
+        })
+    }
+
+    fn new_request(&self, method: Method, url: Url) -> RequestBuilder {
+        let mut request_builder = RequestBuilder::new(self.client.clone(), method, url);
+
+        if let Some(token) = &self.token {
+            request_builder = request_builder.auth(token.clone());
+        }
+
+        request_builder
+    }
+
+    /// Resolves a path relative to the root of the registry
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.base.clone();
+        url.set_path(&format!(
+            "{}/{path}",
+            self.base.path().trim_end_matches('/')
+        ));
+        url
+    }
+
+    fn index_url(&self, repository: &str, name: &PackageName) -> Url {
+        self.url(&format!("index/{repository}/{name}.json"))
+    }
+
+    fn package_url(&self, repository: &str, name: &PackageName, version: &Version) -> Url {
+        self.url(&format!("{repository}/{name}/{name}-{version}.tgz"))
+    }
+
+    /// Fetches the index of a package, which is empty if the package has not been published
+    async fn fetch_index(
+        &self,
+        repository: &str,
+        name: &PackageName,
+    ) -> miette::Result<PackageIndex> {
+        let url = self.index_url(repository, name);
+
+        tracing::debug!("fetching package index from {url}");
+
+        let Some(ValidatedResponse(response)) = self
+            .new_request(Method::GET, url.clone())
+            .send_optional()
+            .await?
+        else {
+            return Ok(PackageIndex::default());
+        };
+
+        let body = response.bytes().await.into_diagnostic()?;
+
+        serde_json::from_slice(&body)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to parse the package index at {url}"))
+    }
+}
+
+#[async_trait]
+impl Registry for IndexRegistry {
+    /// Downloads a package and verifies it against the digest listed in the index
+    async fn download(&self, dependency: Dependency) -> miette::Result<Package> {
+        let DependencyManifest::Remote(ref manifest) = dependency.manifest else {
+            bail!(
+                "unable to download local dependency ({}) from a registry",
+                dependency.package
+            );
+        };
+
+        let version =
+            Version::parse(&super::dependency_version_string(&dependency)?).into_diagnostic()?;
+
+        let index = self
+            .fetch_index(&manifest.repository, &dependency.package)
+            .await?;
+
+        let entry = index.get(&version).ok_or_else(|| {
+            miette!(
+                "{}@{} is not listed in the index of repository {}",
+                dependency.package,
+                version,
+                manifest.repository
+            )
+        })?;
+
+        if entry.yanked {
+            tracing::warn!(
+                "[warn] {}@{} has been yanked from {}",
+                dependency.package,
+                version,
+                self.registry
+            );
+        }
+
+        let url = self.package_url(&manifest.repository, &dependency.package, &version);
+
+        tracing::debug!("downloading package from {url}");
+
+        let ValidatedResponse(response) = self.new_request(Method::GET, url).send().await?;
+        let data = response.bytes().await.into_diagnostic()?;
+
+        let digest = entry.digest.algorithm().digest(&data);
+
+        ensure!(
+            digest == entry.digest,
+            "digest mismatch for {}@{} - expected {}, actual {}",
+            dependency.package,
+            version,
+            entry.digest,
+            digest
+        );
+
+        Package::try_from(data).wrap_err(miette!(
+            "failed to download dependency {}",
+            dependency.package
+        ))
+    }
+
+    /// Uploads a package and adds it to the index of the package
+    async fn publish(&self, package: Package, repository: String) -> miette::Result<()> {
+        let mut index = self.fetch_index(&repository, package.name()).await?;
+        let digest = DigestAlgorithm::SHA256.digest(&package.tgz);
+
+        if let Some(entry) = index.get(package.version()) {
+            ensure!(
+                entry.digest == digest,
+                "unable to publish {}: package is already published with a different hash",
+                package.name()
+            );
+
+            tracing::info!(
+                "{}/{}@{} is already published, skipping",
+                repository,
+                package.name(),
+                package.version()
+            );
+
+            return Ok(());
+        }
+
+        let url = self.package_url(&repository, package.name(), package.version());
+
+        self.new_request(Method::PUT, url)
+            .body(package.tgz.clone())
+            .send()
+            .await?;
+
+        // The index is updated last, so it never lists versions which cannot be downloaded
+        index.versions.push(IndexEntry {
+            version: package.version().clone(),
+            digest,
+            yanked: false,
+        });
+
+        index.versions.sort_by(|a, b| a.version.cmp(&b.version));
+
+        let body = serde_json::to_vec_pretty(&index).into_diagnostic()?;
+
+        self.new_request(Method::PUT, self.index_url(&repository, package.name()))
+            .body(body)
+            .send()
+            .await
+            .wrap_err("failed to update the package index")?;
+
+        tracing::info!(
+            "published {}/{}@{}",
+            repository,
+            package.name(),
+            package.version()
+        );
+
+        Ok(())
+    }
+
+    async fn list_versions(
+        &self,
+        repository: String,
+        name: PackageName,
+    ) -> miette::Result<Vec<Version>> {
+        Ok(self
+            .fetch_index(&repository, &name)
+            .await?
+            .published_versions())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use semver::Version;
+
+    use super::{IndexRegistry, PackageIndex};
+    use crate::{credentials::Credentials, package::PackageName, registry::RegistryUri};
+
+    #[test]
+    fn skips_yanked_versions() {
+        let index: PackageIndex = serde_json::from_str(
+            r#"{
+                "versions": [
+                    { "version": "0.2.0", "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824" },
+                    { "version": "0.1.0", "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824", "yanked": false },
+                    { "version": "0.3.0", "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824", "yanked": true }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            index.published_versions(),
+            vec![Version::new(0, 1, 0), Version::new(0, 2, 0)]
+        );
+        assert!(index.get(&Version::new(0, 3, 0)).unwrap().yanked);
+    }
+
+    #[test]
+    fn resolves_index_locations() {
+        let registry = RegistryUri::from_str("index+https://example.com/protos").unwrap();
+        let registry = IndexRegistry::new(registry, &Credentials::default()).unwrap();
+        let name = PackageName::unchecked("my-package");
+
+        assert_eq!(
+            registry.index_url("my-repo", &name).as_str(),
+            "https://example.com/protos/index/my-repo/my-package.json"
+        );
+        assert_eq!(
+            registry
+                .package_url("my-repo", &name, &Version::new(1, 0, 0))
+                .as_str(),
+            "https://example.com/protos/my-repo/my-package/my-package-1.0.0.tgz"
+        );
+    }
+}
@@ -13,28 +13,281 @@
 // limitations under the License.
 
 use std::{
+    collections::BTreeMap,
     fmt::{self, Display},
+    net::IpAddr,
     ops::{Deref, DerefMut},
     str::FromStr,
+    sync::Arc,
 };
 
 mod artifactory;
-#[cfg(test)]
 mod cache;
+pub(crate) mod http;
+mod index;
+mod oci;
+mod s3;
 
 pub use artifactory::Artifactory;
-use miette::{Context, IntoDiagnostic, ensure, miette};
-use semver::VersionReq;
+use async_trait::async_trait;
+pub use cache::LocalRegistry;
+pub use index::IndexRegistry;
+use miette::{Context, IntoDiagnostic, bail, ensure, miette};
+pub use oci::OciRegistry;
+pub use s3::S3Registry;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use url::Url;
 
-use crate::manifest::{Dependency, DependencyManifest};
+use crate::{
+    credentials::Credentials,
+    lock::Digest,
+    manifest::{Dependency, DependencyManifest},
+    package::{Package, PackageName},
+};
+
+/// A backend that packages are published to and downloaded from
+///
+/// Packages are addressed by repository, name and version. Implementations are selected from the
+/// registry URI by [`connect`], but can also be passed to operations like
+/// [`Publisher::with_registry`](crate::operations::publish::Publisher::with_registry) directly.
+#[async_trait]
+pub trait Registry: fmt::Debug + Send + Sync {
+    /// Downloads the package matching a pinned remote dependency
+    async fn download(&self, dependency: Dependency) -> miette::Result<Package>;
+
+    /// Downloads a package along with the digest of the artifact manifest it was published with
+    ///
+    /// Registries which store packages as content addressed artifacts download the package
+    /// through the given manifest if one is pinned, and report the digest of the manifest they
+    /// used, so it can be locked. Other registries ignore the manifest and report none.
+    async fn download_artifact(
+        &self,
+        dependency: Dependency,
+        manifest: Option<Digest>,
+    ) -> miette::Result<(Package, Option<Digest>)> {
+        let _ = manifest;
+
+        Ok((self.download(dependency).await?, None))
+    }
+
+    /// Publishes a package to a repository of the registry
+    async fn publish(&self, package: Package, repository: String) -> miette::Result<()>;
+
+    /// Lists all published versions of a package
+    ///
+    /// The returned versions are sorted in ascending order. An empty list is returned if the
+    /// package has not been published to the given repository.
+    async fn list_versions(
+        &self,
+        repository: String,
+        name: PackageName,
+    ) -> miette::Result<Vec<Version>>;
+
+    /// Retrieves the latest published version of a package
+    ///
+    /// Returns an error if no version could be found.
+    async fn get_latest_version(
+        &self,
+        repository: String,
+        name: PackageName,
+    ) -> miette::Result<Version> {
+        let highest_version = self
+            .list_versions(repository.clone(), name.clone())
+            .await?
+            .into_iter()
+            .max();
+
+        tracing::debug!("highest version for artifact: {:?}", highest_version);
+
+        highest_version.ok_or_else(|| {
+            tracing::error!("no version could be found for package {} in repository {}", name, repository);
+            miette!("no version could be found in the registry for this artifact name. Does it exist in this registry and repository?")
+        })
+    }
+
+    /// Searches for packages whose name contains a term
+    ///
+    /// Searches all repositories unless a repository is given. The results are sorted by
+    /// repository and name, with their versions in ascending order. Registries which cannot
+    /// enumerate their packages return an error.
+    async fn search(
+        &self,
+        term: &str,
+        repository: Option<String>,
+    ) -> miette::Result<Vec<SearchResult>> {
+        let _ = (term, repository);
+
+        bail!("searching for packages is not supported by this registry")
+    }
+}
+
+/// A package found by [`Registry::search`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SearchResult {
+    /// Repository the package is published to
+    pub repository: String,
+    /// Name of the package
+    pub name: PackageName,
+    /// Published versions of the package, in ascending order
+    pub versions: Vec<Version>,
+}
+
+/// Groups archives stored as `<repository>/<name>/<name>-<version>.tgz` into search results
+///
+/// Only the last three segments of the paths are considered, so paths may carry any prefix.
+/// Paths which do not follow this layout or whose package name does not contain the term are
+/// skipped.
+fn search_results<S: AsRef<str>>(
+    term: &str,
+    paths: impl IntoIterator<Item = S>,
+) -> Vec<SearchResult> {
+    let mut packages: BTreeMap<(String, PackageName), Vec<Version>> = BTreeMap::new();
+
+    for path in paths {
+        let mut segments = path.as_ref().rsplit('/');
+
+        let (Some(artifact), Some(name), Some(repository)) =
+            (segments.next(), segments.next(), segments.next())
+        else {
+            continue;
+        };
+
+        let Ok(name) = PackageName::new(name) else {
+            continue;
+        };
+
+        if !name.contains(term) {
+            continue;
+        }
+
+        if let Some(version) = artifactory::artifact_version(&name, artifact) {
+            packages
+                .entry((repository.to_owned(), name))
+                .or_default()
+                .push(version);
+        }
+    }
+
+    packages
+        .into_iter()
+        .map(|((repository, name), mut versions)| {
+            versions.sort();
+            versions.dedup();
+
+            SearchResult {
+                repository,
+                name,
+                versions,
+            }
+        })
+        .collect()
+}
+
+/// Connects to the registry backend selected by the scheme of the registry URI
+///
+/// See [`RegistryKind`] for the backends of the supported schemes.
+pub fn connect(
+    registry: &RegistryUri,
+    credentials: &Credentials,
+) -> miette::Result<Arc<dyn Registry>> {
+    match registry.kind() {
+        RegistryKind::Artifactory => Ok(Arc::new(Artifactory::new(registry.clone(), credentials)?)),
+        RegistryKind::Filesystem => {
+            let base_dir = registry
+                .to_file_path()
+                .map_err(|_| miette!("not a valid file path: {registry}"))?;
+
+            Ok(Arc::new(LocalRegistry::new(base_dir)))
+        }
+        RegistryKind::Index => Ok(Arc::new(IndexRegistry::new(registry.clone(), credentials)?)),
+        RegistryKind::Oci => Ok(Arc::new(OciRegistry::new(registry.clone(), credentials)?)),
+        RegistryKind::S3 => Ok(Arc::new(S3Registry::new(registry.clone(), credentials)?)),
+    }
+}
+
+/// Scheme prefix of registries serving static files with a JSON version index
+const INDEX_SCHEME_PREFIX: &str = "index+";
+
+/// The protocols spoken by registries, selected by the scheme of the registry URI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistryKind {
+    /// Artifactory, addressed by `http` and `https` URIs
+    Artifactory,
+    /// A directory of the local filesystem, addressed by `file` URIs
+    Filesystem,
+    /// Static files with a JSON version index, addressed by `index+http` and `index+https` URIs
+    Index,
+    /// An OCI distribution registry, addressed by `oci` URIs
+    Oci,
+    /// An S3 compatible bucket, addressed by `s3` URIs
+    S3,
+}
 
 /// A representation of a registry URI
 #[derive(Debug, Clone, Hash, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct RegistryUri(Url);
 
+impl RegistryUri {
+    /// Returns the protocol of the registry
+    pub fn kind(&self) -> RegistryKind {
+        match self.scheme() {
+            "file" => RegistryKind::Filesystem,
+            "oci" => RegistryKind::Oci,
+            "s3" => RegistryKind::S3,
+            scheme if scheme.starts_with(INDEX_SCHEME_PREFIX) => RegistryKind::Index,
+            _ => RegistryKind::Artifactory,
+        }
+    }
+
+    /// Returns the location packages are served from, without the protocol prefix of the scheme
+    ///
+    /// OCI registries are served from the `/v2` API of the host, over plain HTTP for loopback
+    /// addresses and over HTTPS otherwise. S3 buckets are served from the endpoint configured in
+    /// the environment, see [`S3Registry`].
+    pub fn base_url(&self) -> Url {
+        if self.kind() == RegistryKind::S3 {
+            let Ok(mut url) = s3::bucket_url(self) else {
+                return self.0.clone();
+            };
+
+            let prefix = self.path().trim_matches('/');
+
+            if !prefix.is_empty() {
+                url.set_path(&format!("{}/{prefix}", url.path().trim_end_matches('/')));
+            }
+
+            return url;
+        }
+
+        if self.kind() == RegistryKind::Oci {
+            let host = self.host_str().unwrap_or_default();
+            let loopback = host == "localhost"
+                || host
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .parse::<IpAddr>()
+                    .is_ok_and(|ip| ip.is_loopback());
+
+            let scheme = if loopback { "http" } else { "https" };
+            let port = self
+                .port()
+                .map(|port| format!(":{port}"))
+                .unwrap_or_default();
+            let path = self.path().trim_end_matches('/');
+
+            return Url::parse(&format!("{scheme}://{host}{port}/v2{path}"))
+                .unwrap_or_else(|_| self.0.clone());
+        }
+
+        self.as_str()
+            .strip_prefix(INDEX_SCHEME_PREFIX)
+            .and_then(|url| Url::parse(url).ok())
+            .unwrap_or_else(|| self.0.clone())
+    }
+}
+
 impl From<RegistryUri> for Url {
     fn from(value: RegistryUri) -> Self {
         value.0
@@ -78,9 +331,37 @@ impl FromStr for RegistryUri {
 fn sanity_check_url(url: &Url) -> miette::Result<()> {
     let scheme = url.scheme();
 
+    // Filesystem registries are located by their path alone
+    if scheme == "file" {
+        ensure!(
+            url.to_file_path().is_ok(),
+            "the URI must contain an absolute path: {url}"
+        );
+
+        return Ok(());
+    }
+
+    // OCI registries are addressed by host and namespace, S3 registries by bucket and prefix
+    if scheme == "oci" || scheme == "s3" {
+        ensure!(
+            url.host_str().is_some_and(|host| !host.is_empty()),
+            "the URI must contain a host component: {url}"
+        );
+
+        return Ok(());
+    }
+
+    // Index registries are served by plain web servers
+    let url = match scheme.strip_prefix(INDEX_SCHEME_PREFIX) {
+        Some(_) => Url::parse(&url.as_str()[INDEX_SCHEME_PREFIX.len()..]).into_diagnostic()?,
+        None => url.clone(),
+    };
+
+    let scheme = url.scheme();
+
     ensure!(
         scheme == "http" || scheme == "https",
-        "invalid URI scheme {scheme} - must be http or https"
+        "invalid URI scheme {scheme} - must be http, https, index+http, index+https, oci, s3 or file"
     );
 
     if let Some(host) = url.host_str() {
@@ -149,7 +430,7 @@ fn dependency_version_string(dependency: &Dependency) -> miette::Result<String>
 mod tests {
     use std::str::FromStr;
 
-    use semver::VersionReq;
+    use semver::{Version, VersionReq};
 
     use crate::{
         manifest::Dependency,
@@ -157,7 +438,7 @@ mod tests {
         registry::{VersionNotPinned, dependency_version_string},
     };
 
-    use super::RegistryUri;
+    use super::{RegistryKind, RegistryUri, SearchResult, search_results};
 
     fn get_dependency(version: &str) -> Dependency {
         let registry = RegistryUri::from_str("https://my-registry.com").unwrap();
@@ -205,4 +486,89 @@ mod tests {
         let dependency = get_dependency("=1");
         assert!(dependency_version_string(&dependency).is_err());
     }
+
+    #[test]
+    fn groups_search_results() {
+        let results = search_results(
+            "api",
+            [
+                "https://example.com/artifactory/api/storage/protos/my-api/my-api-0.2.0.tgz",
+                "protos/my-api/my-api-0.1.0.tgz",
+                "protos/my-api/my-api-0.1.0.tgz",
+                "protos/my-api/README.md",
+                "protos/my-lib/my-lib-1.0.0.tgz",
+                "internal/api/api-1.0.0-rc.1.tgz",
+                "my-api-1.0.0.tgz",
+            ],
+        );
+
+        assert_eq!(
+            results,
+            vec![
+                SearchResult {
+                    repository: "internal".into(),
+                    name: PackageName::unchecked("api"),
+                    versions: vec![Version::parse("1.0.0-rc.1").unwrap()],
+                },
+                SearchResult {
+                    repository: "protos".into(),
+                    name: PackageName::unchecked("my-api"),
+                    versions: vec![Version::new(0, 1, 0), Version::new(0, 2, 0)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn index_registry_uris() {
+        let registry = RegistryUri::from_str("index+https://my-registry.com/protos").unwrap();
+        assert_eq!(registry.kind(), RegistryKind::Index);
+        assert_eq!(
+            registry.base_url().as_str(),
+            "https://my-registry.com/protos"
+        );
+
+        assert!(RegistryUri::from_str("index+ftp://my-registry.com").is_err());
+        assert!(RegistryUri::from_str("index+file:///srv/protos").is_err());
+    }
+
+    #[test]
+    fn oci_registry_uris() {
+        let registry = RegistryUri::from_str("oci://ghcr.io/helsing-ai/protos").unwrap();
+        assert_eq!(registry.kind(), RegistryKind::Oci);
+        assert_eq!(
+            registry.base_url().as_str(),
+            "https://ghcr.io/v2/helsing-ai/protos"
+        );
+
+        let registry = RegistryUri::from_str("oci://localhost:5000").unwrap();
+        assert_eq!(registry.base_url().as_str(), "http://localhost:5000/v2");
+
+        let registry = RegistryUri::from_str("oci://127.0.0.1:5000/protos").unwrap();
+        assert_eq!(
+            registry.base_url().as_str(),
+            "http://127.0.0.1:5000/v2/protos"
+        );
+
+        assert!(RegistryUri::from_str("oci:///protos").is_err());
+    }
+
+    #[test]
+    fn s3_registry_uris() {
+        let registry = RegistryUri::from_str("s3://my-bucket/protos").unwrap();
+        assert_eq!(registry.kind(), RegistryKind::S3);
+        assert_eq!(registry.host_str(), Some("my-bucket"));
+        assert_eq!(registry.path(), "/protos");
+
+        assert!(RegistryUri::from_str("s3:///protos").is_err());
+    }
+
+    #[test]
+    fn file_registry_uris() {
+        let registry = RegistryUri::from_str("file:///srv/protos").unwrap();
+        assert_eq!(registry.scheme(), "file");
+
+        assert!(RegistryUri::from_str("file://remote-host/srv/protos").is_err());
+        assert!(RegistryUri::from_str("ftp://my-registry.com").is_err());
+    }
 }
@@ -0,0 +1,803 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use bytes::Bytes;
+use miette::{Context, IntoDiagnostic, bail, ensure, miette};
+use reqwest::{
+    Method, Response, StatusCode,
+    header::{self, HeaderValue},
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    Registry, RegistryUri, SearchResult,
+    http::{self, ValidatedResponse},
+};
+use crate::{
+    credentials::Credentials,
+    lock::{Digest, DigestAlgorithm},
+    manifest::{Dependency, DependencyManifest},
+    package::{Package, PackageName},
+};
+
+/// Media type of OCI image manifests
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+/// Artifact type of buffrs packages
+const ARTIFACT_TYPE: &str = "application/vnd.buffrs.package.v1";
+/// Media type of the config blob, which holds the package manifest
+const CONFIG_MEDIA_TYPE: &str = "application/vnd.buffrs.manifest.v1+toml";
+/// Media type of the layer, which holds the package archive
+const LAYER_MEDIA_TYPE: &str = "application/vnd.buffrs.package.v1.tar+gzip";
+/// Header carrying the digest of a manifest returned by the registry
+const CONTENT_DIGEST_HEADER: &str = "docker-content-digest";
+
+/// The registry implementation for OCI distribution registries
+///
+/// Every package is stored as an artifact in the OCI repository `<namespace>/<repository>/<name>`,
+/// tagged with its version. The package manifest is the config blob and the archive is the only
+/// layer of the artifact. The digest of the artifact manifest is recorded in the lockfile, so
+/// locked installations download the same artifact even if its tag is moved.
+#[derive(Debug)]
+pub struct OciRegistry {
+    registry: RegistryUri,
+    base: Url,
+    credentials: Option<String>,
+    /// Authorization obtained from the last authentication challenge
+    authorization: Mutex<Option<HeaderValue>>,
+    client: reqwest::Client,
+}
+
+/// An OCI image manifest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct ImageManifest {
+    schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    media_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artifact_type: Option<String>,
+    config: Descriptor,
+    layers: Vec<Descriptor>,
+}
+
+/// A reference to a blob of an OCI repository
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    media_type: String,
+    digest: Digest,
+    size: usize,
+}
+
+impl Descriptor {
+    fn new(media_type: &str, data: &[u8]) -> Self {
+        Self {
+            media_type: media_type.to_owned(),
+            digest: DigestAlgorithm::SHA256.digest(data),
+            size: data.len(),
+        }
+    }
+}
+
+impl ImageManifest {
+    /// Describes a package as an OCI artifact
+    fn new(config: &[u8], tgz: &[u8]) -> Self {
+        Self {
+            schema_version: 2,
+            media_type: Some(MANIFEST_MEDIA_TYPE.to_owned()),
+            artifact_type: Some(ARTIFACT_TYPE.to_owned()),
+            config: Descriptor::new(CONFIG_MEDIA_TYPE, config),
+            layers: vec![Descriptor::new(LAYER_MEDIA_TYPE, tgz)],
+        }
+    }
+
+    /// Returns the layer holding the package archive
+    fn package_layer(&self) -> Option<&Descriptor> {
+        self.layers
+            .iter()
+            .find(|layer| layer.media_type == LAYER_MEDIA_TYPE)
+    }
+}
+
+/// The tags of an OCI repository
+#[derive(Debug, Deserialize)]
+struct TagList {
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+/// The repositories listed by the catalog of a registry
+#[derive(Debug, Deserialize)]
+struct Catalog {
+    #[serde(default)]
+    repositories: Option<Vec<String>>,
+}
+
+/// The response of a token endpoint
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+/// Converts a version into a tag, as tags cannot contain `+`
+fn version_tag(version: &Version) -> String {
+    version.to_string().replace('+', "_")
+}
+
+/// Converts a tag into a version, ignoring tags like `latest`
+fn tag_version(tag: &str) -> Option<Version> {
+    Version::parse(&tag.replace('_', "+")).ok()
+}
+
+/// Parses a `WWW-Authenticate` header into its scheme and parameters
+fn parse_challenge(value: &str) -> Option<(String, Vec<(String, String)>)> {
+    let (scheme, mut rest) = value.trim().split_once(' ').unwrap_or((value.trim(), ""));
+    let mut params = Vec::new();
+
+    while let Some((key, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+
+        let (value, remainder) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+
+        params.push((key.trim().to_owned(), value.to_owned()));
+        rest = remainder.trim_start_matches([',', ' ']);
+    }
+
+    Some((scheme.to_ascii_lowercase(), params))
+}
+
+/// Computes the digest of a manifest, verifying it against the digest reported by the registry
+fn manifest_digest(body: &[u8], reported: Option<&Digest>) -> miette::Result<Digest> {
+    let Some(reported) = reported else {
+        return Ok(DigestAlgorithm::SHA256.digest(body));
+    };
+
+    let digest = reported.algorithm().digest(body);
+
+    ensure!(
+        &digest == reported,
+        "manifest digest mismatch - reported {reported}, actual {digest}"
+    );
+
+    Ok(digest)
+}
+
+/// Extracts the next page from a `Link` header
+fn next_page(base: &Url, response: &Response) -> Option<Url> {
+    let link = response.headers().get(header::LINK)?.to_str().ok()?;
+    let (target, relation) = link.split_once(';')?;
+
+    if !relation.contains("rel=\"next\"") {
+        return None;
+    }
+
+    base.join(target.trim().trim_start_matches('<').trim_end_matches('>'))
+        .ok()
+}
+
+impl OciRegistry {
+    /// Creates a new instance of an OCI registry client
+    pub fn new(registry: RegistryUri, credentials: &Credentials) -> miette::Result<Self> {
+        // Registries commonly redirect blob downloads to object storage
+        let client = http::client().build().into_diagnostic()?;
+
+        Ok(Self {
+            base: registry.base_url(),
+            credentials: credentials.registry_tokens.get(&registry).cloned(),
+            authorization: Mutex::new(None),
+            registry,
+            client,
+        })
+    }
+
+    /// Resolves a path of an OCI repository
+    fn url(&self, repository: &str, name: &PackageName, path: &str) -> Url {
+        let mut url = self.base.clone();
+        url.set_path(&format!(
+            "{}/{repository}/{name}/{path}",
+            self.base.path().trim_end_matches('/')
+        ));
+        url
+    }
+
+    /// Resolves the manifest of an artifact, referenced by tag or digest
+    fn manifest_url(&self, repository: &str, name: &PackageName, reference: &str) -> Url {
+        self.url(repository, name, &format!("manifests/{reference}"))
+    }
+
+    fn blob_url(&self, repository: &str, name: &PackageName, digest: &Digest) -> Url {
+        self.url(repository, name, &format!("blobs/{digest}"))
+    }
+
+    /// The credentials as an authorization header value for the given scheme
+    fn credentials_for(&self, scheme: &str) -> Option<String> {
+        let credentials = self.credentials.as_ref()?;
+
+        match (scheme, credentials.contains(':')) {
+            ("basic", true) | ("bearer", true) => {
+                Some(format!("Basic {}", BASE64_STANDARD.encode(credentials)))
+            }
+            ("bearer", false) => Some(format!("Bearer {credentials}")),
+            _ => None,
+        }
+    }
+
+    /// Obtains a bearer token from the token endpoint of an authentication challenge
+    async fn fetch_token(&self, params: &[(String, String)]) -> miette::Result<String> {
+        let realm = params
+            .iter()
+            .find(|(key, _)| key == "realm")
+            .map(|(_, value)| value)
+            .ok_or_else(|| {
+                miette!(
+                    "authentication challenge of {} lacks a realm",
+                    self.registry
+                )
+            })?;
+
+        let mut url = Url::parse(realm)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("invalid token endpoint {realm}"))?;
+
+        for (key, value) in params.iter().filter(|(key, _)| key != "realm") {
+            url.query_pairs_mut().append_pair(key, value);
+        }
+
+        tracing::debug!("requesting token from {url}");
+
+        let mut request = self.client.get(url);
+
+        if let Some(authorization) = self.credentials_for("bearer") {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+
+        let ValidatedResponse(response) = self.execute(request).await?.try_into()?;
+        let body = response.bytes().await.into_diagnostic()?;
+        let response: TokenResponse = serde_json::from_slice(&body).into_diagnostic()?;
+
+        response
+            .token
+            .or(response.access_token)
+            .ok_or_else(|| miette!("token endpoint of {} returned no token", self.registry))
+    }
+
+    /// Sends a request, answering authentication challenges of the registry
+    async fn send(&self, request: reqwest::RequestBuilder) -> miette::Result<Response> {
+        let retry = request.try_clone();
+
+        let authorization = self.authorization.lock().unwrap().clone();
+        let request = match authorization {
+            Some(authorization) => request.header(header::AUTHORIZATION, authorization),
+            None => request,
+        };
+
+        let response = self.execute(request).await?;
+        tracing::debug!("HTTP response received, status: {}", response.status());
+
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        let challenge = response
+            .headers()
+            .get(header::WWW_AUTHENTICATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_challenge);
+
+        let (Some(retry), Some((scheme, params))) = (retry, challenge) else {
+            return Ok(response);
+        };
+
+        let authorization = match scheme.as_str() {
+            "bearer" => format!("Bearer {}", self.fetch_token(&params).await?),
+            "basic" => match self.credentials_for("basic") {
+                Some(authorization) => authorization,
+                None => return Ok(response),
+            },
+            _ => return Ok(response),
+        };
+
+        let authorization = HeaderValue::from_str(&authorization).into_diagnostic()?;
+        *self.authorization.lock().unwrap() = Some(authorization.clone());
+
+        self.execute(retry.header(header::AUTHORIZATION, authorization))
+            .await
+    }
+
+    /// Sends a request, retrying transient failures
+    async fn execute(&self, request: reqwest::RequestBuilder) -> miette::Result<Response> {
+        let request = request.build().into_diagnostic()?;
+
+        http::execute(&self.client, request).await.into_diagnostic()
+    }
+
+    /// Fetches the manifest of an artifact by tag or digest, which is `None` if it does not exist
+    ///
+    /// The manifest is returned along with its digest, which is verified against the digest
+    /// reported by the registry.
+    async fn fetch_manifest(
+        &self,
+        repository: &str,
+        name: &PackageName,
+        reference: &str,
+    ) -> miette::Result<Option<(ImageManifest, Digest)>> {
+        let url = self.manifest_url(repository, name, reference);
+
+        tracing::debug!("fetching artifact manifest from {url}");
+
+        let response = self
+            .send(
+                self.client
+                    .get(url.clone())
+                    .header(header::ACCEPT, MANIFEST_MEDIA_TYPE),
+            )
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let ValidatedResponse(response) = response.try_into()?;
+
+        let reported = response
+            .headers()
+            .get(CONTENT_DIGEST_HEADER)
+            .map(|digest| digest.to_str().into_diagnostic()?.parse().into_diagnostic())
+            .transpose()
+            .wrap_err_with(|| format!("invalid manifest digest reported for {url}"))?;
+
+        let body = response.bytes().await.into_diagnostic()?;
+        let digest = manifest_digest(&body, reported.as_ref())
+            .wrap_err_with(|| format!("failed to verify the artifact manifest at {url}"))?;
+
+        let manifest = serde_json::from_slice(&body)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to parse the artifact manifest at {url}"))?;
+
+        Ok(Some((manifest, digest)))
+    }
+
+    /// Uploads a blob unless the repository already contains it
+    async fn upload_blob(
+        &self,
+        repository: &str,
+        name: &PackageName,
+        descriptor: &Descriptor,
+        data: Bytes,
+    ) -> miette::Result<()> {
+        let response = self
+            .send(
+                self.client
+                    .head(self.blob_url(repository, name, &descriptor.digest)),
+            )
+            .await?;
+
+        if response.status().is_success() {
+            tracing::debug!("blob {} already exists", descriptor.digest);
+            return Ok(());
+        }
+
+        let ValidatedResponse(response) = self
+            .send(
+                self.client
+                    .post(self.url(repository, name, "blobs/uploads/"))
+                    .body(Vec::new()),
+            )
+            .await?
+            .try_into()?;
+
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| miette!("{} did not return an upload location", self.registry))?;
+
+        let mut url = self.base.join(location).into_diagnostic()?;
+
+        url.query_pairs_mut()
+            .append_pair("digest", &descriptor.digest.to_string());
+
+        let ValidatedResponse(_) = self
+            .send(
+                self.client
+                    .put(url)
+                    .header(header::CONTENT_TYPE, "application/octet-stream")
+                    .body(data),
+            )
+            .await?
+            .try_into()
+            .wrap_err_with(|| format!("failed to upload blob {}", descriptor.digest))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Registry for OciRegistry {
+    /// Downloads the layer of a tagged artifact and verifies it against the manifest
+    async fn download(&self, dependency: Dependency) -> miette::Result<Package> {
+        self.download_artifact(dependency, None)
+            .await
+            .map(|(package, _)| package)
+    }
+
+    /// Downloads the layer of an artifact, through the pinned manifest if one is given
+    async fn download_artifact(
+        &self,
+        dependency: Dependency,
+        manifest: Option<Digest>,
+    ) -> miette::Result<(Package, Option<Digest>)> {
+        let DependencyManifest::Remote(ref remote) = dependency.manifest else {
+            bail!(
+                "unable to download local dependency ({}) from a registry",
+                dependency.package
+            );
+        };
+
+        let version =
+            Version::parse(&super::dependency_version_string(&dependency)?).into_diagnostic()?;
+
+        let reference = match &manifest {
+            Some(digest) => digest.to_string(),
+            None => version_tag(&version),
+        };
+
+        let (artifact, digest) = self
+            .fetch_manifest(&remote.repository, &dependency.package, &reference)
+            .await?
+            .ok_or_else(|| {
+                miette!(
+                    "{}@{} ({}) is not published to repository {}",
+                    dependency.package,
+                    version,
+                    reference,
+                    remote.repository
+                )
+            })?;
+
+        if let Some(pinned) = &manifest {
+            ensure!(
+                &digest == pinned,
+                "manifest digest mismatch for {}@{} - expected {}, actual {}",
+                dependency.package,
+                version,
+                pinned,
+                digest
+            );
+        }
+
+        let layer = artifact.package_layer().ok_or_else(|| {
+            miette!(
+                "{}@{} is not a buffrs package - no layer of type {LAYER_MEDIA_TYPE}",
+                dependency.package,
+                version
+            )
+        })?;
+
+        let url = self.blob_url(&remote.repository, &dependency.package, &layer.digest);
+
+        tracing::debug!("downloading package from {url}");
+
+        let ValidatedResponse(response) = self.send(self.client.get(url)).await?.try_into()?;
+        let data = response.bytes().await.into_diagnostic()?;
+
+        let actual = layer.digest.algorithm().digest(&data);
+
+        ensure!(
+            actual == layer.digest,
+            "digest mismatch for {}@{} - expected {}, actual {}",
+            dependency.package,
+            version,
+            layer.digest,
+            actual
+        );
+
+        let package = Package::try_from(data).wrap_err(miette!(
+            "failed to download dependency {}",
+            dependency.package
+        ))?;
+
+        Ok((package, Some(digest)))
+    }
+
+    /// Uploads the blobs of a package and tags its manifest with the version
+    async fn publish(&self, package: Package, repository: String) -> miette::Result<()> {
+        let config: String = package.manifest.clone().try_into().into_diagnostic()?;
+        let config = Bytes::from(config);
+        let artifact = ImageManifest::new(&config, &package.tgz);
+
+        let tag = version_tag(package.version());
+        let existing = self
+            .fetch_manifest(&repository, package.name(), &tag)
+            .await?;
+
+        if let Some((existing, _)) = existing {
+            ensure!(
+                existing.package_layer() == artifact.package_layer(),
+                "unable to publish {}: package is already published with a different hash",
+                package.name()
+            );
+
+            tracing::info!(
+                "{}/{}@{} is already published, skipping",
+                repository,
+                package.name(),
+                package.version()
+            );
+
+            return Ok(());
+        }
+
+        self.upload_blob(&repository, package.name(), &artifact.config, config)
+            .await?;
+
+        self.upload_blob(
+            &repository,
+            package.name(),
+            &artifact.layers[0],
+            package.tgz.clone(),
+        )
+        .await?;
+
+        // The manifest is pushed last, so tags never refer to missing blobs
+        let body = serde_json::to_vec(&artifact).into_diagnostic()?;
+
+        let ValidatedResponse(_) = self
+            .send(
+                self.client
+                    .request(
+                        Method::PUT,
+                        self.manifest_url(&repository, package.name(), &tag),
+                    )
+                    .header(header::CONTENT_TYPE, MANIFEST_MEDIA_TYPE)
+                    .body(body),
+            )
+            .await?
+            .try_into()
+            .wrap_err("failed to push the artifact manifest")?;
+
+        tracing::info!(
+            "published {}/{}@{}",
+            repository,
+            package.name(),
+            package.version()
+        );
+
+        Ok(())
+    }
+
+    /// Lists the tags of the OCI repository of a package which are versions
+    async fn list_versions(
+        &self,
+        repository: String,
+        name: PackageName,
+    ) -> miette::Result<Vec<Version>> {
+        let mut versions = Vec::new();
+        let mut next = Some(self.url(&repository, &name, "tags/list"));
+
+        while let Some(url) = next {
+            let response = self.send(self.client.get(url)).await?;
+
+            if response.status() == StatusCode::NOT_FOUND {
+                break;
+            }
+
+            let ValidatedResponse(response) = response.try_into()?;
+
+            next = next_page(&self.base, &response);
+
+            let body = response.bytes().await.into_diagnostic()?;
+            let tags: TagList = serde_json::from_slice(&body).into_diagnostic()?;
+
+            versions.extend(
+                tags.tags
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|tag| tag_version(&tag)),
+            );
+        }
+
+        versions.sort();
+        versions.dedup();
+
+        Ok(versions)
+    }
+
+    /// Searches the catalog of the registry for packages below the namespace of the registry
+    ///
+    /// The versions of every matching package are listed separately. This requires the catalog
+    /// API, which some registries restrict or do not offer at all.
+    async fn search(
+        &self,
+        term: &str,
+        repository: Option<String>,
+    ) -> miette::Result<Vec<SearchResult>> {
+        let namespace = self.registry.path().trim_matches('/');
+
+        let mut catalog = self.base.clone();
+        catalog.set_path("/v2/_catalog");
+
+        let mut packages = Vec::new();
+        let mut next = Some(catalog);
+
+        while let Some(url) = next {
+            let ValidatedResponse(response) = self
+                .send(self.client.get(url))
+                .await?
+                .try_into()
+                .wrap_err_with(|| format!("failed to list the catalog of {}", self.registry))?;
+
+            next = next_page(&self.base, &response);
+
+            let body = response.bytes().await.into_diagnostic()?;
+            let catalog: Catalog = serde_json::from_slice(&body).into_diagnostic()?;
+
+            for path in catalog.repositories.into_iter().flatten() {
+                let relative = match namespace {
+                    "" => Some(path.as_str()),
+                    namespace => path
+                        .strip_prefix(namespace)
+                        .and_then(|path| path.strip_prefix('/')),
+                };
+
+                let Some((found, name)) = relative.and_then(|path| path.split_once('/')) else {
+                    continue;
+                };
+
+                if repository
+                    .as_deref()
+                    .is_some_and(|repository| repository != found)
+                {
+                    continue;
+                }
+
+                let Ok(name) = PackageName::new(name) else {
+                    continue;
+                };
+
+                if name.contains(term) {
+                    packages.push((found.to_owned(), name));
+                }
+            }
+        }
+
+        let mut results = Vec::new();
+
+        for (repository, name) in packages {
+            let versions = self.list_versions(repository.clone(), name.clone()).await?;
+
+            if !versions.is_empty() {
+                results.push(SearchResult {
+                    repository,
+                    name,
+                    versions,
+                });
+            }
+        }
+
+        results.sort();
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use semver::Version;
+
+    use super::{
+        ImageManifest, OciRegistry, manifest_digest, parse_challenge, tag_version, version_tag,
+    };
+    use crate::{
+        credentials::Credentials, lock::DigestAlgorithm, package::PackageName,
+        registry::RegistryUri,
+    };
+
+    #[test]
+    fn resolves_repository_locations() {
+        let registry = RegistryUri::from_str("oci://ghcr.io/helsing-ai").unwrap();
+        let registry = OciRegistry::new(registry, &Credentials::default()).unwrap();
+        let name = PackageName::unchecked("my-package");
+
+        assert_eq!(
+            registry
+                .manifest_url(
+                    "my-repo",
+                    &name,
+                    &version_tag(&Version::parse("1.0.0+build.1").unwrap())
+                )
+                .as_str(),
+            "https://ghcr.io/v2/helsing-ai/my-repo/my-package/manifests/1.0.0_build.1"
+        );
+        assert_eq!(
+            registry.url("my-repo", &name, "tags/list").as_str(),
+            "https://ghcr.io/v2/helsing-ai/my-repo/my-package/tags/list"
+        );
+    }
+
+    #[test]
+    fn maps_versions_to_tags() {
+        let version = Version::parse("1.2.3-rc.1+build.5").unwrap();
+
+        assert_eq!(version_tag(&version), "1.2.3-rc.1_build.5");
+        assert_eq!(tag_version(&version_tag(&version)), Some(version));
+        assert_eq!(tag_version("latest"), None);
+    }
+
+    #[test]
+    fn parses_challenges() {
+        let (scheme, params) = parse_challenge(
+            r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:a/b:pull,push""#,
+        )
+        .unwrap();
+
+        assert_eq!(scheme, "bearer");
+        assert_eq!(
+            params,
+            vec![
+                ("realm".to_owned(), "https://ghcr.io/token".to_owned()),
+                ("service".to_owned(), "ghcr.io".to_owned()),
+                ("scope".to_owned(), "repository:a/b:pull,push".to_owned()),
+            ]
+        );
+
+        let (scheme, params) = parse_challenge(r#"Basic realm="zot""#).unwrap();
+
+        assert_eq!(scheme, "basic");
+        assert_eq!(params, vec![("realm".to_owned(), "zot".to_owned())]);
+    }
+
+    #[test]
+    fn verifies_manifest_digests() {
+        let digest = DigestAlgorithm::SHA256.digest(b"{}");
+
+        assert_eq!(manifest_digest(b"{}", None).unwrap(), digest);
+        assert_eq!(manifest_digest(b"{}", Some(&digest)).unwrap(), digest);
+        assert!(manifest_digest(b"{ }", Some(&digest)).is_err());
+    }
+
+    #[test]
+    fn writes_deterministic_manifests() {
+        let manifest = ImageManifest::new(b"[package]", b"archive");
+
+        assert_eq!(manifest, ImageManifest::new(b"[package]", b"archive"));
+        assert_eq!(
+            serde_json::to_string(&manifest).unwrap(),
+            "{\"schemaVersion\":2,\
+             \"mediaType\":\"application/vnd.oci.image.manifest.v1+json\",\
+             \"artifactType\":\"application/vnd.buffrs.package.v1\",\
+             \"config\":{\"mediaType\":\"application/vnd.buffrs.manifest.v1+toml\",\
+             \"digest\":\"sha256:70acf00586aa7b90c3866278505be7b81fb7ee1f7e21c17c1a22ac239be3c72a\",\"size\":9},\
+             \"layers\":[{\"mediaType\":\"application/vnd.buffrs.package.v1.tar+gzip\",\
+             \"digest\":\"sha256:0eb3e36bfb24dcd9bb1d1bece1531216b59539a8fde17ee80224af0653c92aa3\",\"size\":7}]}"
+        );
+    }
+}
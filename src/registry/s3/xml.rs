@@ -0,0 +1,263 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use miette::{bail, ensure, miette};
+
+/// Returns the text of every element at a path of element names, starting at the root element
+///
+/// Attributes, comments and processing instructions are skipped, namespace prefixes of element
+/// names are ignored and the text of CDATA sections is included. Entity and character
+/// references are decoded. Document type declarations are rejected, since S3 responses do not
+/// contain them.
+pub(super) fn texts(document: &str, path: &[&str]) -> miette::Result<Vec<String>> {
+    let mut open: Vec<&str> = Vec::new();
+    let mut texts = Vec::new();
+    let mut text = String::new();
+    let mut rest = document;
+    let mut root = false;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            ensure!(rest.trim().is_empty(), "text outside of the root element");
+            break;
+        };
+
+        if open == path {
+            decode(&rest[..start], &mut text)?;
+        } else if open.is_empty() {
+            ensure!(
+                rest[..start].trim().is_empty(),
+                "text outside of the root element"
+            );
+        }
+
+        rest = &rest[start..];
+
+        if let Some(markup) = rest.strip_prefix("<?") {
+            rest = skip_past(markup, "?>")?;
+        } else if let Some(markup) = rest.strip_prefix("<!--") {
+            rest = skip_past(markup, "-->")?;
+        } else if let Some(markup) = rest.strip_prefix("<![CDATA[") {
+            let end = markup
+                .find("]]>")
+                .ok_or_else(|| miette!("unterminated CDATA"))?;
+
+            if open == path {
+                text.push_str(&markup[..end]);
+            }
+
+            rest = &markup[end + 3..];
+        } else if rest.starts_with("<!") {
+            bail!("document type declarations are not supported");
+        } else if let Some(markup) = rest.strip_prefix("</") {
+            let end = markup
+                .find('>')
+                .ok_or_else(|| miette!("unterminated tag"))?;
+            let name = markup[..end].trim_end();
+
+            ensure!(
+                open.last().is_some_and(|open| *open == local_name(name)),
+                "unexpected closing tag {name}"
+            );
+
+            if open == path {
+                texts.push(std::mem::take(&mut text));
+            }
+
+            open.pop();
+            rest = &markup[end + 1..];
+        } else {
+            let markup = &rest[1..];
+            let end = tag_end(markup)?;
+            let tag = &markup[..end];
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+
+            let name = tag
+                .split(|c: char| c.is_ascii_whitespace())
+                .next()
+                .unwrap_or_default();
+
+            ensure!(!name.is_empty(), "tag without a name");
+            ensure!(!(open.is_empty() && root), "multiple root elements");
+
+            root = true;
+            open.push(local_name(name));
+
+            if open == path {
+                text.clear();
+            }
+
+            if empty {
+                if open == path {
+                    texts.push(String::new());
+                }
+
+                open.pop();
+            }
+
+            rest = &markup[end + 1..];
+        }
+    }
+
+    ensure!(root, "no root element");
+    ensure!(open.is_empty(), "unclosed element {}", open.join("/"));
+
+    Ok(texts)
+}
+
+/// Returns the rest of the markup after a terminator
+fn skip_past<'a>(markup: &'a str, terminator: &str) -> miette::Result<&'a str> {
+    let end = markup
+        .find(terminator)
+        .ok_or_else(|| miette!("unterminated markup, expected {terminator}"))?;
+
+    Ok(&markup[end + terminator.len()..])
+}
+
+/// Finds the `>` ending a start tag, which may also appear in quoted attribute values
+fn tag_end(markup: &str) -> miette::Result<usize> {
+    let mut quote = None;
+
+    for (index, c) in markup.char_indices() {
+        match (quote, c) {
+            (None, '>') => return Ok(index),
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            _ => {}
+        }
+    }
+
+    bail!("unterminated tag")
+}
+
+/// Strips the namespace prefix of an element name
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Appends text with its entity and character references decoded
+fn decode(mut raw: &str, text: &mut String) -> miette::Result<()> {
+    while let Some(start) = raw.find('&') {
+        text.push_str(&raw[..start]);
+
+        let reference = &raw[start + 1..];
+        let end = reference
+            .find(';')
+            .ok_or_else(|| miette!("unterminated reference"))?;
+
+        let decoded = match &reference[..end] {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            number => number
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| number.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32)
+                .ok_or_else(|| miette!("invalid reference &{number};"))?,
+        };
+
+        text.push(decoded);
+        raw = &reference[end + 1..];
+    }
+
+    text.push_str(raw);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::texts;
+
+    const LISTING: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>bucket</Name>
+  <Prefix>repo/</Prefix>
+  <IsTruncated>true</IsTruncated>
+  <!-- <Key>commented</Key> -->
+  <Contents>
+    <Key>repo/lib/lib-0.1.0.tgz</Key>
+    <ETag>&quot;bf1d737a4d46a19f3bced6905cc8b902&quot;</ETag>
+  </Contents>
+  <Contents>
+    <Key>repo/lib/a&amp;b&#x3C;&#62;.tgz</Key>
+  </Contents>
+  <Contents><Key><![CDATA[repo/<lib>/]]>x.tgz</Key></Contents>
+  <Contents><Key/></Contents>
+  <CommonPrefixes><Prefix>repo/lib/</Prefix></CommonPrefixes>
+  <NextContinuationToken>1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=</NextContinuationToken>
+</ListBucketResult>"#;
+
+    #[test]
+    fn reads_elements_at_a_path() {
+        assert_eq!(
+            texts(LISTING, &["ListBucketResult", "Contents", "Key"]).unwrap(),
+            vec![
+                "repo/lib/lib-0.1.0.tgz",
+                "repo/lib/a&b<>.tgz",
+                "repo/<lib>/x.tgz",
+                ""
+            ]
+        );
+        assert_eq!(
+            texts(LISTING, &["ListBucketResult", "Prefix"]).unwrap(),
+            vec!["repo/"]
+        );
+        assert_eq!(
+            texts(LISTING, &["ListBucketResult", "Contents", "ETag"]).unwrap(),
+            vec!["\"bf1d737a4d46a19f3bced6905cc8b902\""]
+        );
+        assert!(
+            texts(LISTING, &["ListBucketResult", "KeyCount"])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn ignores_namespace_prefixes_and_attributes() {
+        let document =
+            r#"<s3:Result xmlns:s3="urn:s3" a="1>2"><s3:Key id='k'>key</s3:Key></s3:Result>"#;
+
+        assert_eq!(texts(document, &["Result", "Key"]).unwrap(), vec!["key"]);
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        for document in [
+            "",
+            "<Result><Key>key</Result>",
+            "<Result><Key>key</Key>",
+            "<Result>&unknown;</Result>",
+            "<Result>&#xD800;</Result>",
+            "<Result>&amp</Result>",
+            "<Result/><Result/>",
+            "text<Result/>",
+            "<!DOCTYPE Result [<!ENTITY e 'x'>]><Result/>",
+            "<Result><![CDATA[text</Result>",
+        ] {
+            assert!(
+                texts(document, &["Result"]).is_err(),
+                "{document} is accepted"
+            );
+        }
+    }
+}
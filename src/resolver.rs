@@ -2,25 +2,30 @@
 
 use std::{
     collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    num::NonZeroUsize,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
-use async_recursion::async_recursion;
-use miette::{Context as _, Diagnostic, bail};
-use semver::VersionReq;
+use miette::{Context as _, Diagnostic, IntoDiagnostic, NamedSource, SourceSpan, bail, ensure};
+use semver::{Version, VersionReq};
 use thiserror::Error;
+use tokio::task::JoinSet;
 
 use crate::{
-    cache::Cache,
+    cache::{Cache, Entry as CacheEntry},
     credentials::Credentials,
-    lock::Lockfile,
+    lock::{Digest, LockedGitSource, Lockfile},
     manifest::{
-        Dependency, DependencyManifest, LocalDependencyManifest, MANIFEST_FILE, Manifest,
-        PackagesManifest,
+        Dependency, DependencyManifest, GitDependencyManifest, LocalDependencyManifest,
+        MANIFEST_FILE, Manifest, PackagesManifest, RemoteDependencyManifest,
+        TarballDependencyManifest,
     },
     operations::install::NetworkMode,
-    package::{PackageName, PackageType},
-    registry::{Artifactory, RegistryUri},
+    package::{Package, PackageName, PackageType},
+    registry::{self, Registry, RegistryUri},
+    util::normalize,
 };
 
 /// Models the source of a dependency
@@ -31,12 +36,31 @@ pub enum DependencySource {
         /// Local path
         path: PathBuf,
     },
+    /// A git dependency, checked out in the buffrs home
+    Git {
+        /// Path of the package inside the checkout
+        path: PathBuf,
+        /// Root directory of the checkout
+        root: PathBuf,
+        /// The repository and commit that was checked out
+        source: LockedGitSource,
+    },
+    /// A tarball dependency, downloaded from its URL
+    Tarball {
+        /// The declared tarball
+        manifest: TarballDependencyManifest,
+    },
     /// A remote dependencies, expressed by it's repo & registry
     Remote {
         /// Registry
         registry: RegistryUri,
         /// Repository name
         repository: String,
+        /// The package fetched while resolving, which is installed without downloading it again
+        package: Package,
+        /// Digest of the artifact manifest the package was published with, if the registry
+        /// stores packages as content addressed artifacts
+        manifest: Option<Digest>,
     },
 }
 
@@ -51,10 +75,34 @@ pub struct DependencyNode {
     pub source: DependencySource,
     /// Packages that this package depends on
     pub dependencies: Vec<PackageName>,
+    /// Dependencies as declared in the manifest of this package, before patches are applied
+    pub declarations: Vec<Dependency>,
     /// Version requirement
     pub version: VersionReq,
+    /// Concrete version selected for this package, if known
+    pub resolved_version: Option<Version>,
+    /// Contents of the manifest of local and git packages, to point at declarations in errors
+    pub manifest_source: Option<NamedSource<String>>,
 }
 
+/// A version requirement on a package, as declared by one of its dependants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRequirement {
+    /// The package declaring the requirement
+    pub dependant: String,
+    /// The declared version requirement
+    pub version: VersionReq,
+}
+
+impl fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (required by {})", self.version, self.dependant)
+    }
+}
+
+/// Default number of remote packages fetched at a time while building a dependency graph
+pub const DEFAULT_CONCURRENCY: NonZeroUsize = NonZeroUsize::new(8).unwrap();
+
 /// Maps a package name to metadata describing the package
 pub type MetadataMap = HashMap<PackageName, DependencyNode>;
 
@@ -90,33 +138,67 @@ impl DependencyGraph {
         lockfile: Option<Lockfile>,
         network_mode: NetworkMode,
     ) -> miette::Result<Self> {
-        let mut builder =
-            GraphBuilder::new(base_path.to_path_buf(), credentials, lockfile, network_mode);
+        Self::build_with_concurrency(
+            manifest,
+            base_path,
+            credentials,
+            lockfile,
+            network_mode,
+            DEFAULT_CONCURRENCY,
+        )
+        .await
+    }
+
+    /// Build a dependency graph from a manifest, fetching up to `concurrency` remote packages
+    /// at a time
+    ///
+    /// The resulting graph is the same for any level of concurrency.
+    pub async fn build_with_concurrency(
+        manifest: &PackagesManifest,
+        base_path: &Path,
+        credentials: &Credentials,
+        lockfile: Option<Lockfile>,
+        network_mode: NetworkMode,
+        concurrency: NonZeroUsize,
+    ) -> miette::Result<Self> {
+        let mut builder = GraphBuilder::new(credentials, lockfile, network_mode, concurrency);
 
-        // Get the parent package type from the manifest
-        let parent_package_type = manifest.package.as_ref().map(|p| p.kind);
+        // Patches are declared by the root manifest, so local patch paths are relative to it
+        builder.patches = manifest
+            .patches()
+            .iter()
+            .map(|patch| {
+                (
+                    patch.package.clone(),
+                    (patch.manifest.clone(), base_path.to_path_buf()),
+                )
+            })
+            .collect();
 
-        // Add root dependencies
-        for dependency in manifest.dependencies.iter().flatten() {
+        // Resolve until every package satisfies the requirements of all of its dependants
+        loop {
             builder
-                .add_dependency(dependency, parent_package_type)
-                .await
-                .wrap_err_with(|| {
-                    format!(
-                        "while resolving dependencies of {}",
-                        manifest
-                            .package
-                            .as_ref()
-                            .map(|p| p.name.to_string())
-                            .unwrap_or_else(|| "root".to_string())
-                    )
-                })?;
-        }
-
-        Ok(Self {
+                .resolve(PendingDependency::declared_by(manifest, base_path))
+                .await?;
+
+            if !builder.restart_required {
+                break;
+            }
+
+            tracing::debug!("re-resolving dependency graph with unified version requirements");
+
+            builder.restart();
+        }
+
+        let graph = Self {
             nodes: builder.nodes,
             network_mode,
-        })
+        };
+
+        // Packages are added breadth-first, so cycles are only detected on the complete graph
+        graph.topological_sort()?;
+
+        Ok(graph)
     }
 
     /// Returns dependencies in topological order (dependencies before dependents)
@@ -188,149 +270,501 @@ impl DependencyGraph {
             }
         }
 
-        // Cycles should not happen since we check during graph constructon, but
+        // Packages that are part of or depend on a cycle are never processed
         if sorted.len() != self.nodes.len() {
-            let unprocessed: Vec<_> = self
+            let unprocessed: HashSet<&PackageName> = self
                 .nodes
                 .keys()
                 .filter(|name| !sorted.contains(name))
-                .map(|n| n.to_string())
                 .collect();
 
-            return Err(DependencyError::CircularDependency(
-                unprocessed.join(" -> "),
-            ));
+            let cycle = self.find_cycle(&unprocessed);
+
+            let edges = cycle
+                .windows(2)
+                .filter_map(|pair| {
+                    let dependant = self.nodes.get(&pair[0])?;
+                    Some(CycleEdge::new(dependant, &pair[1]))
+                })
+                .collect();
+
+            return Err(DependencyError::CircularDependency { cycle, edges });
         }
 
         Ok(sorted)
     }
 
+    /// Follows dependencies between the given packages until one repeats
+    ///
+    /// Returns the packages of the cycle, starting and ending with the same package. Every
+    /// given package is expected to depend on another given package, which holds for the
+    /// packages left unprocessed by a topological sort.
+    fn find_cycle(&self, packages: &HashSet<&PackageName>) -> Vec<PackageName> {
+        let Some(mut current) = packages.iter().min().copied() else {
+            return vec![];
+        };
+
+        let mut path: Vec<&PackageName> = vec![];
+
+        loop {
+            if let Some(start) = path.iter().position(|name| *name == current) {
+                return path[start..]
+                    .iter()
+                    .chain([&current])
+                    .map(|name| (*name).clone())
+                    .collect();
+            }
+
+            path.push(current);
+
+            // Always take the smallest dependency, so the reported cycle is deterministic
+            let next = self.nodes.get(current).and_then(|node| {
+                node.dependencies
+                    .iter()
+                    .filter(|dependency| packages.contains(dependency))
+                    .min()
+            });
+
+            let Some(next) = next else {
+                return path.into_iter().cloned().collect();
+            };
+
+            current = next;
+        }
+    }
+
     /// Gets the number of packages that depend on a package
     pub fn dependants_count_of(&self, package_name: &PackageName) -> usize {
+        self.dependants_of(package_name).count()
+    }
+
+    /// Gets the packages in the graph that depend on a package
+    ///
+    /// The manifest the graph was built for is not part of the graph, so it is never returned.
+    pub fn dependants_of<'a>(
+        &'a self,
+        package_name: &'a PackageName,
+    ) -> impl Iterator<Item = &'a PackageName> {
         self.nodes
             .values()
             .filter(|node| node.dependencies.contains(package_name))
-            .count()
+            .map(|node| &node.name)
+    }
+}
+
+/// A dependency discovered during resolution that has yet to be added to the graph
+#[derive(Debug, Clone)]
+struct PendingDependency {
+    dependency: Dependency,
+    /// The package declaring the dependency, formatted as `name@version`
+    dependant: String,
+    /// Name and version of the package declaring the dependency, unless it is the root
+    declared_by: Option<(PackageName, Version)>,
+    /// Name of the package declaring the dependency, used for error context
+    parent: String,
+    parent_type: Option<PackageType>,
+    /// Directory that local dependency paths are relative to
+    base_path: PathBuf,
+}
+
+impl PendingDependency {
+    /// Collects the dependencies declared by a package manifest
+    fn declared_by(manifest: &PackagesManifest, base_path: &Path) -> Vec<Self> {
+        let (dependant, parent) = match &manifest.package {
+            Some(package) => (
+                format!("{}@{}", package.name, package.version),
+                package.name.to_string(),
+            ),
+            None => ("root".to_string(), "root".to_string()),
+        };
+
+        let parent_type = manifest.package.as_ref().map(|p| p.kind);
+        let declared_by = manifest
+            .package
+            .as_ref()
+            .map(|package| (package.name.clone(), package.version.clone()));
+
+        manifest
+            .dependencies
+            .iter()
+            .flatten()
+            .map(|dependency| Self {
+                dependency: dependency.clone(),
+                dependant: dependant.clone(),
+                declared_by: declared_by.clone(),
+                parent: parent.clone(),
+                parent_type,
+                base_path: base_path.to_path_buf(),
+            })
+            .collect()
     }
+
+    fn context(&self) -> String {
+        format!("while resolving dependencies of {}", self.parent)
+    }
+}
+
+/// A version requirement recorded during resolution, along with the package version declaring it
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecordedRequirement {
+    /// Name and version of the dependant, unless it is the root
+    declared_by: Option<(PackageName, Version)>,
+    requirement: VersionRequirement,
 }
 
 /// Internal builder for constructing the dependency graph
 struct GraphBuilder<'a> {
     nodes: HashMap<PackageName, DependencyNode>,
-    base_path: PathBuf,
-    /// Track which packages we're currently visiting to detect cycles during construction
-    visiting: HashSet<PackageName>,
     credentials: &'a Credentials,
     lockfile: Option<Lockfile>,
-    registry_clients: HashMap<RegistryUri, Artifactory>,
+    registry_clients: HashMap<RegistryUri, Arc<dyn Registry>>,
     network_mode: NetworkMode,
+    /// Maximum number of registry requests in flight at a time
+    concurrency: NonZeroUsize,
+    /// Replacements for dependencies, along with the directory that local paths are relative to
+    patches: HashMap<PackageName, (DependencyManifest, PathBuf)>,
+    /// Version requirements of all dependants seen so far, kept across restarts
+    ///
+    /// Requirements of dependant versions which are no longer part of the graph are kept as
+    /// well, but ignored, see [`GraphBuilder::is_live`].
+    requirements: HashMap<PackageName, Vec<RecordedRequirement>>,
+    /// Versions selected by the previous attempt, for packages not yet added in this attempt
+    previous: HashMap<PackageName, Option<Version>>,
+    /// Packages fetched so far along with their artifact manifests, kept across restarts
+    packages: HashMap<(PackageName, Version), (Package, Option<Digest>)>,
+    /// Set when a package was resolved to a version that a later dependant does not accept
+    restart_required: bool,
 }
 
 impl<'a> GraphBuilder<'a> {
     fn new(
-        base_path: PathBuf,
         credentials: &'a Credentials,
         lockfile: Option<Lockfile>,
         network_mode: NetworkMode,
+        concurrency: NonZeroUsize,
     ) -> Self {
         Self {
             nodes: HashMap::new(),
-            base_path,
-            visiting: HashSet::new(),
             credentials,
             lockfile,
             registry_clients: HashMap::new(),
             network_mode,
+            concurrency,
+            patches: HashMap::new(),
+            requirements: HashMap::new(),
+            previous: HashMap::new(),
+            packages: HashMap::new(),
+            restart_required: false,
         }
     }
 
-    #[async_recursion]
-    async fn add_dependency(
-        &mut self,
-        dependency: &Dependency,
-        parent_type: Option<PackageType>,
-    ) -> miette::Result<()> {
-        let package_name = &dependency.package;
+    /// Discards the graph built so far while keeping the collected version requirements
+    fn restart(&mut self) {
+        self.previous = self
+            .nodes
+            .drain()
+            .map(|(name, node)| (name, node.resolved_version))
+            .collect();
 
-        // Check for cycle during traversal
-        if self.visiting.contains(package_name) {
-            bail!(DependencyError::CircularDependency(format!(
-                "detected while processing {}",
-                package_name
-            )));
-        }
+        self.restart_required = false;
+    }
 
-        // If already processed, just validate compatibility
-        if let Some(existing) = self.nodes.get(package_name) {
-            self.validate_compatibility(dependency, existing)
-                .wrap_err_with(|| format!("conflicting dependency on {}", package_name))?;
-            return Ok(());
+    /// Adds the given dependencies and all of their transitive dependencies to the graph
+    ///
+    /// The graph is built breadth-first. Remote packages discovered at the same depth are
+    /// fetched concurrently, but the graph is always extended in the order of the sorted
+    /// dependencies, so the result does not depend on the order in which requests complete.
+    async fn resolve(&mut self, dependencies: Vec<PendingDependency>) -> miette::Result<()> {
+        let mut level = dependencies;
+
+        while !level.is_empty() {
+            level.sort_by(|a, b| {
+                (&a.dependency.package, &a.dependant).cmp(&(&b.dependency.package, &b.dependant))
+            });
+
+            level = self.resolve_level(level).await?;
         }
 
-        // Mark as visiting
-        self.visiting.insert(package_name.clone());
+        Ok(())
+    }
+
+    /// Adds the dependencies found at one depth of the graph and returns those of the next
+    async fn resolve_level(
+        &mut self,
+        level: Vec<PendingDependency>,
+    ) -> miette::Result<Vec<PendingDependency>> {
+        let level: Vec<PendingDependency> = level
+            .into_iter()
+            .map(|pending| self.patch(pending))
+            .collect();
+
+        // Record all requirements first, so packages are resolved against every dependant
+        // found at this depth
+        let recorded: Vec<bool> = level
+            .iter()
+            .map(|pending| self.record_requirement(pending))
+            .collect();
+
+        let mut next = Vec::new();
+        let mut remote = Vec::new();
+        let mut existing = Vec::new();
+        let mut added = HashSet::new();
 
-        match &dependency.manifest {
-            DependencyManifest::Local(local) => {
-                self.add_local_dependency(dependency, local, parent_type)
-                    .await?;
+        for (pending, recorded) in level.into_iter().zip(recorded) {
+            let package_name = &pending.dependency.package;
+
+            if self.nodes.contains_key(package_name) || added.contains(package_name) {
+                existing.push((pending, recorded));
+                continue;
             }
-            DependencyManifest::Remote(remote) => {
-                self.add_remote_dependency(dependency, remote, parent_type)
-                    .await?;
+
+            added.insert(package_name.clone());
+
+            match &pending.dependency.manifest {
+                DependencyManifest::Local(local) => {
+                    let children = self
+                        .add_local_dependency(&pending, local)
+                        .await
+                        .wrap_err_with(|| pending.context())?;
+
+                    next.extend(children);
+                }
+                DependencyManifest::Git(git) => {
+                    let children = self
+                        .add_git_dependency(&pending, git)
+                        .await
+                        .wrap_err_with(|| pending.context())?;
+
+                    next.extend(children);
+                }
+                DependencyManifest::Tarball(tarball) => {
+                    let children = self
+                        .add_tarball_dependency(&pending, tarball)
+                        .await
+                        .wrap_err_with(|| pending.context())?;
+
+                    next.extend(children);
+                }
+                DependencyManifest::Remote(manifest) => {
+                    let manifest = manifest.clone();
+                    remote.push((pending, manifest));
+                }
             }
         }
 
-        // Unmark as visiting
-        self.visiting.remove(package_name);
+        next.extend(self.add_remote_dependencies(remote).await?);
+
+        // Packages required more than once must be compatible with the node added first
+        for (pending, recorded) in existing {
+            self.validate_existing(&pending.dependency, recorded)
+                .wrap_err_with(|| pending.context())?;
+        }
+
+        Ok(next)
+    }
+
+    /// Replaces a dependency with its patch, if the root manifest declares one
+    fn patch(&self, mut pending: PendingDependency) -> PendingDependency {
+        if let Some((manifest, base_path)) = self.patches.get(&pending.dependency.package) {
+            pending.dependency.manifest = manifest.clone();
+            pending.base_path = base_path.clone();
+        }
+
+        pending
+    }
+
+    /// Validates that a package already in the graph satisfies another dependant
+    fn validate_existing(&mut self, dependency: &Dependency, recorded: bool) -> miette::Result<()> {
+        let package_name = &dependency.package;
+
+        let Some(existing) = self.nodes.get(package_name) else {
+            return Ok(());
+        };
+
+        self.validate_manifest_conflicts(dependency, existing)
+            .wrap_err_with(|| format!("conflicting dependency on {}", package_name))?;
+
+        if !Self::is_version_compatible(dependency, existing) {
+            // A new requirement was recorded, so resolving again may find a version that
+            // satisfies every dependant. Otherwise no such version exists.
+            ensure!(recorded, self.conflict(package_name));
+
+            self.restart_required = true;
+        }
 
         Ok(())
     }
 
     async fn add_local_dependency(
         &mut self,
-        dependency: &Dependency,
+        pending: &PendingDependency,
         local_manifest: &LocalDependencyManifest,
-        parent_type: Option<PackageType>,
-    ) -> miette::Result<()> {
-        let resolved_path = self.base_path.join(&local_manifest.path);
+    ) -> miette::Result<Vec<PendingDependency>> {
+        let resolved_path = pending.base_path.join(&local_manifest.path);
+
+        self.add_package_at(
+            pending,
+            &resolved_path,
+            DependencySource::Local {
+                path: resolved_path.clone(),
+            },
+        )
+        .await
+    }
+
+    /// Checks out a git dependency and adds it like a local package
+    ///
+    /// The commit recorded in the lockfile is checked out if the repository and revision are
+    /// unchanged, so branches only move when the lockfile is updated.
+    #[cfg(feature = "git")]
+    async fn add_git_dependency(
+        &mut self,
+        pending: &PendingDependency,
+        git_manifest: &GitDependencyManifest,
+    ) -> miette::Result<Vec<PendingDependency>> {
+        // The package has to be located inside the checkout
+        if let Some(path) = &git_manifest.path {
+            use std::path::Component;
+
+            ensure!(
+                path.components()
+                    .all(|c| matches!(c, Component::Normal(_) | Component::CurDir)),
+                "path {} of git dependency {} must be relative and must not contain `..`",
+                path.display(),
+                pending.dependency.package
+            );
+        }
+
+        let locked = self.lockfile.as_ref().and_then(|lockfile| {
+            lockfile
+                .local_packages()
+                .filter(|local| local.name == pending.dependency.package)
+                .filter_map(|local| local.git.as_ref())
+                .find(|git| git.url == git_manifest.git && git.rev == git_manifest.rev)
+                .map(|git| git.commit.clone())
+        });
+
+        let checkout = crate::git::checkout(
+            git_manifest,
+            locked.as_deref(),
+            self.network_mode == NetworkMode::Online,
+        )
+        .await?;
+
+        let path = match &git_manifest.path {
+            Some(path) => checkout.root.join(path),
+            None => checkout.root.clone(),
+        };
+
+        self.add_package_at(
+            pending,
+            &path,
+            DependencySource::Git {
+                path: path.clone(),
+                root: checkout.root,
+                source: checkout.source,
+            },
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "git"))]
+    async fn add_git_dependency(
+        &mut self,
+        _pending: &PendingDependency,
+        git_manifest: &GitDependencyManifest,
+    ) -> miette::Result<Vec<PendingDependency>> {
+        bail!(
+            "unable to check out {}: buffrs was built without the `git` feature",
+            git_manifest.git
+        )
+    }
+
+    /// Downloads a tarball dependency and adds its package to the graph
+    async fn add_tarball_dependency(
+        &mut self,
+        pending: &PendingDependency,
+        tarball_manifest: &TarballDependencyManifest,
+    ) -> miette::Result<Vec<PendingDependency>> {
+        let dependency = &pending.dependency;
+
+        let package = crate::tarball::fetch(
+            &dependency.package,
+            tarball_manifest,
+            self.network_mode == NetworkMode::Online,
+        )
+        .await?;
+
+        let manifest = package.manifest;
+        let package_type = manifest.package.as_ref().map(|p| p.kind);
+
+        Self::ensure_lib_not_depends_on_api(dependency, pending.parent_type, package_type)?;
+
+        self.nodes.insert(
+            dependency.package.clone(),
+            DependencyNode {
+                name: dependency.package.clone(),
+                package_type,
+                source: DependencySource::Tarball {
+                    manifest: tarball_manifest.clone(),
+                },
+                dependencies: manifest.get_dependency_package_names(),
+                declarations: manifest.dependencies.clone().unwrap_or_default(),
+                version: VersionReq::STAR,
+                resolved_version: manifest.package.as_ref().map(|p| p.version.clone()),
+                manifest_source: None,
+            },
+        );
+
+        Ok(PendingDependency::declared_by(
+            &manifest,
+            &pending.base_path,
+        ))
+    }
+
+    /// Adds the package located in a directory to the graph
+    async fn add_package_at(
+        &mut self,
+        pending: &PendingDependency,
+        resolved_path: &Path,
+        source: DependencySource,
+    ) -> miette::Result<Vec<PendingDependency>> {
+        let dependency = &pending.dependency;
         let manifest_path = resolved_path.join(MANIFEST_FILE);
 
         let manifest = Manifest::require_package_manifest(&manifest_path).await?;
         let package_type = manifest.package.as_ref().map(|p| p.kind);
 
-        Self::ensure_lib_not_depends_on_api(dependency, parent_type, package_type)?;
+        Self::ensure_lib_not_depends_on_api(dependency, pending.parent_type, package_type)?;
 
         let sub_dependencies: Vec<PackageName> = manifest.get_dependency_package_names();
 
+        // Kept on a best effort basis, as it is only used to report errors
+        let manifest_source =
+            tokio::fs::read_to_string(&manifest_path)
+                .await
+                .ok()
+                .map(|contents| {
+                    NamedSource::new(normalize(&manifest_path).display().to_string(), contents)
+                });
+
         // Add node
         self.nodes.insert(
             dependency.package.clone(),
             DependencyNode {
                 name: dependency.package.clone(),
                 package_type,
-                source: DependencySource::Local {
-                    path: resolved_path.clone(),
-                },
-                dependencies: sub_dependencies.clone(),
+                source,
+                dependencies: sub_dependencies,
+                declarations: manifest.dependencies.clone().unwrap_or_default(),
                 version: VersionReq::STAR,
+                resolved_version: manifest.package.as_ref().map(|p| p.version.clone()),
+                manifest_source,
             },
         );
 
-        // Recursively process dependencies with the new base path
-        for sub_dep in manifest.dependencies.unwrap_or_default() {
-            // We need to update the base path for sub-dependencies
-            let old_base = self.base_path.clone();
-            self.base_path = resolved_path.clone();
-            self.add_dependency(&sub_dep, package_type)
-                .await
-                .wrap_err_with(|| {
-                    format!("while resolving dependencies of {}", dependency.package)
-                })?;
-            self.base_path = old_base;
-        }
-
-        Ok(())
+        // Paths of sub-dependencies are relative to this package
+        Ok(PendingDependency::declared_by(&manifest, resolved_path))
     }
 
     /// Ensures that a lib package doesn't depend on an api package
@@ -352,165 +786,467 @@ impl<'a> GraphBuilder<'a> {
         Ok(())
     }
 
-    async fn add_remote_dependency(
+    /// Adds remote packages to the graph, sending the registry requests concurrently
+    async fn add_remote_dependencies(
         &mut self,
-        dependency: &Dependency,
-        remote_manifest: &crate::manifest::RemoteDependencyManifest,
-        parent_type: Option<PackageType>,
-    ) -> miette::Result<()> {
-        let package_name = &dependency.package;
-        let registry = &remote_manifest.registry;
-        let repository = &remote_manifest.repository;
-        let version = &remote_manifest.version;
-
-        let package = {
-            let reformatted = version
-                .to_string()
-                .chars()
-                .skip_while(|c| *c == '=')
-                .collect::<String>();
-
-            let version = reformatted
-                .parse()
-                .map_err(|e| miette::miette!("{e}"))
-                .wrap_err_with(|| {
-                    format!("expected only exact version requirements: {package_name}@{version}")
-                })?;
-
-            let mut cached_package = None;
-
-            // Try to resolve from lockfile + cache first
-            if let Some(lockfile) = &self.lockfile
-                && let Some(file_req) = lockfile.get(package_name, &version)
-            {
-                // Verify registry matches (lockfile vs manifest)
-                if file_req.url().as_str().starts_with(registry.as_str()) {
-                    let cache = Cache::open().await?;
-                    if let Ok(Some(pkg)) = cache.get(file_req).await {
-                        tracing::debug!("resolved {}@{} from local cache", package_name, version);
-                        cached_package = Some(pkg);
-                    }
-                }
+        pending: Vec<(PendingDependency, RemoteDependencyManifest)>,
+    ) -> miette::Result<Vec<PendingDependency>> {
+        // 1. Select a version for every package, listing published versions where required
+        let mut requirements = Vec::with_capacity(pending.len());
+        let mut versions = Vec::with_capacity(pending.len());
+        let mut listings = Vec::new();
+
+        for (index, (dependency, remote_manifest)) in pending.iter().enumerate() {
+            let package_name = &dependency.dependency.package;
+            let requirement = self.unified_requirement(package_name);
+
+            let version = self
+                .select_version(package_name, remote_manifest, &requirement)
+                .wrap_err_with(|| dependency.context())?;
+
+            if version.is_none() {
+                let client = self.registry_client(&remote_manifest.registry)?;
+                let repository = remote_manifest.repository.clone();
+                let name = package_name.clone();
+
+                listings.push((index, async move {
+                    client.list_versions(repository, name).await
+                }));
             }
 
-            match (cached_package, self.network_mode) {
-                (Some(pkg), _) => pkg,
-                (None, NetworkMode::Online) => {
-                    tracing::debug!("downloading {}@{} from registry", package_name, version);
-
-                    // Reuse or create artifactory client
-                    let artifactory = if let Some(client) = self.registry_clients.get(registry) {
-                        client.clone()
-                    } else {
-                        let client = Artifactory::new(registry.clone(), self.credentials)
-                            .wrap_err_with(|| {
-                                format!("failed to initialize registry {}", registry)
-                            })?;
-                        self.registry_clients
-                            .insert(registry.clone(), client.clone());
-                        client
-                    };
-
-                    artifactory.download(dependency.clone()).await?
-                }
-                (None, NetworkMode::Offline) => {
-                    bail!(DependencyError::Offline {
-                        name: package_name.clone(),
-                        version: remote_manifest.version.clone(),
-                    });
-                }
+            requirements.push(requirement);
+            versions.push(version);
+        }
+
+        let (indices, listings): (Vec<_>, Vec<_>) = listings.into_iter().unzip();
+
+        for (index, published) in indices
+            .into_iter()
+            .zip(concurrently(self.concurrency, listings).await?)
+        {
+            let (dependency, _) = &pending[index];
+            let package_name = &dependency.dependency.package;
+
+            let version = published
+                .wrap_err_with(|| format!("failed to list versions of {package_name}"))
+                .and_then(|published| {
+                    self.highest_matching(package_name, &requirements[index], published)
+                })
+                .wrap_err_with(|| dependency.context())?;
+
+            versions[index] = Some(version);
+        }
+
+        let versions: Vec<Version> = versions.into_iter().flatten().collect();
+
+        // 2. Fetch every selected version that has not been fetched before
+        let mut packages = vec![None; pending.len()];
+        let mut downloads = Vec::new();
+
+        for (index, ((dependency, remote_manifest), version)) in
+            pending.iter().zip(&versions).enumerate()
+        {
+            let key = (dependency.dependency.package.clone(), version.clone());
+
+            if let Some(package) = self.packages.get(&key) {
+                packages[index] = Some(package.clone());
+                continue;
             }
-        };
 
-        // Read the package manifest to discover dependencies and package type
-        let manifest = package.manifest;
-        let package_type = manifest.package.as_ref().map(|p| p.kind);
+            let download = self.fetch(&dependency.dependency, remote_manifest, version)?;
 
-        Self::ensure_lib_not_depends_on_api(dependency, parent_type, package_type)?;
+            downloads.push((index, download));
+        }
 
-        let sub_dependencies: Vec<PackageName> = manifest.get_dependency_package_names();
+        let (indices, downloads): (Vec<_>, Vec<_>) = downloads.into_iter().unzip();
 
-        // Add node with discovered metadata
-        self.nodes.insert(
-            package_name.clone(),
-            DependencyNode {
-                name: package_name.clone(),
+        for (index, artifact) in indices
+            .into_iter()
+            .zip(concurrently(self.concurrency, downloads).await?)
+        {
+            let (dependency, _) = &pending[index];
+            let artifact = artifact.wrap_err_with(|| dependency.context())?;
+
+            self.packages.insert(
+                (
+                    dependency.dependency.package.clone(),
+                    versions[index].clone(),
+                ),
+                artifact.clone(),
+            );
+
+            packages[index] = Some(artifact);
+        }
+
+        // 3. Add the nodes in order, independent of when each package was fetched
+        let mut next = Vec::new();
+
+        for ((((dependency, remote_manifest), requirement), version), (package, artifact)) in
+            pending
+                .iter()
+                .zip(requirements)
+                .zip(versions)
+                .zip(packages.into_iter().flatten())
+        {
+            let package_name = &dependency.dependency.package;
+
+            // Read the package manifest to discover dependencies and package type
+            let manifest = package.manifest.clone();
+            let package_type = manifest.package.as_ref().map(|p| p.kind);
+
+            Self::ensure_lib_not_depends_on_api(
+                &dependency.dependency,
+                dependency.parent_type,
                 package_type,
-                source: DependencySource::Remote {
-                    registry: registry.clone(),
-                    repository: repository.clone(),
+            )
+            .wrap_err_with(|| dependency.context())?;
+
+            // Add node with discovered metadata
+            self.nodes.insert(
+                package_name.clone(),
+                DependencyNode {
+                    name: package_name.clone(),
+                    package_type,
+                    source: DependencySource::Remote {
+                        registry: remote_manifest.registry.clone(),
+                        repository: remote_manifest.repository.clone(),
+                        package,
+                        manifest: artifact,
+                    },
+                    dependencies: manifest.get_dependency_package_names(),
+                    declarations: manifest.dependencies.clone().unwrap_or_default(),
+                    version: requirement,
+                    resolved_version: Some(version),
+                    manifest_source: None,
                 },
-                dependencies: sub_dependencies.clone(),
-                version: version.clone(),
+            );
+
+            next.extend(PendingDependency::declared_by(
+                &manifest,
+                &dependency.base_path,
+            ));
+        }
+
+        Ok(next)
+    }
+
+    /// Records the version requirement a dependant places on a remote dependency
+    ///
+    /// Returns whether the requirement was not known yet.
+    fn record_requirement(&mut self, pending: &PendingDependency) -> bool {
+        let DependencyManifest::Remote(remote) = &pending.dependency.manifest else {
+            return false;
+        };
+
+        let recorded = RecordedRequirement {
+            declared_by: pending.declared_by.clone(),
+            requirement: VersionRequirement {
+                dependant: pending.dependant.clone(),
+                version: remote.version.clone(),
             },
-        );
+        };
 
-        // Recursively process transitive dependencies
-        for sub_dep in manifest.dependencies.unwrap_or_default() {
-            self.add_dependency(&sub_dep, package_type)
-                .await
-                .wrap_err_with(|| format!("while resolving dependencies of {}", package_name))?;
+        let requirements = self
+            .requirements
+            .entry(pending.dependency.package.clone())
+            .or_default();
+
+        if requirements.contains(&recorded) {
+            return false;
         }
 
-        Ok(())
+        requirements.push(recorded);
+
+        true
     }
 
-    fn validate_compatibility(
+    /// Returns whether a requirement was declared by a dependant version that is still selected
+    ///
+    /// Dependants which have not been added yet are judged by the version the previous attempt
+    /// selected for them, and are assumed to be selected if they have never been added before.
+    fn is_live(&self, declared_by: &Option<(PackageName, Version)>) -> bool {
+        let Some((name, version)) = declared_by else {
+            return true;
+        };
+
+        match self.nodes.get(name) {
+            Some(node) => node.resolved_version.as_ref() == Some(version),
+            None => self
+                .previous
+                .get(name)
+                .is_none_or(|previous| previous.as_ref() == Some(version)),
+        }
+    }
+
+    /// Returns the version requirements of the dependants of a package which are still selected
+    fn requirements_of(&self, package_name: &PackageName) -> Vec<VersionRequirement> {
+        self.requirements
+            .get(package_name)
+            .into_iter()
+            .flatten()
+            .filter(|recorded| self.is_live(&recorded.declared_by))
+            .map(|recorded| recorded.requirement.clone())
+            .collect()
+    }
+
+    /// Reports that no version satisfies all recorded requirements of a package
+    fn conflict(&self, package_name: &PackageName) -> DependencyError {
+        DependencyError::VersionConflict {
+            package: package_name.clone(),
+            requirements: self.requirements_of(package_name),
+        }
+    }
+
+    /// Combines the requirements of all dependants of a package into a single requirement
+    fn unified_requirement(&self, package_name: &PackageName) -> VersionReq {
+        VersionReq {
+            comparators: self
+                .requirements_of(package_name)
+                .into_iter()
+                .flat_map(|requirement| requirement.version.comparators)
+                .collect(),
+        }
+    }
+
+    /// Selects the concrete version to use for a remote dependency without asking the registry
+    ///
+    /// Versions pinned by the lockfile take precedence if they were locked from the same registry
+    /// and repository, followed by exact requirements. Returns `None` if the highest matching
+    /// version in the registry has to be used instead.
+    fn select_version(
         &self,
-        dependency: &Dependency,
-        existing: &DependencyNode,
-    ) -> miette::Result<()> {
-        // Check for local/remote conflicts
-        self.validate_manifest_conflicts(dependency, existing)?;
+        package_name: &PackageName,
+        remote_manifest: &RemoteDependencyManifest,
+        requirement: &VersionReq,
+    ) -> miette::Result<Option<Version>> {
+        if let Some(lockfile) = &self.lockfile
+            && let Some(locked) = lockfile.find_matching(package_name, requirement)
+            && locked.registry == remote_manifest.registry
+            && locked.repository == remote_manifest.repository
+        {
+            tracing::debug!(
+                "resolved {}@{} to locked version {}",
+                package_name,
+                requirement,
+                locked.version
+            );
+
+            return Ok(Some(locked.version.clone()));
+        }
 
-        // Check for version conflicts on remote dependencies
-        self.validate_version_compatibility(dependency, existing)?;
+        let pinned = self
+            .requirements_of(package_name)
+            .iter()
+            .find_map(|requirement| pinned_version(&requirement.version));
 
-        Ok(())
+        if let Some(version) = pinned {
+            ensure!(requirement.matches(&version), self.conflict(package_name));
+
+            return Ok(Some(version));
+        }
+
+        if self.network_mode == NetworkMode::Offline {
+            bail!(DependencyError::Offline {
+                name: package_name.clone(),
+                version: requirement.clone(),
+            });
+        }
+
+        Ok(None)
     }
 
-    /// Validates that version requirements are compatible when the same package is requested multiple times
-    fn validate_version_compatibility(
+    /// Picks the highest of the published versions of a package that matches a requirement
+    fn highest_matching(
         &self,
+        package_name: &PackageName,
+        requirement: &VersionReq,
+        published: Vec<Version>,
+    ) -> miette::Result<Version> {
+        let version = published
+            .into_iter()
+            .filter(|version| requirement.matches(version))
+            .max();
+
+        let Some(version) = version else {
+            ensure!(
+                self.requirements_of(package_name).len() <= 1,
+                self.conflict(package_name)
+            );
+
+            bail!(DependencyError::NoMatchingVersion {
+                name: package_name.clone(),
+                version: requirement.clone(),
+            });
+        };
+
+        tracing::debug!(
+            "resolved {}@{} to version {} from registry",
+            package_name,
+            requirement,
+            version
+        );
+
+        Ok(version)
+    }
+
+    /// Prepares retrieving a specific version of a remote dependency from the cache or the
+    /// registry
+    ///
+    /// Downloaded packages are cached, and the fetched package is installed as is, so every
+    /// package is downloaded at most once. The returned future does not borrow the builder, so it can be run as a separate task.
+    fn fetch(
+        &mut self,
         dependency: &Dependency,
-        existing: &DependencyNode,
-    ) -> miette::Result<()> {
-        // Only check version compatibility for remote dependencies
-        if let (DependencyManifest::Remote(new_remote), DependencySource::Remote { .. }) =
-            (&dependency.manifest, &existing.source)
-        {
-            // For now, buffrs only supports pinned versions (see TODO #205)
-            // We check if the version requirements are equal since they should be exact pins
-            // In the future with dynamic version resolution, this would need to check for compatibility
-            if new_remote.version != existing.version {
-                bail!(DependencyError::VersionConflict {
-                    package: dependency.package.clone(),
-                    required_version: new_remote.version.clone(),
-                    existing_version: existing.version.clone(),
-                });
+        remote_manifest: &RemoteDependencyManifest,
+        version: &Version,
+    ) -> miette::Result<
+        impl Future<Output = miette::Result<(Package, Option<Digest>)>> + Send + 'static,
+    > {
+        let package_name = dependency.package.clone();
+        let registry = &remote_manifest.registry;
+
+        // Packages locked from the same repository are taken from the cache if possible
+        let locked = self
+            .lockfile
+            .as_ref()
+            .and_then(|lockfile| {
+                lockfile.packages().find(|locked| {
+                    locked.name == package_name
+                        && &locked.version == version
+                        && &locked.registry == registry
+                        && locked.repository == remote_manifest.repository
+                })
+            })
+            .cloned();
+
+        let client = match self.network_mode {
+            NetworkMode::Online => Some(self.registry_client(registry)?),
+            NetworkMode::Offline => None,
+        };
+
+        let requirement = remote_manifest.version.clone();
+        let dependency = dependency.with_version(version);
+        let version = version.clone();
+
+        Ok(async move {
+            let cache = Cache::open().await?;
+
+            if let Some(locked) = &locked
+                && let Ok(Some(package)) = cache.get(locked.clone().into()).await
+            {
+                locked.validate(&package)?;
+
+                tracing::debug!("resolved {}@{} from local cache", package_name, version);
+
+                return Ok((package, locked.manifest.clone()));
             }
+
+            let Some(client) = client else {
+                bail!(DependencyError::Offline {
+                    name: package_name,
+                    version: requirement,
+                });
+            };
+
+            tracing::debug!("downloading {}@{} from registry", package_name, version);
+
+            // Locked artifacts are downloaded through their manifest, even if the tag was moved
+            let manifest = locked.and_then(|locked| locked.manifest);
+            let (package, manifest) = client.download_artifact(dependency, manifest).await?;
+
+            // Cache the package for future installations
+            cache
+                .put(CacheEntry::from(&package), package.tgz.clone())
+                .await
+                .ok();
+
+            Ok((package, manifest))
+        })
+    }
+
+    /// Reuses or creates the client for a registry
+    fn registry_client(&mut self, registry: &RegistryUri) -> miette::Result<Arc<dyn Registry>> {
+        if let Some(client) = self.registry_clients.get(registry) {
+            return Ok(client.clone());
         }
 
-        Ok(())
+        let client = registry::connect(registry, self.credentials)
+            .wrap_err_with(|| format!("failed to initialize registry {}", registry))?;
+
+        self.registry_clients
+            .insert(registry.clone(), client.clone());
+
+        Ok(client)
+    }
+
+    /// Checks whether the version selected for a package satisfies another dependant
+    fn is_version_compatible(dependency: &Dependency, existing: &DependencyNode) -> bool {
+        // Only check version compatibility for remote dependencies
+        let (DependencyManifest::Remote(new_remote), DependencySource::Remote { .. }) =
+            (&dependency.manifest, &existing.source)
+        else {
+            return true;
+        };
+
+        match &existing.resolved_version {
+            Some(resolved) => new_remote.version.matches(resolved),
+            None => new_remote.version == existing.version,
+        }
     }
 
     /// Checks for conflicting dependencies between local / remote deps in the dependency tree
+    ///
+    /// Git and tarball dependencies also conflict if they are declared with different sources.
     fn validate_manifest_conflicts(
         &self,
         dependency: &Dependency,
         existing: &DependencyNode,
     ) -> miette::Result<()> {
         match (&dependency.manifest, &existing.source) {
-            (DependencyManifest::Local(_), DependencySource::Remote { .. }) => {
+            (
+                DependencyManifest::Local(_)
+                | DependencyManifest::Git(_)
+                | DependencyManifest::Tarball(_),
+                DependencySource::Remote { .. },
+            ) => {
                 bail!(DependencyError::LocalRemoteConflict {
                     package: dependency.package.clone(),
                 });
             }
-            (DependencyManifest::Remote(_), DependencySource::Local { .. }) => {
+            (
+                DependencyManifest::Remote(_),
+                DependencySource::Local { .. }
+                | DependencySource::Git { .. }
+                | DependencySource::Tarball { .. },
+            ) => {
                 bail!(DependencyError::LocalRemoteConflict {
                     package: dependency.package.clone(),
                 });
             }
+            (DependencyManifest::Git(git), DependencySource::Git { path, root, source }) => {
+                let requested = git.path.as_deref().unwrap_or(Path::new(""));
+                let existing = path.strip_prefix(root).unwrap_or(path);
+
+                if git.git != source.url
+                    || git.rev != source.rev
+                    || normalize(requested) != normalize(existing)
+                {
+                    bail!(DependencyError::SourceConflict {
+                        package: dependency.package.clone(),
+                        existing: git_source(&source.url, source.rev.as_deref(), existing),
+                        requested: git_source(&git.git, git.rev.as_deref(), requested),
+                    });
+                }
+            }
+            (DependencyManifest::Tarball(tarball), DependencySource::Tarball { manifest })
+                if (&tarball.url, &tarball.digest) != (&manifest.url, &manifest.digest) =>
+            {
+                bail!(DependencyError::SourceConflict {
+                    package: dependency.package.clone(),
+                    existing: format!("tarball {} ({})", manifest.url, manifest.digest),
+                    requested: format!("tarball {} ({})", tarball.url, tarball.digest),
+                });
+            }
             _ => {}
         }
 
@@ -518,6 +1254,134 @@ impl<'a> GraphBuilder<'a> {
     }
 }
 
+/// Describes the source of a git dependency for conflict diagnostics
+fn git_source(url: &str, rev: Option<&str>, path: &Path) -> String {
+    let mut source = format!("git repository {url}");
+
+    if let Some(rev) = rev {
+        source.push_str(&format!(" at {rev}"));
+    }
+
+    if !path.as_os_str().is_empty() {
+        source.push_str(&format!(" (path {})", path.display()));
+    }
+
+    source
+}
+
+/// Runs the given tasks with at most `limit` of them in flight at a time
+///
+/// Outputs are returned in the order of the tasks, regardless of the order of completion.
+async fn concurrently<F>(limit: NonZeroUsize, tasks: Vec<F>) -> miette::Result<Vec<F::Output>>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let mut outputs: Vec<Option<F::Output>> = tasks.iter().map(|_| None).collect();
+    let mut tasks = tasks.into_iter().enumerate();
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.len() < limit.get()
+            && let Some((index, task)) = tasks.next()
+        {
+            running.spawn(async move { (index, task.await) });
+        }
+
+        let Some(result) = running.join_next().await else {
+            break;
+        };
+
+        let (index, output) = result.into_diagnostic()?;
+
+        outputs[index] = Some(output);
+    }
+
+    Ok(outputs.into_iter().flatten().collect())
+}
+
+/// Returns the version pinned by an exact requirement such as `=1.2.3`
+fn pinned_version(requirement: &VersionReq) -> Option<Version> {
+    let [comparator] = requirement.comparators.as_slice() else {
+        return None;
+    };
+
+    if comparator.op != semver::Op::Exact {
+        return None;
+    }
+
+    Some(Version {
+        major: comparator.major,
+        minor: comparator.minor?,
+        patch: comparator.patch?,
+        pre: comparator.pre.clone(),
+        build: Default::default(),
+    })
+}
+
+/// A dependency declaration that is part of a dependency cycle
+#[derive(Error, Diagnostic, Debug)]
+#[error("{dependant} depends on {dependency}")]
+pub struct CycleEdge {
+    /// The package declaring the dependency
+    pub dependant: PackageName,
+    /// The package depended on
+    pub dependency: PackageName,
+    /// The manifest of the dependant, if it is a local package
+    #[source_code]
+    pub manifest: Option<NamedSource<String>>,
+    /// Location of the dependency declaration in the manifest
+    #[label("{dependency} is declared here")]
+    pub span: Option<SourceSpan>,
+}
+
+impl CycleEdge {
+    fn new(dependant: &DependencyNode, dependency: &PackageName) -> Self {
+        // Only the manifests of local and git packages are kept while building the graph
+        let manifest = dependant.manifest_source.clone();
+
+        let span = manifest
+            .as_ref()
+            .and_then(|manifest| find_declaration(manifest.inner(), dependency));
+
+        Self {
+            dependant: dependant.name.clone(),
+            dependency: dependency.clone(),
+            manifest,
+            span,
+        }
+    }
+}
+
+/// Locates the declaration of a dependency in the contents of a manifest
+///
+/// Dependencies may be declared as values, inline tables, dotted keys or tables of their own,
+/// e.g. `[dependencies.name]`, whose header is located in that case.
+fn find_declaration(manifest: &str, dependency: &PackageName) -> Option<SourceSpan> {
+    let document = toml_edit::ImDocument::parse(manifest).ok()?;
+    let dependencies = document.get("dependencies")?.as_table_like()?;
+    let (key, item) = dependencies.get_key_value(dependency)?;
+
+    let span = match item {
+        toml_edit::Item::Table(table) if !table.is_dotted() => {
+            let span = table.span()?;
+            let header = manifest[span.clone()].lines().next()?.trim_end();
+
+            span.start..span.start + header.len()
+        }
+        item => {
+            let key = key.span()?;
+
+            match item.span() {
+                Some(value) => key.start..value.end,
+                None => key,
+            }
+        }
+    };
+
+    Some(span.into())
+}
+
 /// Errors that can occur during dependency resolution
 #[derive(Error, Diagnostic, Debug)]
 pub enum DependencyError {
@@ -528,6 +1392,20 @@ pub enum DependencyError {
         package: PackageName,
     },
 
+    /// Dependants declare a git or tarball dependency with different sources
+    #[error("conflicting sources for package {package}: {existing} and {requested}")]
+    #[diagnostic(help(
+        "declare {package} with the same source everywhere, or patch it in the root manifest"
+    ))]
+    SourceConflict {
+        /// The package that has the conflict
+        package: PackageName,
+        /// The source of the package already in the graph
+        existing: String,
+        /// The conflicting source
+        requested: String,
+    },
+
     /// A lib package cannot depend on an api package
     #[error("package of type lib cannot depend on package of type api: {parent} -> {dependency}")]
     InvalidPackageTypeDependency {
@@ -538,20 +1416,29 @@ pub enum DependencyError {
     },
 
     /// A circular dependency was detected in the dependency graph
-    #[error("circular dependency detected: {0}")]
-    CircularDependency(String),
+    #[error(
+        "circular dependency detected: {}",
+        cycle.iter().map(ToString::to_string).collect::<Vec<_>>().join(" -> ")
+    )]
+    #[diagnostic(help("remove one of the dependencies of the cycle from its manifest"))]
+    CircularDependency {
+        /// The packages forming the cycle, starting and ending with the same package
+        cycle: Vec<PackageName>,
+        /// The dependency declarations forming the cycle
+        #[related]
+        edges: Vec<CycleEdge>,
+    },
 
     /// Version conflict between multiple dependants
     #[error(
-        "version conflict for {package}: requires {required_version} but already resolved to {existing_version}"
+        "version conflict for {package}: no version satisfies {}",
+        requirements.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
     )]
     VersionConflict {
         /// The package with conflicting versions
         package: PackageName,
-        /// The version requirement that conflicts
-        required_version: VersionReq,
-        /// The version already resolved in the graph
-        existing_version: VersionReq,
+        /// The requirements of all dependants on the package
+        requirements: Vec<VersionRequirement>,
     },
 
     /// Failed to download a dependency from the registry
@@ -563,6 +1450,18 @@ pub enum DependencyError {
         version: VersionReq,
     },
 
+    /// No published version satisfies the version requirement
+    #[error("no version of {name} matches the requirement {version}")]
+    #[diagnostic(help(
+        "check the versions published for {name} or relax the requirement in the manifest"
+    ))]
+    NoMatchingVersion {
+        /// Package name
+        name: PackageName,
+        /// Version requirement
+        version: VersionReq,
+    },
+
     /// A network request was needed but --offline mode is active
     #[error("cannot fetch {name}@{version} in offline mode")]
     #[diagnostic(help(
@@ -576,3 +1475,191 @@ pub enum DependencyError {
         version: VersionReq,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::Path};
+
+    use semver::{Version, VersionReq};
+
+    use super::{DependencyGraph, find_declaration};
+    use crate::{
+        credentials::Credentials,
+        lock::{DigestAlgorithm, LockedPackage, Lockfile, PackageLockfile},
+        manifest::{Dependency, PackageManifest, PackagesManifest},
+        operations::install::NetworkMode,
+        package::{Package, PackageName, PackageType},
+        registry::{LocalRegistry, Registry, RegistryUri},
+    };
+
+    fn manifest(
+        registry: &RegistryUri,
+        package: Option<(&str, &str)>,
+        dependencies: &[(&str, &str)],
+    ) -> PackagesManifest {
+        let dependencies = dependencies
+            .iter()
+            .map(|(name, requirement)| {
+                Dependency::new(
+                    registry.clone(),
+                    "test-repo".into(),
+                    PackageName::unchecked(*name),
+                    VersionReq::parse(requirement).unwrap(),
+                )
+            })
+            .collect();
+
+        let builder = PackagesManifest::builder().dependencies(dependencies);
+
+        match package {
+            Some((name, version)) => builder
+                .package(PackageManifest {
+                    kind: PackageType::Lib,
+                    name: PackageName::unchecked(name),
+                    version: Version::parse(version).unwrap(),
+                    description: None,
+                })
+                .build(),
+            None => builder.build(),
+        }
+    }
+
+    async fn publish(
+        registry: &LocalRegistry,
+        uri: &RegistryUri,
+        package: (&str, &str),
+        dependencies: &[(&str, &str)],
+    ) {
+        let package = Package::create(
+            manifest(uri, Some(package), dependencies),
+            BTreeMap::new(),
+            false,
+        )
+        .unwrap();
+
+        registry.publish(package, "test-repo".into()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn ignores_requirements_of_downgraded_dependants() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = LocalRegistry::new(dir.path().to_path_buf());
+        let uri: RegistryUri = url::Url::from_directory_path(dir.path())
+            .unwrap()
+            .as_str()
+            .parse()
+            .unwrap();
+
+        publish(&registry, &uri, ("lib-c", "1.0.0"), &[]).await;
+        publish(&registry, &uri, ("lib-c", "2.0.0"), &[]).await;
+        publish(&registry, &uri, ("lib-a", "1.0.0"), &[("lib-c", "^1")]).await;
+        publish(&registry, &uri, ("lib-a", "2.0.0"), &[("lib-c", "^2")]).await;
+        publish(&registry, &uri, ("lib-b", "1.0.0"), &[("lib-a", "^1")]).await;
+
+        // lib-a@2 is selected first and requires lib-c ^2, until lib-b downgrades lib-a to 1.0.0,
+        // which requires lib-c ^1 instead
+        let root = manifest(&uri, None, &[("lib-a", ">=1.0.0"), ("lib-b", "^1")]);
+
+        let graph = DependencyGraph::build(
+            &root,
+            Path::new("."),
+            &Credentials::default(),
+            None,
+            NetworkMode::Online,
+        )
+        .await
+        .unwrap();
+
+        let version = |name: &str| {
+            graph.nodes[&PackageName::unchecked(name)]
+                .resolved_version
+                .clone()
+                .unwrap()
+        };
+
+        assert_eq!(version("lib-a"), Version::new(1, 0, 0));
+        assert_eq!(version("lib-b"), Version::new(1, 0, 0));
+        assert_eq!(version("lib-c"), Version::new(1, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn ignores_versions_locked_from_other_repositories() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = LocalRegistry::new(dir.path().to_path_buf());
+        let uri: RegistryUri = url::Url::from_directory_path(dir.path())
+            .unwrap()
+            .as_str()
+            .parse()
+            .unwrap();
+
+        publish(&registry, &uri, ("lib-a", "1.0.0"), &[]).await;
+        publish(&registry, &uri, ("lib-a", "1.1.0"), &[]).await;
+
+        let locked = LockedPackage {
+            name: PackageName::unchecked("lib-a"),
+            version: Version::new(1, 0, 0),
+            digest: DigestAlgorithm::SHA256.digest(b"lib-a"),
+            registry: uri.clone(),
+            repository: "other-repo".into(),
+            manifest: None,
+            dependencies: vec![],
+            dependants: 1,
+            edition: None,
+            files: vec![],
+        };
+
+        let lockfile = Lockfile::Package(PackageLockfile::from_iter([locked]));
+        let root = manifest(&uri, None, &[("lib-a", "^1")]);
+
+        let graph = DependencyGraph::build(
+            &root,
+            Path::new("."),
+            &Credentials::default(),
+            Some(lockfile),
+            NetworkMode::Online,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            graph.nodes[&PackageName::unchecked("lib-a")].resolved_version,
+            Some(Version::new(1, 1, 0))
+        );
+    }
+
+    #[test]
+    fn finds_dependency_declarations() {
+        let declared = |manifest: &str, dependency: &str| {
+            let span = find_declaration(manifest, &PackageName::unchecked(dependency))?;
+
+            Some(manifest[span.offset()..span.offset() + span.len()].to_owned())
+        };
+
+        let manifest = r#"
+            [dependencies]
+            plain = { path = "../plain" }
+            dotted.path = "../dotted"
+
+            [dependencies.table]
+            path = "../table"
+        "#;
+
+        assert_eq!(
+            declared(manifest, "plain").as_deref(),
+            Some(r#"plain = { path = "../plain" }"#)
+        );
+        assert_eq!(declared(manifest, "dotted").as_deref(), Some("dotted"));
+        assert_eq!(
+            declared(manifest, "table").as_deref(),
+            Some("[dependencies.table]")
+        );
+        assert_eq!(declared(manifest, "missing"), None);
+
+        let manifest = r#"dependencies = { inline = { path = "../inline" } }"#;
+
+        assert_eq!(
+            declared(manifest, "inline").as_deref(),
+            Some(r#"inline = { path = "../inline" }"#)
+        );
+    }
+}
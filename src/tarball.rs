@@ -0,0 +1,180 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::Bytes;
+use miette::{Context as _, IntoDiagnostic, bail, ensure, miette};
+use semver::Version;
+use url::Url;
+
+use crate::{
+    cache::{Cache, Entry as CacheEntry},
+    manifest::{PackageManifest, PackagesManifest, TarballDependencyManifest},
+    package::{Package, PackageName, PackageType},
+    registry::http,
+};
+
+/// Retrieves the package of a tarball dependency from the cache or its URL
+///
+/// Tarballs are verified against the declared digest before they are used or cached. Tarballs
+/// without a manifest are treated as a library package with the name of the dependency, whose
+/// version is declared by the dependency or taken from the file name of the tarball.
+pub async fn fetch(
+    name: &PackageName,
+    manifest: &TarballDependencyManifest,
+    online: bool,
+) -> miette::Result<Package> {
+    let cache = Cache::open().await?;
+    let entry = CacheEntry::new(name, &manifest.digest);
+    let algorithm = manifest.digest.algorithm();
+
+    let tgz = match cache.read(&entry).await {
+        Some(tgz) if algorithm.digest(&tgz) == manifest.digest => {
+            tracing::debug!("resolved {name} from local cache");
+            tgz
+        }
+        _ => {
+            ensure!(
+                online,
+                "tarball of {name} is not cached and network access is disabled"
+            );
+
+            let tgz = download(&manifest.url)
+                .await
+                .wrap_err_with(|| format!("failed to download {}", manifest.url))?;
+
+            let digest = algorithm.digest(&tgz);
+
+            ensure!(
+                digest == manifest.digest,
+                "digest mismatch for {name}: {} has digest {digest} but {} was declared",
+                manifest.url,
+                manifest.digest
+            );
+
+            cache.put(entry, tgz.clone()).await.ok();
+
+            tgz
+        }
+    };
+
+    let package = Package::parse_or_else(tgz, || {
+        let version = match &manifest.version {
+            Some(version) => version.clone(),
+            None => version_from_url(name, &manifest.url).ok_or_else(|| {
+                miette!(
+                    "tarball of {name} contains no manifest, declare its version in the dependency"
+                )
+            })?,
+        };
+
+        Ok(PackagesManifest::builder()
+            .package(PackageManifest {
+                kind: PackageType::Lib,
+                name: name.clone(),
+                version,
+                description: None,
+            })
+            .build())
+    })
+    .wrap_err_with(|| format!("failed to read tarball of {name}"))?;
+
+    ensure!(
+        package.manifest.package.is_some(),
+        "manifest of {name} has no package declaration"
+    );
+
+    ensure!(
+        package.name() == name,
+        "tarball of {name} contains package {}",
+        package.name()
+    );
+
+    if let Some(version) = &manifest.version {
+        ensure!(
+            package.version() == version,
+            "tarball of {name} contains version {} but {version} was declared",
+            package.version()
+        );
+    }
+
+    Ok(package)
+}
+
+async fn download(url: &Url) -> miette::Result<Bytes> {
+    match url.scheme() {
+        "http" | "https" => {
+            tracing::info!("downloading {url}");
+
+            let client = http::client().build().into_diagnostic()?;
+            let request = client.get(url.clone()).build().into_diagnostic()?;
+
+            http::execute(&client, request)
+                .await
+                .into_diagnostic()?
+                .error_for_status()
+                .into_diagnostic()?
+                .bytes()
+                .await
+                .into_diagnostic()
+        }
+        "file" => {
+            let path = url
+                .to_file_path()
+                .map_err(|_| miette!("{url} is not a valid file path"))?;
+
+            tokio::fs::read(path)
+                .await
+                .into_diagnostic()
+                .map(Bytes::from)
+        }
+        scheme => bail!("unsupported URL scheme {scheme}, must be http, https or file"),
+    }
+}
+
+/// Parses the version of a tarball named `<name>-<version>.tgz` or `<name>-<version>.tar.gz`
+fn version_from_url(name: &PackageName, url: &Url) -> Option<Version> {
+    let file = url.path_segments()?.next_back()?;
+
+    let version = file
+        .strip_suffix(".tgz")
+        .or_else(|| file.strip_suffix(".tar.gz"))?
+        .strip_prefix(&**name)?
+        .strip_prefix('-')?;
+
+    version.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn parses_versions_from_file_names() {
+        let name = PackageName::from_str("vendor-api").unwrap();
+        let version = |url: &str| version_from_url(&name, &Url::parse(url).unwrap());
+
+        assert_eq!(
+            version("https://example.com/releases/vendor-api-1.2.0.tgz"),
+            Some(Version::new(1, 2, 0))
+        );
+        assert_eq!(
+            version("https://example.com/vendor-api-1.2.0-rc.1.tar.gz?download=1"),
+            Some(Version::parse("1.2.0-rc.1").unwrap())
+        );
+        assert_eq!(version("https://example.com/vendor-api.tgz"), None);
+        assert_eq!(version("https://example.com/other-1.2.0.tgz"), None);
+    }
+}
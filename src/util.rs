@@ -0,0 +1,70 @@
+// Copyright 2026 Helsing GmbH
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Lexically resolves `.` and `..` components of a path
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Returns a path relative to `base`, assuming both paths are absolute
+pub(crate) fn relative(path: &Path, base: &Path) -> PathBuf {
+    let path = normalize(path);
+    let base = normalize(base);
+
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let parents = base.components().skip(common).map(|_| Component::ParentDir);
+
+    parents.chain(path.components().skip(common)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::relative;
+
+    #[test]
+    fn relative_paths() {
+        let base = Path::new("/workspace/pkg1");
+
+        assert_eq!(
+            relative(Path::new("/workspace/pkg1/./libs/a"), base),
+            PathBuf::from("libs/a")
+        );
+        assert_eq!(
+            relative(Path::new("/workspace/pkg1/../shared"), base),
+            PathBuf::from("../shared")
+        );
+        assert_eq!(relative(base, base), PathBuf::new());
+    }
+}
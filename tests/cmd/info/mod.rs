@@ -0,0 +1,143 @@
+use crate::{VirtualFileSystem, with_test_registry};
+
+const PROTO: &str =
+    "syntax = \"proto3\";\n\npackage base;\n\nmessage Data {\n  string value = 1;\n}\n";
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::empty();
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+        let cache = cwd.join("cache");
+
+        for version in ["0.1.0", "0.2.0"] {
+            crate::publish_test_library(
+                &cwd.join(format!("libs-{version}")),
+                &buffrs_home,
+                url,
+                "test-repo",
+                "base-lib",
+                Some(version),
+                "base.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        // Publish an api with a description and a dependency
+        let api = cwd.join("remote-api");
+        std::fs::create_dir_all(api.join("proto")).unwrap();
+
+        crate::cli!()
+            .args(["init", "--api", "remote-api"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&api)
+            .assert()
+            .success();
+
+        let manifest = std::fs::read_to_string(api.join("Proto.toml")).unwrap();
+        let manifest = manifest.replace(
+            "version = \"0.1.0\"",
+            "version = \"0.1.0\"\ndescription = \"The remote api\"",
+        );
+        std::fs::write(api.join("Proto.toml"), manifest).unwrap();
+
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/base-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&api)
+            .assert()
+            .success();
+
+        std::fs::write(
+            api.join("proto/api.proto"),
+            "syntax = \"proto3\";\n\npackage remote.api;\n",
+        )
+        .unwrap();
+
+        crate::cli!()
+            .args(["publish", "--registry", url, "--repository", "test-repo"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&api)
+            .assert()
+            .success();
+
+        let info = |args: &[&str]| {
+            let output = crate::cli!()
+                .args(["info", "--registry", url])
+                .args(args)
+                .env("BUFFRS_HOME", &buffrs_home)
+                .env("BUFFRS_CACHE", &cache)
+                .current_dir(&cwd)
+                .assert()
+                .success();
+
+            String::from_utf8_lossy(&output.get_output().stdout).into_owned()
+        };
+
+        // The latest version is described by default
+        let text = info(&["test-repo/base-lib"]);
+
+        assert!(text.starts_with("base-lib@0.2.0 (lib)\n"), "{text}");
+        assert!(text.contains("repository: test-repo\n"), "{text}");
+        assert!(text.contains("digest:     sha256:"), "{text}");
+        assert!(text.contains("files:\n  base.proto\n"), "{text}");
+        assert!(!text.contains("dependencies:"), "{text}");
+
+        let text = info(&["test-repo/remote-api"]);
+
+        assert!(
+            text.starts_with("remote-api@0.1.0 (api)\nThe remote api\n"),
+            "{text}"
+        );
+        assert!(
+            text.contains("dependencies:\n  test-repo/base-lib@=0.1.0\n"),
+            "{text}"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&info(&["test-repo/remote-api@0.1", "--json"])).unwrap();
+
+        assert_eq!(json["name"], "remote-api");
+        assert_eq!(json["version"], "0.1.0");
+        assert_eq!(json["type"], "api");
+        assert_eq!(json["description"], "The remote api");
+        assert_eq!(json["registry"], url);
+        assert_eq!(json["repository"], "test-repo");
+        assert_eq!(
+            json["dependencies"],
+            serde_json::json!([{
+                "name": "base-lib",
+                "version": "=0.1.0",
+                "repository": "test-repo",
+                "registry": url,
+            }])
+        );
+        assert_eq!(json["files"][0]["path"], "api.proto");
+
+        // Downloaded packages are cached by their digest
+        let digest = json["digest"].as_str().unwrap().replace(':', ".");
+        assert!(cache.join(format!("remote-api.{digest}.tgz")).exists());
+
+        // Unknown versions are reported
+        crate::cli!()
+            .args(["info", "--registry", url, "test-repo/base-lib@=1.0.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", &cache)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        // Lockfiles which cannot be read are reported instead of being ignored
+        std::fs::write(cwd.join("Proto.lock"), "not a lockfile").unwrap();
+
+        crate::cli!()
+            .args(["info", "--registry", url, "test-repo/base-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", &cache)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+    });
+}
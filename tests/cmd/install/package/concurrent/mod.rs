@@ -0,0 +1,133 @@
+use std::path::Path;
+
+use crate::{VirtualFileSystem, with_test_registry};
+
+const PROTO: &str =
+    "syntax = \"proto3\";\n\npackage lib;\n\nmessage Data {\n  string value = 1;\n}\n";
+
+/// Creates and publishes a library depending on the given packages
+fn publish_lib(url: &str, buffrs_home: &Path, cwd: &Path, name: &str, dependencies: &[&str]) {
+    let lib_dir = cwd.join(name);
+    std::fs::create_dir(&lib_dir).unwrap();
+
+    crate::cli!()
+        .args(["init", "--lib", name])
+        .env("BUFFRS_HOME", buffrs_home)
+        .current_dir(&lib_dir)
+        .assert()
+        .success();
+
+    for dependency in dependencies {
+        crate::cli!()
+            .args(["add", "--registry", url, dependency])
+            .env("BUFFRS_HOME", buffrs_home)
+            .current_dir(&lib_dir)
+            .assert()
+            .success();
+    }
+
+    crate::cli!()
+        .args(["publish", "--registry", url, "--repository", "test-repo"])
+        .env("BUFFRS_HOME", buffrs_home)
+        .current_dir(&lib_dir)
+        .assert()
+        .success();
+
+    std::fs::remove_dir_all(&lib_dir).unwrap();
+}
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        for name in ["lib-c", "lib-d", "lib-e"] {
+            crate::publish_test_library(
+                &cwd.join("libs"),
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                None,
+                "lib.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        publish_lib(
+            url,
+            &buffrs_home,
+            &cwd,
+            "lib-b",
+            &["test-repo/lib-d@^0.1", "test-repo/lib-e@^0.1"],
+        );
+        publish_lib(
+            url,
+            &buffrs_home,
+            &cwd,
+            "lib-a",
+            &["test-repo/lib-c@^0.1", "test-repo/lib-d@^0.1"],
+        );
+
+        for dependency in ["test-repo/lib-a@^0.1", "test-repo/lib-b@^0.1"] {
+            crate::cli!()
+                .args(["add", "--registry", url, dependency])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&cwd)
+                .assert()
+                .success();
+        }
+
+        // Resolving one package at a time and several at once yields the same lockfile
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_JOBS", "1")
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let sequential = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+
+        for name in ["lib-a", "lib-b", "lib-c", "lib-d", "lib-e"] {
+            assert!(
+                sequential.contains(&format!("name = \"{name}\"")),
+                "expected {name} to be locked, got:\n{sequential}"
+            );
+        }
+
+        // lib-d is shared by lib-a and lib-b, the other packages have a single dependant
+        let lockfile: toml::Value = sequential.parse().unwrap();
+        for package in lockfile["packages"].as_array().unwrap() {
+            let expected = if package["name"].as_str() == Some("lib-d") {
+                2
+            } else {
+                1
+            };
+            assert_eq!(package["dependants"].as_integer(), Some(expected));
+        }
+
+        std::fs::remove_file(cwd.join("Proto.lock")).unwrap();
+
+        crate::cli!()
+            .args(["install", "--jobs", "4"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let concurrent = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+
+        assert_eq!(sequential, concurrent);
+
+        crate::cli!()
+            .args(["install", "--jobs", "0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+    });
+}
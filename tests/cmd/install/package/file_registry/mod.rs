@@ -0,0 +1,79 @@
+use crate::VirtualFileSystem;
+
+#[test]
+fn fixture() {
+    let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+    let buffrs_home = vfs.root().join("$HOME");
+    let cwd = vfs.root();
+
+    let registry = cwd.join("registry");
+    let url = url::Url::from_directory_path(&registry).unwrap();
+    let url = url.as_str().trim_end_matches('/');
+
+    // Publish a remote-lib to a directory instead of a web server
+    crate::publish_test_library(
+        &cwd,
+        &buffrs_home,
+        url,
+        "test-repo",
+        "remote-lib",
+        None,
+        "remote.proto",
+        "syntax = \"proto3\";\n\npackage remote;\n\nmessage Data {\n  string value = 1;\n}\n",
+        &[],
+    );
+
+    assert!(
+        registry
+            .join("test-repo/remote-lib/remote-lib-0.1.0.tgz")
+            .exists(),
+        "Expected remote-lib to be published to the registry directory"
+    );
+
+    // Resolve the latest version from the directory layout
+    crate::cli!()
+        .args(["add", "--registry", url, "test-repo/remote-lib@latest"])
+        .env("BUFFRS_HOME", &buffrs_home)
+        .current_dir(&cwd)
+        .assert()
+        .success();
+
+    crate::cli!()
+        .arg("install")
+        .env("BUFFRS_HOME", &buffrs_home)
+        .env("BUFFRS_CACHE", cwd.join("empty-cache"))
+        .current_dir(&cwd)
+        .assert()
+        .success();
+
+    assert!(
+        cwd.join("proto/vendor/remote-lib/remote.proto").exists(),
+        "Expected remote-lib to be installed in vendor directory"
+    );
+
+    // The lockfile points to the file in the registry directory
+    let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+    assert!(lockfile.contains(&format!("registry = \"{url}\"")));
+
+    let output = crate::cli!()
+        .args(["lock", "print-files"])
+        .current_dir(&cwd)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains(&format!("{url}/test-repo/remote-lib/remote-lib-0.1.0.tgz")));
+
+    // Reinstalling from the lockfile downloads the package from the directory again
+    std::fs::remove_dir_all(cwd.join("proto/vendor")).unwrap();
+
+    crate::cli!()
+        .args(["install", "--locked"])
+        .env("BUFFRS_HOME", &buffrs_home)
+        .env("BUFFRS_CACHE", cwd.join("another-empty-cache"))
+        .current_dir(&cwd)
+        .assert()
+        .success();
+
+    assert!(cwd.join("proto/vendor/remote-lib/remote.proto").exists());
+}
@@ -0,0 +1,231 @@
+use std::path::Path;
+
+use crate::VirtualFileSystem;
+
+const PROTO: &str =
+    "syntax = \"proto3\";\n\npackage shared;\n\nmessage Data {\n  string value = 1;\n}\n";
+
+/// Runs git in a directory, returning its trimmed output
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args([
+            "-c",
+            "user.name=buffrs",
+            "-c",
+            "user.email=buffrs@example.com",
+        ])
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "git {args:?} failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+#[test]
+fn fixture() {
+    let vfs = VirtualFileSystem::empty();
+    let cwd = vfs.root();
+
+    // 1. Create a repository containing a library package in a subdirectory
+    let work = cwd.join("work");
+    let lib = work.join("shared");
+    std::fs::create_dir_all(lib.join("proto")).unwrap();
+
+    git(&work, &["-c", "init.defaultBranch=main", "init", "--quiet"]);
+
+    crate::cli!()
+        .args(["init", "--lib", "shared-lib"])
+        .current_dir(&lib)
+        .assert()
+        .success();
+
+    std::fs::write(lib.join("proto/shared.proto"), PROTO).unwrap();
+
+    git(&work, &["add", "."]);
+    git(&work, &["commit", "--quiet", "-m", "Add shared library"]);
+
+    let first = git(&work, &["rev-parse", "HEAD"]);
+
+    let remote = cwd.join("protos.git");
+    git(
+        cwd.as_path(),
+        &["clone", "--quiet", "--bare", "work", "protos.git"],
+    );
+
+    // 2. Depend on the package through the bare repository
+    let app = cwd.join("app");
+    std::fs::create_dir_all(app.join("proto")).unwrap();
+
+    crate::cli!()
+        .args(["init", "--api", "app"])
+        .current_dir(&app)
+        .assert()
+        .success();
+
+    let url = format!("file://{}", remote.display());
+    let manifest = std::fs::read_to_string(app.join("Proto.toml")).unwrap();
+    std::fs::write(
+        app.join("Proto.toml"),
+        format!(
+            "{manifest}shared-lib = {{ git = \"{url}\", rev = \"main\", path = \"shared\" }}\n"
+        ),
+    )
+    .unwrap();
+
+    crate::cli!()
+        .arg("install")
+        .current_dir(&app)
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(app.join("proto/vendor/shared-lib/shared.proto")).unwrap(),
+        PROTO
+    );
+
+    let lockfile = std::fs::read_to_string(app.join("Proto.lock")).unwrap();
+    assert!(
+        lockfile.contains(&format!("commit = \"{first}\"")),
+        "unexpected lockfile:\n{lockfile}"
+    );
+    assert!(lockfile.contains("path = \"shared\""));
+
+    // 3. Move the branch forward
+    std::fs::write(
+        lib.join("proto/shared.proto"),
+        PROTO.replace("value = 1", "renamed = 1"),
+    )
+    .unwrap();
+
+    git(&work, &["commit", "--quiet", "-am", "Rename field"]);
+    git(
+        &work,
+        &["push", "--quiet", remote.to_str().unwrap(), "main"],
+    );
+
+    // 4. Installs keep using the locked commit
+    crate::cli!()
+        .arg("install")
+        .current_dir(&app)
+        .assert()
+        .success();
+
+    crate::cli!()
+        .args(["install", "--locked"])
+        .current_dir(&app)
+        .assert()
+        .success();
+
+    assert_eq!(
+        std::fs::read_to_string(app.join("proto/vendor/shared-lib/shared.proto")).unwrap(),
+        PROTO
+    );
+    assert_eq!(
+        std::fs::read_to_string(app.join("Proto.lock")).unwrap(),
+        lockfile
+    );
+
+    // 5. Git dependencies are never published
+    let output = crate::cli!()
+        .args([
+            "publish",
+            "--allow-dirty",
+            "--registry",
+            "http://localhost:1/registry",
+            "--repository",
+            "test-repo",
+        ])
+        .current_dir(&app)
+        .assert()
+        .failure();
+
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(
+        stderr.contains("cannot publish packages with git dependencies: shared-lib"),
+        "unexpected output:
+{stderr}"
+    );
+
+    // 6. Locked commits must be full commit ids
+    std::fs::write(app.join("Proto.lock"), lockfile.replace(&first, "../../db")).unwrap();
+
+    let output = crate::cli!()
+        .arg("install")
+        .current_dir(&app)
+        .assert()
+        .failure();
+
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(
+        stderr.contains("locked commit ../../db"),
+        "unexpected output:\n{stderr}"
+    );
+
+    std::fs::write(app.join("Proto.lock"), &lockfile).unwrap();
+
+    // 7. Packages have to be located inside the repository
+    std::fs::write(
+        app.join("Proto.toml"),
+        format!("{manifest}shared-lib = {{ git = \"{url}\", rev = \"main\", path = \"../..\" }}\n"),
+    )
+    .unwrap();
+
+    let output = crate::cli!()
+        .arg("install")
+        .current_dir(&app)
+        .assert()
+        .failure();
+
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(
+        stderr.contains("path ../.. of git dependency shared-lib"),
+        "unexpected output:\n{stderr}"
+    );
+
+    // 8. Dependants have to agree on the revision of a git dependency
+    let other = cwd.join("other");
+    std::fs::create_dir_all(other.join("proto")).unwrap();
+
+    crate::cli!()
+        .args(["init", "--lib", "other-lib"])
+        .current_dir(&other)
+        .assert()
+        .success();
+
+    let other_manifest = std::fs::read_to_string(other.join("Proto.toml")).unwrap();
+    std::fs::write(
+        other.join("Proto.toml"),
+        format!(
+            "{other_manifest}shared-lib = {{ git = \"{url}\", rev = \"{first}\", path = \"shared\" }}\n"
+        ),
+    )
+    .unwrap();
+
+    std::fs::write(
+        app.join("Proto.toml"),
+        format!(
+            "{manifest}shared-lib = {{ git = \"{url}\", rev = \"main\", path = \"shared\" }}\n\
+             other-lib = {{ path = \"../other\" }}\n"
+        ),
+    )
+    .unwrap();
+
+    let output = crate::cli!()
+        .arg("install")
+        .current_dir(&app)
+        .assert()
+        .failure();
+
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(
+        stderr.contains("conflicting sources for package"),
+        "unexpected output:\n{stderr}"
+    );
+}
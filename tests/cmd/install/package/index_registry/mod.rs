@@ -0,0 +1,40 @@
+use crate::{VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        // The test registry serves and stores arbitrary files, like a static web server
+        let registry = format!("index+{url}");
+
+        // The latest version is looked up in the index
+        crate::install_latest_test_library(&cwd, &buffrs_home, &registry, &[]);
+
+        // The locked files are plain downloads
+        let output = crate::cli!()
+            .args(["lock", "print-files"])
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(stdout.contains(&format!(
+            "\"{url}/test-repo/remote-lib/remote-lib-0.2.0.tgz\""
+        )));
+
+        std::fs::remove_dir_all(cwd.join("proto/vendor")).unwrap();
+
+        crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("another-empty-cache"))
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        assert!(cwd.join("proto/vendor/remote-lib/remote.proto").exists());
+    })
+}
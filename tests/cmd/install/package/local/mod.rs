@@ -13,4 +13,27 @@ fn fixture() {
         .stderr(include_str!("stderr.log"));
 
     vfs.verify_against(crate::parent_directory!().join("out"));
+
+    crate::cli!()
+        .args(["install", "--locked"])
+        .current_dir(vfs.root())
+        .assert()
+        .success();
+
+    // Changing the contents of a local package changes its locked digest
+    let proto = vfs.root().join("some-local-api/proto/local.proto");
+    let contents = std::fs::read_to_string(&proto).unwrap();
+    std::fs::write(&proto, format!("{contents}\n// changed\n")).unwrap();
+
+    let output = crate::cli!()
+        .args(["install", "--locked"])
+        .current_dir(vfs.root())
+        .assert()
+        .failure();
+
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(
+        stderr.contains("~ some-local-api (some-local-api) digest"),
+        "expected the changed digest in the error output, got:\n{stderr}"
+    );
 }
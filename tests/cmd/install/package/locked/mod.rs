@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{PROTO, VirtualFileSystem, utils::list_files, with_test_registry};
+
+/// Contents of the vendored packages, by path
+fn vendored(cwd: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    let vendor = cwd.join("proto/vendor");
+
+    if !vendor.exists() {
+        return BTreeMap::new();
+    }
+
+    list_files(&vendor)
+        .into_iter()
+        .map(|path| {
+            let contents = std::fs::read(&path).unwrap();
+            (path.strip_prefix(&vendor).unwrap().to_path_buf(), contents)
+        })
+        .collect()
+}
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        for name in ["remote-lib", "other-lib"] {
+            crate::publish_test_library(
+                &cwd,
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                None,
+                "remote.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/remote-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // Without a lockfile, the resolution always differs
+        let output = crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("+ remote-lib 0.1.0"),
+            "expected the added entry in the error output, got:\n{stderr}"
+        );
+        assert!(!cwd.join("Proto.lock").exists());
+        assert!(vendored(&cwd).is_empty());
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        let vendor = vendored(&cwd);
+
+        assert!(vendor.contains_key(Path::new("remote-lib/remote.proto")));
+
+        // An up to date lockfile is accepted, also without network access
+        crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .args(["install", "--frozen"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // A new dependency is not added to the lockfile
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/other-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let output = crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("+ other-lib 0.1.0") && !stderr.contains("remote-lib"),
+            "expected only the new entry in the error output, got:\n{stderr}"
+        );
+
+        // A refused installation leaves the vendored packages untouched
+        assert_eq!(vendor, vendored(&cwd));
+
+        // --frozen does not download the new dependency
+        let output = crate::cli!()
+            .args(["install", "--frozen"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("empty-cache"))
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("offline"),
+            "expected 'offline' in error output, got:\n{stderr}"
+        );
+
+        assert_eq!(
+            lockfile,
+            std::fs::read_to_string(cwd.join("Proto.lock")).unwrap()
+        );
+        assert_eq!(vendor, vendored(&cwd));
+    });
+}
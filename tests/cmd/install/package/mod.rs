@@ -1,6 +1,18 @@
+mod concurrent;
 mod empty;
+mod file_registry;
+mod git;
+mod index_registry;
 mod local;
+mod locked;
 mod lockfile;
+mod oci_registry;
 mod offline;
 mod online;
+mod patch;
+mod retry;
+mod s3_registry;
+mod tarball;
+mod unified_requirements;
 mod upgrade;
+mod version_range;
@@ -0,0 +1,72 @@
+use crate::{VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        // The test registry implements the OCI distribution API, served over plain HTTP on loopback
+        let registry = url.replacen("http://", "oci://", 1);
+
+        // The latest version is looked up in the tags of the repository
+        crate::install_latest_test_library(&cwd, &buffrs_home, &registry, &[]);
+
+        // The locked files address the package layers by digest
+        let output = crate::cli!()
+            .args(["lock", "print-files"])
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        let base = url.replacen("/registry", "/v2/registry", 1);
+        assert!(
+            stdout.contains(&format!("\"{base}/test-repo/remote-lib/blobs/sha256:")),
+            "unexpected files:\n{stdout}"
+        );
+
+        // The lockfile pins the artifact manifest by digest
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        let manifest = lockfile
+            .lines()
+            .find_map(|line| line.strip_prefix("manifest = "))
+            .unwrap_or_else(|| panic!("no manifest locked:\n{lockfile}"));
+        assert!(manifest.starts_with("\"sha256:"), "{lockfile}");
+
+        std::fs::remove_dir_all(cwd.join("proto/vendor")).unwrap();
+
+        crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("another-empty-cache"))
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        assert!(cwd.join("proto/vendor/remote-lib/remote.proto").exists());
+
+        // Locked installations pull the artifact through the pinned manifest instead of its tag
+        let unknown = format!("\"sha256:{}\"", "0".repeat(64));
+        std::fs::write(
+            cwd.join("Proto.lock"),
+            lockfile.replacen(manifest, &unknown, 1),
+        )
+        .unwrap();
+
+        let output = crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("yet-another-empty-cache"))
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("is not published"),
+            "unexpected error output:\n{stderr}"
+        );
+    })
+}
@@ -17,6 +17,7 @@ fn fixture() {
             None,
             "remote.proto",
             "syntax = \"proto3\";\n\npackage remote;\n\nmessage Data {\n  string value = 1;\n}\n",
+            &[],
         );
 
         // Add the dependency
@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use crate::{VirtualFileSystem, with_test_registry};
+
+const PROTO: &str =
+    "syntax = \"proto3\";\n\npackage lib;\n\nmessage Data {\n  string value = 1;\n}\n";
+
+/// Creates and publishes a library depending on the given packages
+fn publish_lib(url: &str, buffrs_home: &Path, cwd: &Path, name: &str, dependencies: &[&str]) {
+    let lib_dir = cwd.join(name);
+    std::fs::create_dir(&lib_dir).unwrap();
+
+    crate::cli!()
+        .args(["init", "--lib", name])
+        .env("BUFFRS_HOME", buffrs_home)
+        .current_dir(&lib_dir)
+        .assert()
+        .success();
+
+    for dependency in dependencies {
+        crate::cli!()
+            .args(["add", "--registry", url, dependency])
+            .env("BUFFRS_HOME", buffrs_home)
+            .current_dir(&lib_dir)
+            .assert()
+            .success();
+    }
+
+    crate::cli!()
+        .args(["publish", "--registry", url, "--repository", "test-repo"])
+        .env("BUFFRS_HOME", buffrs_home)
+        .current_dir(&lib_dir)
+        .assert()
+        .success();
+
+    std::fs::remove_dir_all(&lib_dir).unwrap();
+}
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        crate::publish_test_library(
+            &cwd.join("libs"),
+            &buffrs_home,
+            url,
+            "test-repo",
+            "lib-b",
+            None,
+            "lib.proto",
+            PROTO,
+            &[],
+        );
+
+        publish_lib(url, &buffrs_home, &cwd, "lib-a", &["test-repo/lib-b@^0.1"]);
+
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/lib-a@^0.1"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // Replace the transitive dependency on lib-b with a local checkout
+        let manifest = std::fs::read_to_string(cwd.join("Proto.toml")).unwrap();
+        std::fs::write(
+            cwd.join("Proto.toml"),
+            format!("{manifest}\n[patch]\nlib-b = {{ path = \"lib-b-fix\" }}\n"),
+        )
+        .unwrap();
+
+        let output = crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(
+            stdout
+                .contains("patch active: every dependency on lib-b is replaced by path lib-b-fix"),
+            "expected a patch warning, got:\n{stdout}"
+        );
+
+        let vendored = std::fs::read_to_string(cwd.join("proto/vendor/lib-b/lib.proto")).unwrap();
+        assert!(vendored.contains("string fixed = 2;"));
+
+        // Patched local packages are locked by path instead of the published version
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        assert!(lockfile.contains("name = \"lib-a\""));
+        assert_eq!(lockfile.matches("name = \"lib-b\"").count(), 1);
+        assert!(lockfile.contains("path = \"lib-b-fix\""));
+
+        // Publishing with an active patch is refused
+        let output = crate::cli!()
+            .args([
+                "publish",
+                "--registry",
+                url,
+                "--repository",
+                "test-repo",
+                "--dry-run",
+            ])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("cannot publish while dependencies are patched: lib-b"),
+            "expected publish to be refused, got:\n{stderr}"
+        );
+    });
+}
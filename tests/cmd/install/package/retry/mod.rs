@@ -0,0 +1,91 @@
+use crate::{VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        for name in ["remote-lib", "other-lib", "late-lib"] {
+            crate::publish_test_library(
+                &cwd,
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                None,
+                "remote.proto",
+                "syntax = \"proto3\";\n\npackage remote;\n\nmessage Data {\n  string value = 1;\n}\n",
+                &[],
+            );
+        }
+
+        // Every package served below /flaky fails three times before it is delivered
+        let flaky = url.replace("/registry", "/flaky/registry");
+
+        crate::cli!()
+            .args(["add", "--registry", &flaky, "test-repo/remote-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let output = crate::cli!()
+            .args(["install"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("remote-cache"))
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert_eq!(stdout.matches("retrying GET").count(), 3, "{stdout}");
+        assert!(cwd.join("proto/vendor/remote-lib/remote.proto").exists());
+
+        // Giving up before the package is delivered fails the installation
+        crate::cli!()
+            .args(["add", "--registry", &flaky, "test-repo/other-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .args(["install"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("other-cache"))
+            .env("BUFFRS_HTTP_RETRIES", "1")
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        // No retry is started once it would exceed the retry deadline
+        crate::cli!()
+            .args(["rm", "other-lib"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .args(["add", "--registry", &flaky, "test-repo/late-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let output = crate::cli!()
+            .args(["install"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("late-cache"))
+            .env("BUFFRS_HTTP_RETRY_DEADLINE", "0")
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(!stdout.contains("retrying GET"), "{stdout}");
+        assert!(stdout.contains("retry deadline of 0s exceeded"), "{stdout}");
+    });
+}
@@ -0,0 +1,42 @@
+use crate::{VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        // The test registry stores arbitrary paths, so it serves as an S3 endpoint with path style
+        // addressing where the first path segment is the bucket
+        let endpoint = url.strip_suffix("/registry").unwrap();
+        let registry = "s3://registry/protos";
+
+        let env = [
+            ("AWS_ENDPOINT_URL", endpoint),
+            ("AWS_REGION", "eu-central-1"),
+            ("AWS_ACCESS_KEY_ID", "test-access-key"),
+            ("AWS_SECRET_ACCESS_KEY", "test-secret-key"),
+        ];
+
+        // The latest version is looked up with ListObjectsV2
+        crate::install_latest_test_library(&cwd, &buffrs_home, registry, &env);
+
+        // The locked files point to the objects at the configured endpoint
+        let output = crate::cli!()
+            .args(["lock", "print-files"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .envs(env)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(
+            stdout.contains(&format!(
+                "\"{url}/protos/test-repo/remote-lib/remote-lib-0.2.0.tgz\""
+            )),
+            "unexpected files:\n{stdout}"
+        );
+    })
+}
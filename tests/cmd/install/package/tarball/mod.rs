@@ -0,0 +1,190 @@
+use std::{io::Write, path::Path};
+
+use buffrs::lock::DigestAlgorithm;
+
+use crate::{VirtualFileSystem, with_test_registry};
+
+const VENDOR_PROTO: &str =
+    "syntax = \"proto3\";\n\npackage vendor;\n\nmessage Data {\n  string value = 1;\n}\n";
+
+const RAW_PROTO: &str =
+    "syntax = \"proto3\";\n\npackage raw;\n\nmessage Event {\n  string id = 1;\n}\n";
+
+/// Creates a tarball without a manifest, like the release artifacts of other tools
+fn raw_tarball(path: &Path, files: &[(&str, &str)]) {
+    let mut archive = tar::Builder::new(Vec::new());
+
+    for (name, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(0o644);
+        header.set_size(contents.len() as u64);
+
+        archive
+            .append_data(&mut header, name, contents.as_bytes())
+            .unwrap();
+    }
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&archive.into_inner().unwrap()).unwrap();
+
+    std::fs::write(path, encoder.finish().unwrap()).unwrap();
+}
+
+fn digest(path: &Path) -> String {
+    DigestAlgorithm::SHA256
+        .digest(&std::fs::read(path).unwrap())
+        .to_string()
+}
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        // 1. A buffrs package served over HTTP and a plain tarball on disk
+        crate::publish_test_library(
+            &cwd,
+            &buffrs_home,
+            url,
+            "releases",
+            "vendor-lib",
+            Some("1.2.0"),
+            "vendor.proto",
+            VENDOR_PROTO,
+            &[],
+        );
+
+        // Publishing is reproducible, so a mirror on disk yields the digest of the archive
+        crate::cli!()
+            .args([
+                "publish",
+                "--registry",
+                &format!("file://{}", cwd.join("mirror").display()),
+                "--repository",
+                "releases",
+            ])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(cwd.join("vendor-lib"))
+            .assert()
+            .success();
+
+        let vendor_digest = digest(&cwd.join("mirror/releases/vendor-lib/vendor-lib-1.2.0.tgz"));
+
+        std::fs::create_dir(cwd.join("dist")).unwrap();
+
+        let raw = cwd.join("dist/raw-protos-0.3.0.tgz");
+        raw_tarball(&raw, &[("raw.proto", RAW_PROTO)]);
+
+        let manifest = std::fs::read_to_string(cwd.join("Proto.toml")).unwrap();
+        let manifest = format!(
+            "{manifest}\
+             vendor-lib = {{ url = \"{url}/releases/vendor-lib/vendor-lib-1.2.0.tgz\", digest = \"{vendor_digest}\" }}\n\
+             raw-protos = {{ url = \"file://{}\", digest = \"{}\" }}\n",
+            raw.display(),
+            digest(&raw),
+        );
+        std::fs::write(cwd.join("Proto.toml"), &manifest).unwrap();
+
+        // 2. Tarballs are verified, unpacked and cached
+        let cache = cwd.join("cache");
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", &cache)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("proto/vendor/vendor-lib/vendor.proto")).unwrap(),
+            VENDOR_PROTO
+        );
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("proto/vendor/raw-protos/raw.proto")).unwrap(),
+            RAW_PROTO
+        );
+
+        crate::cli!()
+            .args(["lock", "verify"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", &cache)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // 3. Cached tarballs are available offline, even if their source changed
+        raw_tarball(&raw, &[("raw.proto", "syntax = \"proto3\";\n")]);
+
+        crate::cli!()
+            .args(["install", "--offline"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", &cache)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("proto/vendor/raw-protos/raw.proto")).unwrap(),
+            RAW_PROTO
+        );
+
+        // 4. Tarballs which do not match their digest are rejected
+        let output = crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", cwd.join("empty-cache"))
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("digest mismatch for raw-protos"),
+            "unexpected output:\n{stderr}"
+        );
+
+        // 5. Dependants have to agree on the digest of a tarball dependency
+        let other = cwd.join("other");
+        std::fs::create_dir_all(other.join("proto")).unwrap();
+
+        crate::cli!()
+            .args(["init", "--lib", "other-lib"])
+            .current_dir(&other)
+            .assert()
+            .success();
+
+        let other_manifest = std::fs::read_to_string(other.join("Proto.toml")).unwrap();
+        std::fs::write(
+            other.join("Proto.toml"),
+            format!(
+                "{other_manifest}\
+                 vendor-lib = {{ url = \"{url}/releases/vendor-lib/vendor-lib-1.2.0.tgz\", digest = \"sha256:{}\" }}\n",
+                "0".repeat(64)
+            ),
+        )
+        .unwrap();
+
+        std::fs::write(
+            cwd.join("Proto.toml"),
+            format!("{manifest}other-lib = {{ path = \"other\" }}\n"),
+        )
+        .unwrap();
+
+        let output = crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .env("BUFFRS_CACHE", &cache)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("conflicting sources for package"),
+            "unexpected output:\n{stderr}"
+        );
+    })
+}
@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use crate::{VirtualFileSystem, with_test_registry};
+
+/// Creates and publishes a library at the given versions, with optional dependencies
+fn publish_lib(
+    url: &str,
+    buffrs_home: &Path,
+    cwd: &Path,
+    name: &str,
+    versions: &[&str],
+    dependencies: &[&str],
+) {
+    let lib_dir = cwd.join(name);
+    std::fs::create_dir(&lib_dir).unwrap();
+
+    crate::cli!()
+        .args(["init", "--lib", name])
+        .env("BUFFRS_HOME", buffrs_home)
+        .current_dir(&lib_dir)
+        .assert()
+        .success();
+
+    for dependency in dependencies {
+        crate::cli!()
+            .args(["add", "--registry", url, dependency])
+            .env("BUFFRS_HOME", buffrs_home)
+            .current_dir(&lib_dir)
+            .assert()
+            .success();
+    }
+
+    let manifest_path = lib_dir.join("Proto.toml");
+    let mut current = "0.1.0";
+
+    for version in versions {
+        let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+        let updated = manifest.replacen(
+            &format!("version = \"{current}\""),
+            &format!("version = \"{version}\""),
+            1,
+        );
+        std::fs::write(&manifest_path, updated).unwrap();
+        current = version;
+
+        crate::cli!()
+            .args(["publish", "--registry", url, "--repository", "test-repo"])
+            .env("BUFFRS_HOME", buffrs_home)
+            .current_dir(&lib_dir)
+            .assert()
+            .success();
+    }
+
+    std::fs::remove_dir_all(&lib_dir).unwrap();
+}
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        publish_lib(url, &buffrs_home, &cwd, "lib-b", &["1.0.0", "1.1.0"], &[]);
+        publish_lib(
+            url,
+            &buffrs_home,
+            &cwd,
+            "lib-a",
+            &["1.0.0"],
+            &["test-repo/lib-b@~1.0"],
+        );
+
+        // The root accepts any 1.x release of lib-b, lib-a only accepts 1.0.x
+        for dependency in ["test-repo/lib-b@^1.0", "test-repo/lib-a@=1.0.0"] {
+            crate::cli!()
+                .args(["add", "--registry", url, dependency])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&cwd)
+                .assert()
+                .success();
+        }
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        let lib_b = lockfile
+            .split("[[packages]]")
+            .find(|s| s.contains("name = \"lib-b\""))
+            .unwrap();
+
+        assert!(
+            lib_b.contains("version = \"1.0.0\""),
+            "expected lib-b to resolve to 1.0.0, got:\n{lockfile}"
+        );
+
+        // Requiring lib-b 1.1 or newer at the root cannot be satisfied together with lib-a
+        std::fs::remove_file(cwd.join("Proto.lock")).unwrap();
+
+        let manifest = std::fs::read_to_string(cwd.join("Proto.toml")).unwrap();
+        std::fs::write(cwd.join("Proto.toml"), manifest.replacen("^1.0", "^1.1", 1)).unwrap();
+
+        let output = crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("version conflict for lib-b")
+                && stderr.contains("^1.1 (required by")
+                && stderr.contains("~1.0 (required by"),
+            "expected conflicting requirements in error output, got:\n{stderr}"
+        );
+    });
+}
@@ -0,0 +1,88 @@
+use crate::{VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        let dummy = cwd.join("dummy");
+
+        let publish = |from: &str, to: &str| {
+            let manifest = std::fs::read_to_string(dummy.join("Proto.toml")).unwrap();
+            let updated_manifest = manifest.replacen(from, to, 1);
+            std::fs::write(dummy.join("Proto.toml"), updated_manifest).unwrap();
+
+            // buffrs publish --repository dummy
+            crate::cli!()
+                .args(["publish", "--registry", url, "--repository", "dummy"])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&dummy)
+                .assert()
+                .success();
+        };
+
+        // Dummy Library
+        {
+            // mkdir dummy
+            std::fs::create_dir(&dummy).unwrap();
+
+            // buffrs init --lib dummy
+            crate::cli!()
+                .args(["init", "--lib", "dummy"])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&dummy)
+                .assert()
+                .success();
+
+            // ed proto/dummy.proto
+            std::fs::copy(
+                crate::parent_directory!().join("in/dummy.proto"),
+                dummy.join("proto/dummy.proto"),
+            )
+            .unwrap();
+
+            publish("0.1.0", "0.1.0");
+            publish("0.1.0", "0.1.1");
+            publish("0.1.1", "0.2.0");
+        }
+
+        crate::cli!()
+            .args(["add", "--registry", url, "dummy/dummy@^0.1"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // Resolves to the highest version matching the requirement
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(include_str!("stdout.log"))
+            .stderr(include_str!("stderr.log"));
+
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        assert!(lockfile.contains("version = \"0.1.1\""));
+
+        // Newer matching versions are ignored once a version is locked
+        publish("0.2.0", "0.1.2");
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(include_str!("stdout.log"))
+            .stderr(include_str!("stderr.log"));
+
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("Proto.lock")).unwrap(),
+            lockfile
+        );
+    })
+}
@@ -17,6 +17,7 @@ fn fixture() {
             None,
             "remote.proto",
             "syntax = \"proto3\";\n\npackage remote;\n\nmessage Data {\n  string value = 1;\n}\n",
+            &[],
         );
 
         // Add remote-lib to pkg1
@@ -66,16 +67,21 @@ fn fixture() {
 
         // Update digest in expected lockfile
         let actual_lockfile_content = std::fs::read_to_string(&lockfile_path).unwrap();
-        let digest = actual_lockfile_content
+        let mut digests = actual_lockfile_content
             .lines()
-            .find(|line| line.starts_with("digest = "))
-            .unwrap()
-            .trim();
+            .filter(|line| line.starts_with("digest = "))
+            .map(str::trim);
+
+        // The local package embeds the registry URL, so its digest differs between runs
+        let digest = digests.next().unwrap();
+        let local_digest = digests.next_back().unwrap();
 
         let expected_lockfile_path = temp_expected.join("Proto.lock");
         if expected_lockfile_path.exists() {
             let content = std::fs::read_to_string(&expected_lockfile_path).unwrap();
-            let updated = content.replace("digest = \"DIGEST\"", digest);
+            let updated = content
+                .replace("digest = \"DIGEST\"", digest)
+                .replace("digest = \"LOCAL_DIGEST\"", local_digest);
             std::fs::write(&expected_lockfile_path, updated).unwrap();
         }
 
@@ -138,14 +138,14 @@ fn fixture() {
         // Extract digests for both packages
         let digest_a = actual_lockfile
             .split("[[packages]]")
-            .find(|s| s.contains("name = \"lib-a\""))
+            .find(|s| s.trim_start().starts_with("name = \"lib-a\""))
             .and_then(|s| s.lines().find(|line| line.starts_with("digest = ")))
             .unwrap()
             .trim();
 
         let digest_b = actual_lockfile
             .split("[[packages]]")
-            .find(|s| s.contains("name = \"lib-b\""))
+            .find(|s| s.trim_start().starts_with("name = \"lib-b\""))
             .and_then(|s| s.lines().find(|line| line.starts_with("digest = ")))
             .unwrap()
             .trim();
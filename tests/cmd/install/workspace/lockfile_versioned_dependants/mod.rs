@@ -0,0 +1,72 @@
+use crate::{PROTO, VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::empty();
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        crate::publish_test_library(
+            &cwd.join("libs"),
+            &buffrs_home,
+            url,
+            "test-repo",
+            "remote-lib",
+            None,
+            "remote.proto",
+            PROTO,
+            &[],
+        );
+
+        std::fs::write(
+            cwd.join("Proto.toml"),
+            "edition = \"0.13\"\n\n[workspace]\nmembers = [\"pkg1\", \"pkg2\"]\n",
+        )
+        .unwrap();
+
+        // Both members depend on remote-lib through different versions of the same local package
+        for (member, version) in [("pkg1", "1.0.0"), ("pkg2", "2.0.0")] {
+            let shared = cwd.join(format!("shared-{version}"));
+            std::fs::create_dir_all(shared.join("proto")).unwrap();
+            std::fs::write(
+                shared.join("Proto.toml"),
+                format!(
+                    "edition = \"0.13\"\n\n[package]\ntype = \"lib\"\nname = \"shared-lib\"\nversion = \"{version}\"\n\n[dependencies]\n"
+                ),
+            )
+            .unwrap();
+
+            crate::cli!()
+                .args(["add", "--registry", url, "test-repo/remote-lib@=0.1.0"])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&shared)
+                .assert()
+                .success();
+
+            let member_dir = cwd.join(member);
+            std::fs::create_dir_all(member_dir.join("proto")).unwrap();
+            std::fs::write(
+                member_dir.join("Proto.toml"),
+                format!(
+                    "edition = \"0.13\"\n\n[package]\ntype = \"api\"\nname = \"workspace-{member}\"\nversion = \"1.0.0\"\n\n[dependencies]\nshared-lib = {{ path = \"../shared-{version}\" }}\n"
+                ),
+            )
+            .unwrap();
+        }
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // Each version of shared-lib counts as a dependant of its own
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        assert!(
+            lockfile.contains("name = \"remote-lib\"") && lockfile.contains("dependants = 2"),
+            "unexpected lockfile:\n{lockfile}"
+        );
+    })
+}
@@ -3,3 +3,4 @@ mod lockfile;
 mod lockfile_diamond_dependencies;
 mod lockfile_multiple_versions;
 mod lockfile_transitive;
+mod lockfile_versioned_dependants;
@@ -0,0 +1,102 @@
+use crate::{PROTO, VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        crate::publish_test_library(
+            &cwd,
+            &buffrs_home,
+            url,
+            "test-repo",
+            "remote-lib",
+            None,
+            "remote.proto",
+            PROTO,
+            &[],
+        );
+
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/remote-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // New lockfiles use the latest format
+        let latest = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        let lock: toml::Table = toml::from_str(&latest).unwrap();
+        let package = lock["packages"][0].as_table().unwrap();
+
+        assert_eq!(lock["version"].as_integer(), Some(2));
+        assert_eq!(package["edition"].as_str(), Some("0.13"));
+        assert_eq!(package["files"][0]["path"].as_str(), Some("remote.proto"));
+
+        // Downgrade the lockfile to the v1 format
+        let mut legacy = lock.clone();
+        legacy.insert("version".into(), 1.into());
+
+        for package in legacy["packages"].as_array_mut().unwrap() {
+            let package = package.as_table_mut().unwrap();
+            package.remove("edition");
+            package.remove("files");
+        }
+
+        std::fs::write(cwd.join("Proto.lock"), toml::to_string(&legacy).unwrap()).unwrap();
+
+        // Installing keeps the format of existing lockfiles
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let installed = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        let lock: toml::Table = toml::from_str(&installed).unwrap();
+
+        assert_eq!(lock["version"].as_integer(), Some(1));
+        assert!(!installed.contains("files"));
+
+        crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // Packages missing from the cache are downloaded again
+        std::fs::remove_dir_all(buffrs_home.join(".buffrs").join("cache")).unwrap();
+
+        crate::cli!()
+            .args(["lock", "migrate"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(":: migrated Proto.lock from v1 to v2\n");
+
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("Proto.lock")).unwrap(),
+            latest
+        );
+
+        crate::cli!()
+            .args(["lock", "migrate"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(":: Proto.lock already uses the latest format (v2)\n");
+    })
+}
@@ -1 +1,4 @@
+mod migrate;
 mod print_files;
+mod resolve_conflicts;
+mod verify;
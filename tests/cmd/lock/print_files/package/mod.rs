@@ -13,3 +13,27 @@ fn fixture() {
         .stdout(include_str!("stdout.log"))
         .stderr(include_str!("stderr.log"));
 }
+
+#[test]
+fn nix() {
+    let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+
+    crate::cli!()
+        .args(["lock", "print-files", "--format", "nix"])
+        .current_dir(vfs.root())
+        .assert()
+        .success()
+        .stdout(include_str!("stdout.nix"));
+}
+
+#[test]
+fn bazel() {
+    let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+
+    crate::cli!()
+        .args(["lock", "print-files", "--format", "bazel"])
+        .current_dir(vfs.root())
+        .assert()
+        .success()
+        .stdout(include_str!("stdout.bzl"));
+}
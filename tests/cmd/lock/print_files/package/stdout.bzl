@@ -0,0 +1,27 @@
+load("@bazel_tools//tools/build_defs/repo:http.bzl", "http_archive", "http_file")
+
+_BUILD_FILE = """
+filegroup(
+    name = "protos",
+    srcs = glob(["**/*.proto"]),
+    visibility = ["//visibility:public"],
+)
+"""
+
+def buffrs_dependencies():
+    """Declares the packages locked by Proto.lock"""
+
+    http_file(
+        name = "buffrs_remote_lib_0_1_0_tgz",
+        urls = ["https://buffrs.local/artifactory/test-repo/remote-lib/remote-lib-0.1.0.tgz"],
+        integrity = "sha256-obLD1OX2eJASNFZ4kBI0VniQq83vEjRWeJCrze8SNFY=",
+        downloaded_file_path = "remote-lib-0.1.0.tgz",
+    )
+
+    http_archive(
+        name = "buffrs_remote_lib_0_1_0",
+        urls = ["https://buffrs.local/artifactory/test-repo/remote-lib/remote-lib-0.1.0.tgz"],
+        integrity = "sha256-obLD1OX2eJASNFZ4kBI0VniQq83vEjRWeJCrze8SNFY=",
+        type = "tar.gz",
+        build_file_content = _BUILD_FILE,
+    )
@@ -0,0 +1,122 @@
+use crate::{PROTO, VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+        let manifest = std::fs::read_to_string(cwd.join("Proto.toml")).unwrap();
+
+        for name in ["lib-a", "lib-b"] {
+            crate::publish_test_library(
+                &cwd,
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                None,
+                "remote.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        // Each branch adds a different dependency
+        let mut branches = Vec::new();
+
+        for name in ["lib-a", "lib-b"] {
+            std::fs::write(cwd.join("Proto.toml"), &manifest).unwrap();
+            std::fs::remove_file(cwd.join("Proto.lock")).ok();
+
+            crate::cli!()
+                .args([
+                    "add",
+                    "--registry",
+                    url,
+                    &format!("test-repo/{name}@=0.1.0"),
+                ])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&cwd)
+                .assert()
+                .success();
+
+            crate::cli!()
+                .arg("install")
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&cwd)
+                .assert()
+                .success();
+
+            branches.push(std::fs::read_to_string(cwd.join("Proto.lock")).unwrap());
+        }
+
+        // Merging both branches keeps both dependencies and conflicts in the lockfile
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/lib-a@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let conflicted = format!(
+            "<<<<<<< HEAD\n{}=======\n{}>>>>>>> branch\n",
+            branches[0], branches[1]
+        );
+
+        std::fs::write(cwd.join("Proto.lock"), &conflicted).unwrap();
+
+        let output = crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("Proto.lock contains unresolved merge conflicts"),
+            "unexpected error output:\n{stderr}"
+        );
+
+        crate::cli!()
+            .args(["lock", "resolve-conflicts"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(":: resolved merge conflicts in Proto.lock\n");
+
+        let resolved = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        let lock: toml::Table = toml::from_str(&resolved).unwrap();
+        let names: Vec<_> = lock["packages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|package| package["name"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(names, ["lib-a", "lib-b"]);
+
+        // The resolved lockfile matches the merged manifest
+        crate::cli!()
+            .args(["install", "--locked"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("Proto.lock")).unwrap(),
+            resolved
+        );
+
+        crate::cli!()
+            .args(["lock", "resolve-conflicts"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(":: no merge conflicts found in Proto.lock\n");
+    })
+}
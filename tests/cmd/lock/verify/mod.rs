@@ -0,0 +1,104 @@
+use crate::{PROTO, VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+        let vendor = cwd.join("proto/vendor/remote-lib");
+
+        crate::publish_test_library(
+            &cwd,
+            &buffrs_home,
+            url,
+            "test-repo",
+            "remote-lib",
+            None,
+            "remote.proto",
+            PROTO,
+            &[],
+        );
+
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/remote-lib@=0.1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .args(["lock", "verify"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(":: installed packages match Proto.lock\n");
+
+        // Hand-edited and added vendored files are reported
+        std::fs::write(vendor.join("remote.proto"), PROTO.replace("Data", "Edited")).unwrap();
+        std::fs::write(vendor.join("extra.proto"), PROTO).unwrap();
+
+        let output = crate::cli!()
+            .args(["lock", "verify"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure()
+            .stdout(
+                "tampered: proto/vendor/remote-lib/remote.proto\n\
+                 extra: proto/vendor/remote-lib/extra.proto\n",
+            );
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("2 differences between the installed packages and Proto.lock"),
+            "unexpected error output:\n{stderr}"
+        );
+
+        // Missing packages are reported
+        std::fs::remove_dir_all(&vendor).unwrap();
+
+        crate::cli!()
+            .args(["lock", "verify"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure()
+            .stdout("missing: remote-lib 0.1.0 is not installed\n");
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // Tampered cache entries are reported
+        let cache = buffrs_home.join(".buffrs/cache");
+
+        for entry in std::fs::read_dir(&cache).unwrap() {
+            std::fs::write(entry.unwrap().path(), "tampered").unwrap();
+        }
+
+        let output = crate::cli!()
+            .args(["lock", "verify"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(
+            stdout.starts_with("tampered: ") && stdout.trim_end().ends_with(".tgz"),
+            "unexpected output:\n{stdout}"
+        );
+    })
+}
@@ -1,11 +1,17 @@
 mod add;
+mod info;
 mod init;
 mod install;
 mod lint;
+mod lock;
 mod login;
 mod logout;
 mod ls;
 mod package;
 mod publish;
 mod remove;
+mod search;
+mod tree;
 mod tuto;
+mod update;
+mod why;
@@ -0,0 +1,99 @@
+use crate::{PROTO, VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::empty();
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        let directory = url::Url::from_directory_path(cwd.join("registry")).unwrap();
+
+        let registries = [
+            url.to_owned(),
+            url.replacen("http://", "oci://", 1),
+            directory.as_str().trim_end_matches('/').to_owned(),
+        ];
+
+        for (index, registry) in registries.iter().enumerate() {
+            for (repository, name, version) in [
+                ("test-repo", "remote-lib", "0.1.0"),
+                ("test-repo", "remote-lib", "0.2.0"),
+                ("test-repo", "unrelated-lib", "0.1.0"),
+                ("other-repo", "remote-api", "1.0.0"),
+            ] {
+                crate::publish_test_library(
+                    &cwd.join(format!("libs-{index}-{repository}-{version}")),
+                    &buffrs_home,
+                    registry,
+                    repository,
+                    name,
+                    Some(version),
+                    "remote.proto",
+                    PROTO,
+                    &[],
+                );
+            }
+        }
+
+        for registry in &registries {
+            let search = |args: &[&str]| {
+                let output = crate::cli!()
+                    .args(["search", "--registry", registry])
+                    .args(args)
+                    .env("BUFFRS_HOME", &buffrs_home)
+                    .current_dir(&cwd)
+                    .assert()
+                    .success();
+
+                String::from_utf8_lossy(&output.get_output().stdout).into_owned()
+            };
+
+            assert_eq!(
+                search(&["remote"]),
+                "other-repo/remote-api (1.0.0)\ntest-repo/remote-lib (0.1.0, 0.2.0)\n",
+                "{registry}"
+            );
+
+            assert_eq!(
+                search(&["lib", "--repository", "test-repo"]),
+                "test-repo/remote-lib (0.1.0, 0.2.0)\ntest-repo/unrelated-lib (0.1.0)\n",
+                "{registry}"
+            );
+
+            assert!(
+                search(&["missing"]).contains("no packages matching missing found"),
+                "{registry}"
+            );
+
+            let json: serde_json::Value =
+                serde_json::from_str(&search(&["api", "--json"])).unwrap();
+
+            assert_eq!(
+                json,
+                serde_json::json!([{
+                    "repository": "other-repo",
+                    "name": "remote-api",
+                    "versions": ["1.0.0"],
+                }]),
+                "{registry}"
+            );
+        }
+
+        // Static index registries cannot enumerate their packages
+        let index = url.replacen("http://", "index+http://", 1);
+
+        let output = crate::cli!()
+            .args(["search", "--registry", &index, "remote"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(
+            stderr.contains("not supported by this registry"),
+            "{stderr}"
+        );
+    });
+}
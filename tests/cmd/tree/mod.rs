@@ -0,0 +1,2 @@
+mod package;
+mod workspace;
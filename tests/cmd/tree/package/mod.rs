@@ -0,0 +1,53 @@
+use crate::VirtualFileSystem;
+
+#[test]
+fn fixture() {
+    let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+
+    crate::cli!()
+        .arg("tree")
+        .current_dir(vfs.root())
+        .assert()
+        .success()
+        .stdout(include_str!("stdout.log"))
+        .stderr(include_str!("stderr.log"));
+
+    crate::cli!()
+        .args(["tree", "--invert", "lib-c"])
+        .current_dir(vfs.root())
+        .assert()
+        .success()
+        .stdout(include_str!("inverted.log"));
+
+    crate::cli!()
+        .args(["tree", "--depth", "1"])
+        .current_dir(vfs.root())
+        .assert()
+        .success()
+        .stdout(include_str!("depth.log"));
+
+    for (format, expected) in [
+        ("dot", include_str!("dot.log")),
+        ("mermaid", include_str!("mermaid.log")),
+        ("json", include_str!("json.log")),
+    ] {
+        crate::cli!()
+            .args(["tree", "--format", format])
+            .current_dir(vfs.root())
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+
+    crate::cli!()
+        .args(["tree", "--format", "json", "--depth", "1"])
+        .current_dir(vfs.root())
+        .assert()
+        .failure();
+
+    crate::cli!()
+        .args(["tree", "--invert", "lib-d"])
+        .current_dir(vfs.root())
+        .assert()
+        .failure();
+}
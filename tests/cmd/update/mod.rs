@@ -0,0 +1,3 @@
+mod package;
+mod transitive;
+mod workspace;
@@ -0,0 +1,142 @@
+use std::path::Path;
+
+use crate::{VirtualFileSystem, with_test_registry};
+
+const PROTO: &str =
+    "syntax = \"proto3\";\n\npackage lib;\n\nmessage Data {\n  string value = 1;\n}\n";
+
+/// Returns the locked version of a package
+fn locked_version(cwd: &Path, name: &str) -> String {
+    let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+
+    lockfile
+        .split("[[packages]]")
+        .find(|s| s.contains(&format!("name = \"{name}\"")))
+        .and_then(|s| s.lines().find(|line| line.starts_with("version = ")))
+        .unwrap()
+        .trim()
+        .to_owned()
+}
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        for (version, name) in [("1.0.0", "lib-a"), ("1.0.0", "lib-b")] {
+            crate::publish_test_library(
+                &cwd.join("libs-1.0.0"),
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                Some(version),
+                "lib.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        for dependency in ["test-repo/lib-a@^1.0", "test-repo/lib-b@^1.0"] {
+            crate::cli!()
+                .args(["add", "--registry", url, dependency])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(&cwd)
+                .assert()
+                .success();
+        }
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        // Publish newer versions that are allowed by the manifest
+        for (version, name) in [("1.1.0", "lib-a"), ("1.1.0", "lib-b")] {
+            crate::publish_test_library(
+                &cwd.join("libs-1.1.0"),
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                Some(version),
+                "lib.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        // A dry run prints the summary without touching the lockfile or the vendored packages
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        let vendored = std::fs::read_to_string(cwd.join("proto/vendor/lib-a/Proto.toml")).unwrap();
+
+        crate::cli!()
+            .args(["update", "--dry-run"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(
+                ":: updating lib-a 1.0.0 → 1.1.0\n\
+                 :: updating lib-b 1.0.0 → 1.1.0\n\
+                 :: dry run: Proto.lock was not modified\n",
+            );
+
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("Proto.lock")).unwrap(),
+            lockfile
+        );
+        assert_eq!(
+            std::fs::read_to_string(cwd.join("proto/vendor/lib-a/Proto.toml")).unwrap(),
+            vendored
+        );
+
+        // Only the given packages are updated
+        let output = crate::cli!()
+            .args(["update", "lib-a"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(stdout.contains(":: updating lib-a 1.0.0 → 1.1.0\n"));
+        assert!(!stdout.contains("updating lib-b"));
+
+        assert_eq!(locked_version(&cwd, "lib-a"), "version = \"1.1.0\"");
+        assert_eq!(locked_version(&cwd, "lib-b"), "version = \"1.0.0\"");
+
+        // Without arguments, all packages are updated
+        crate::cli!()
+            .arg("update")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        assert_eq!(locked_version(&cwd, "lib-b"), "version = \"1.1.0\"");
+
+        crate::cli!()
+            .args(["update", "--dry-run"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success()
+            .stdout(
+                ":: all locked packages are up to date\n\
+                 :: dry run: Proto.lock was not modified\n",
+            );
+
+        // Packages that are not locked are rejected
+        crate::cli!()
+            .args(["update", "lib-c"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .failure();
+    });
+}
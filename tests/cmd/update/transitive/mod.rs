@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use crate::{PROTO, VirtualFileSystem, with_test_registry};
+
+/// Publishes `remote-lib` at the given version with the given dependencies
+fn publish_remote_lib(
+    cwd: &Path,
+    buffrs_home: &Path,
+    url: &str,
+    version: &str,
+    dependencies: &[&str],
+) {
+    let lib = cwd.join(format!("remote-lib-{version}"));
+    std::fs::create_dir_all(lib.join("proto")).unwrap();
+
+    crate::cli!()
+        .args(["init", "--lib", "remote-lib"])
+        .env("BUFFRS_HOME", buffrs_home)
+        .current_dir(&lib)
+        .assert()
+        .success();
+
+    let manifest = std::fs::read_to_string(lib.join("Proto.toml")).unwrap();
+    std::fs::write(
+        lib.join("Proto.toml"),
+        manifest.replace("version = \"0.1.0\"", &format!("version = \"{version}\"")),
+    )
+    .unwrap();
+    std::fs::write(lib.join("proto/remote.proto"), PROTO).unwrap();
+
+    for dependency in dependencies {
+        crate::cli!()
+            .args(["add", "--registry", url, dependency])
+            .env("BUFFRS_HOME", buffrs_home)
+            .current_dir(&lib)
+            .assert()
+            .success();
+    }
+
+    crate::cli!()
+        .args(["publish", "--registry", url, "--repository", "test-repo"])
+        .env("BUFFRS_HOME", buffrs_home)
+        .current_dir(&lib)
+        .assert()
+        .success();
+}
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::empty();
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        for name in ["lib-base", "lib-extra"] {
+            crate::publish_test_library(
+                &cwd.join("libs-1.0.0"),
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                Some("1.0.0"),
+                "base.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        publish_remote_lib(
+            &cwd,
+            &buffrs_home,
+            url,
+            "1.0.0",
+            &["test-repo/lib-base@^1.0", "test-repo/lib-extra@^1.0"],
+        );
+
+        let app = cwd.join("app");
+        std::fs::create_dir_all(app.join("proto")).unwrap();
+
+        crate::cli!()
+            .args(["init", "--api", "app"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&app)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .args(["add", "--registry", url, "test-repo/remote-lib@^1.0"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&app)
+            .assert()
+            .success();
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&app)
+            .assert()
+            .success();
+
+        // The new version of remote-lib requires a version of lib-base which is not locked, while
+        // the locked version of lib-extra remains compatible
+        for name in ["lib-base", "lib-extra"] {
+            crate::publish_test_library(
+                &cwd.join("libs-1.1.0"),
+                &buffrs_home,
+                url,
+                "test-repo",
+                name,
+                Some("1.1.0"),
+                "base.proto",
+                PROTO,
+                &[],
+            );
+        }
+
+        publish_remote_lib(
+            &cwd,
+            &buffrs_home,
+            url,
+            "1.1.0",
+            &["test-repo/lib-base@^1.1", "test-repo/lib-extra@^1.0"],
+        );
+
+        // Dependencies only required by the updated package are updated along with it
+        let output = crate::cli!()
+            .args(["update", "remote-lib"])
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&app)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        for name in ["lib-base", "lib-extra", "remote-lib"] {
+            assert!(
+                stdout.contains(&format!(":: updating {name} 1.0.0 → 1.1.0\n")),
+                "unexpected output:\n{stdout}"
+            );
+        }
+
+        let lockfile = std::fs::read_to_string(app.join("Proto.lock")).unwrap();
+        assert!(
+            !lockfile.contains("version = \"1.0.0\""),
+            "unexpected lockfile:\n{lockfile}"
+        );
+    });
+}
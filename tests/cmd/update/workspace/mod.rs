@@ -0,0 +1,68 @@
+use crate::{PROTO, VirtualFileSystem, with_test_registry};
+
+#[test]
+fn fixture() {
+    with_test_registry(|url| {
+        let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+        let buffrs_home = vfs.root().join("$HOME");
+        let cwd = vfs.root();
+
+        crate::publish_test_library(
+            &cwd.join("libs-0.1.0"),
+            &buffrs_home,
+            url,
+            "test-repo",
+            "remote-lib",
+            None,
+            "remote.proto",
+            PROTO,
+            &[],
+        );
+
+        for member in ["pkg1", "pkg2"] {
+            crate::cli!()
+                .args(["add", "--registry", url, "test-repo/remote-lib@^0.1"])
+                .env("BUFFRS_HOME", &buffrs_home)
+                .current_dir(cwd.join(member))
+                .assert()
+                .success();
+        }
+
+        crate::cli!()
+            .arg("install")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        crate::publish_test_library(
+            &cwd.join("libs-0.1.1"),
+            &buffrs_home,
+            url,
+            "test-repo",
+            "remote-lib",
+            Some("0.1.1"),
+            "remote.proto",
+            PROTO,
+            &[],
+        );
+
+        let output = crate::cli!()
+            .arg("update")
+            .env("BUFFRS_HOME", &buffrs_home)
+            .current_dir(&cwd)
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(
+            stdout.contains(":: updating remote-lib 0.1.0 → 0.1.1\n"),
+            "expected update summary, got:\n{stdout}"
+        );
+
+        let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+        assert!(lockfile.contains("version = \"0.1.1\""));
+        assert!(!lockfile.contains("version = \"0.1.0\""));
+        assert!(lockfile.contains("dependants = 2"));
+    });
+}
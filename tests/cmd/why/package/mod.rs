@@ -0,0 +1,20 @@
+use crate::VirtualFileSystem;
+
+#[test]
+fn fixture() {
+    let vfs = VirtualFileSystem::copy(crate::parent_directory!().join("in"));
+
+    crate::cli!()
+        .args(["why", "lib-c"])
+        .current_dir(vfs.root())
+        .assert()
+        .success()
+        .stdout(include_str!("stdout.log"))
+        .stderr("");
+
+    crate::cli!()
+        .args(["why", "lib-d"])
+        .current_dir(vfs.root())
+        .assert()
+        .failure();
+}
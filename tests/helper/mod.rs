@@ -1,6 +1,8 @@
 use std::path::Path;
 
 /// Helper to create, initialize, write a proto file, and publish a library package.
+///
+/// `env` holds additional environment variables for every buffrs invocation.
 #[allow(clippy::too_many_arguments)]
 pub fn publish_test_library(
     cwd: &Path,
@@ -11,6 +13,7 @@ pub fn publish_test_library(
     version: Option<&str>,
     proto_filename: &str,
     proto_content: &str,
+    env: &[(&str, &str)],
 ) {
     let lib_dir = cwd.join(name);
     std::fs::create_dir_all(&lib_dir).unwrap();
@@ -18,6 +21,7 @@ pub fn publish_test_library(
     crate::cli!()
         .args(["init", "--lib", name])
         .env("BUFFRS_HOME", buffrs_home)
+        .envs(env.iter().copied())
         .current_dir(&lib_dir)
         .assert()
         .success();
@@ -44,7 +48,65 @@ pub fn publish_test_library(
             repository,
         ])
         .env("BUFFRS_HOME", buffrs_home)
+        .envs(env.iter().copied())
         .current_dir(&lib_dir)
         .assert()
         .success();
 }
+
+/// Publishes `test-repo/remote-lib` 0.1.0 and 0.2.0 to a registry, then adds the latest version
+/// to the manifest in `cwd` and installs it without a populated cache
+///
+/// This is the flow every registry backend has to support, fixtures add the assertions specific
+/// to their backend. `env` holds additional environment variables for every buffrs invocation.
+pub fn install_latest_test_library(
+    cwd: &Path,
+    buffrs_home: &Path,
+    registry: &str,
+    env: &[(&str, &str)],
+) {
+    for version in ["0.1.0", "0.2.0"] {
+        crate::publish_test_library(
+            &cwd.join(format!("libs-{version}")),
+            buffrs_home,
+            registry,
+            "test-repo",
+            "remote-lib",
+            Some(version),
+            "remote.proto",
+            crate::PROTO,
+            env,
+        );
+    }
+
+    crate::cli!()
+        .args(["add", "--registry", registry, "test-repo/remote-lib@latest"])
+        .env("BUFFRS_HOME", buffrs_home)
+        .envs(env.iter().copied())
+        .current_dir(cwd)
+        .assert()
+        .success();
+
+    let manifest = std::fs::read_to_string(cwd.join("Proto.toml")).unwrap();
+    assert!(
+        manifest.contains("0.2.0"),
+        "unexpected manifest:\n{manifest}"
+    );
+
+    crate::cli!()
+        .arg("install")
+        .env("BUFFRS_HOME", buffrs_home)
+        .envs(env.iter().copied())
+        .env("BUFFRS_CACHE", cwd.join("empty-cache"))
+        .current_dir(cwd)
+        .assert()
+        .success();
+
+    assert!(cwd.join("proto/vendor/remote-lib/remote.proto").exists());
+
+    let lockfile = std::fs::read_to_string(cwd.join("Proto.lock")).unwrap();
+    assert!(
+        lockfile.contains(&format!("registry = \"{registry}\"")),
+        "unexpected lockfile:\n{lockfile}"
+    );
+}
@@ -16,6 +16,10 @@ mod resolver;
 pub use helper::*;
 pub use registry::*;
 
+/// Contents of the protocol buffer file published by test libraries
+pub const PROTO: &str =
+    "syntax = \"proto3\";\n\npackage remote;\n\nmessage Data {\n  string value = 1;\n}\n";
+
 /// Create a command which runs the cli
 #[macro_export]
 macro_rules! cli {
@@ -1,14 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::net::SocketAddr;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use axum::{
     Router, extract,
-    http::{StatusCode, header},
-    response::IntoResponse,
-    routing::get,
+    http::{Method, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{any, get},
 };
+use buffrs::lock::DigestAlgorithm;
 use bytes::Bytes;
 use miette::{Context as _, IntoDiagnostic, miette};
 use tokio::net::TcpListener;
@@ -21,6 +22,8 @@ struct RegistryState {
     packages: State,
     /// When set, GET requests must include a matching Bearer token
     required_token: Option<String>,
+    /// Number of requests received for every path below `/flaky`
+    attempts: Arc<RwLock<HashMap<String, usize>>>,
 }
 
 /// Run a minimal registry for local testing
@@ -31,8 +34,12 @@ async fn test_registry(
     let state = RegistryState {
         packages: Arc::new(RwLock::new(HashMap::<String, Bytes>::new())),
         required_token,
+        attempts: Default::default(),
     };
     let app = Router::new()
+        .route("/artifactory/api/search/artifact", get(search_artifacts))
+        .route("/v2/{*path}", any(oci_api))
+        .route("/flaky/{*path}", get(get_flaky_package))
         .route("/{*path}", get(get_package).put(put_package))
         .with_state(state);
     axum::serve(listener, app)
@@ -46,7 +53,8 @@ async fn get_package(
     extract::State(state): extract::State<RegistryState>,
     headers: axum::http::HeaderMap,
     extract::Path(path): extract::Path<String>,
-) -> Result<impl IntoResponse, StatusCode> {
+    extract::RawQuery(query): extract::RawQuery,
+) -> Result<Response, StatusCode> {
     // Check authentication if required
     if let Some(ref expected_token) = state.required_token {
         let authorized = headers
@@ -60,6 +68,18 @@ async fn get_package(
         }
     }
 
+    let params: HashMap<String, String> =
+        url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+
+    if params
+        .get("list-type")
+        .is_some_and(|list_type| list_type == "2")
+    {
+        return Ok(list_objects(&state, &path, &params));
+    }
+
     tracing::info!("Downloaded package from {path}");
     let content = state
         .packages
@@ -68,7 +88,151 @@ async fn get_package(
         .get(&path)
         .cloned()
         .ok_or(StatusCode::NOT_FOUND)?;
-    Ok(([(header::CONTENT_TYPE, "application/x-gzip")], content))
+    Ok(([(header::CONTENT_TYPE, "application/x-gzip")], content).into_response())
+}
+
+/// Serves packages like `get_package`, but fails the first three requests for every path
+async fn get_flaky_package(
+    extract::State(state): extract::State<RegistryState>,
+    extract::Path(path): extract::Path<String>,
+) -> Response {
+    let attempt = {
+        let mut attempts = state.attempts.write().unwrap();
+        let attempt = attempts.entry(path.clone()).or_default();
+        *attempt += 1;
+        *attempt
+    };
+
+    match attempt {
+        1 => StatusCode::BAD_GATEWAY.into_response(),
+        2 => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, "0")],
+        )
+            .into_response(),
+        3 => (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "0")]).into_response(),
+        _ => match state.packages.read().unwrap().get(&path).cloned() {
+            Some(content) => {
+                ([(header::CONTENT_TYPE, "application/x-gzip")], content).into_response()
+            }
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+    }
+}
+
+/// Mimics the S3 ListObjectsV2 API, treating the first path segment as the bucket
+fn list_objects(state: &RegistryState, bucket: &str, params: &HashMap<String, String>) -> Response {
+    let prefix = format!(
+        "{bucket}/{}",
+        params.get("prefix").cloned().unwrap_or_default()
+    );
+
+    let contents: String = state
+        .packages
+        .read()
+        .unwrap()
+        .keys()
+        .filter(|path| path.starts_with(&prefix))
+        .map(|path| {
+            format!(
+                "<Contents><Key>{}</Key></Contents>",
+                &path[bucket.len() + 1..]
+            )
+        })
+        .collect();
+
+    tracing::info!("Listed objects of {prefix}");
+
+    (
+        [(header::CONTENT_TYPE, "application/xml")],
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <ListBucketResult><Name>{bucket}</Name><IsTruncated>false</IsTruncated>{contents}</ListBucketResult>"
+        ),
+    )
+        .into_response()
+}
+
+/// Mimics the artifactory artifact search API used to list published versions
+async fn search_artifacts(
+    extract::State(state): extract::State<RegistryState>,
+    extract::RawQuery(query): extract::RawQuery,
+) -> impl IntoResponse {
+    let params: HashMap<String, String> =
+        url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+
+    let name = params.get("name").cloned().unwrap_or_default();
+    let repository = params.get("repos").cloned();
+
+    // Names without wildcards match the artifacts of a package, others are matched as a pattern
+    let matches = |path: &str| {
+        let mut segments = path.split('/');
+
+        let (Some("registry"), Some(repo), Some(package), Some(artifact), None) = (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) else {
+            return false;
+        };
+
+        if repository
+            .as_deref()
+            .is_some_and(|repository| repository != repo)
+        {
+            return false;
+        }
+
+        match name.contains('*') {
+            true => matches_pattern(&name, artifact),
+            false => {
+                package == name
+                    && artifact.starts_with(&format!("{name}-"))
+                    && artifact.ends_with(".tgz")
+            }
+        }
+    };
+
+    let results: Vec<_> = state
+        .packages
+        .read()
+        .unwrap()
+        .keys()
+        .filter(|path| matches(path))
+        .map(|path| serde_json::json!({ "uri": format!("/{path}") }))
+        .collect();
+
+    tracing::info!("Found {} artifacts for {name}", results.len());
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            "application/vnd.org.jfrog.artifactory.search.ArtifactSearchResult+json",
+        )],
+        serde_json::json!({ "results": results }).to_string(),
+    )
+}
+
+/// Matches a value against a pattern with `*` wildcards
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+
+    let Some(mut rest) = value.strip_prefix(parts[0]) else {
+        return false;
+    };
+
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(parts[parts.len() - 1])
 }
 
 async fn put_package(
@@ -80,6 +244,104 @@ async fn put_package(
     state.packages.write().unwrap().insert(path, body);
 }
 
+/// Mimics the subset of the OCI distribution API used to push and pull artifacts
+async fn oci_api(
+    extract::State(state): extract::State<RegistryState>,
+    method: Method,
+    extract::Path(path): extract::Path<String>,
+    extract::RawQuery(query): extract::RawQuery,
+    body: Bytes,
+) -> Response {
+    let mut packages = state.packages.write().unwrap();
+
+    if let Some(name) = path.strip_suffix("/blobs/uploads/") {
+        let location = format!("/v2/{name}/blobs/uploads/{}", packages.len());
+        return (StatusCode::ACCEPTED, [(header::LOCATION, location)]).into_response();
+    }
+
+    if let Some((name, _)) = path.split_once("/blobs/uploads/") {
+        let params: HashMap<String, String> =
+            url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+                .into_owned()
+                .collect();
+
+        let Some(digest) = params.get("digest") else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+
+        tracing::info!("Uploaded blob {digest} to {name} ({} bytes)", body.len());
+        packages.insert(format!("oci/{name}/blobs/{digest}"), body);
+        return StatusCode::CREATED.into_response();
+    }
+
+    if path == "_catalog" {
+        let repositories: BTreeSet<_> = packages
+            .keys()
+            .filter_map(|key| key.strip_prefix("oci/"))
+            .filter_map(|key| key.split_once("/manifests/"))
+            .map(|(name, _)| name)
+            .collect();
+
+        return serde_json::json!({ "repositories": repositories })
+            .to_string()
+            .into_response();
+    }
+
+    if let Some(name) = path.strip_suffix("/tags/list") {
+        let prefix = format!("oci/{name}/manifests/");
+        let tags: Vec<_> = packages
+            .keys()
+            .filter_map(|key| key.strip_prefix(&prefix))
+            .filter(|tag| !tag.starts_with("sha256:"))
+            .collect();
+
+        if tags.is_empty() {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+
+        return serde_json::json!({ "name": name, "tags": tags })
+            .to_string()
+            .into_response();
+    }
+
+    if let Some((name, reference)) = path.rsplit_once("/manifests/") {
+        let key = format!("oci/{name}/manifests/{reference}");
+
+        if method == Method::PUT {
+            // Manifests can be pulled by tag or by the digest of their contents
+            let digest = DigestAlgorithm::SHA256.digest(&body);
+
+            tracing::info!("Tagged manifest {name}:{reference} ({digest})");
+            packages.insert(format!("oci/{name}/manifests/{digest}"), body.clone());
+            packages.insert(key, body);
+            return StatusCode::CREATED.into_response();
+        }
+
+        return match packages.get(&key) {
+            Some(manifest) => (
+                [
+                    (
+                        header::CONTENT_TYPE,
+                        "application/vnd.oci.image.manifest.v1+json".to_owned(),
+                    ),
+                    (
+                        header::HeaderName::from_static("docker-content-digest"),
+                        DigestAlgorithm::SHA256.digest(manifest).to_string(),
+                    ),
+                ],
+                manifest.clone(),
+            )
+                .into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        };
+    }
+
+    match packages.get(&format!("oci/{path}")) {
+        Some(blob) => blob.clone().into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
 async fn wait_for_registry(url: &str, handle: &tokio::task::JoinHandle<miette::Result<()>>) {
     let dur = Duration::from_millis(10);
     let client = reqwest::Client::builder()
@@ -9,7 +9,7 @@ use buffrs::{
     manifest::{Dependency, LocalDependencyManifest, PackageManifest, PackagesManifest},
     operations::install::NetworkMode,
     package::{PackageName, PackageType},
-    resolver::{DependencyGraph, DependencyNode, DependencySource},
+    resolver::{DependencyError, DependencyGraph, DependencyNode, DependencySource},
 };
 use semver::{Version, VersionReq};
 use tempfile::TempDir;
@@ -466,6 +466,31 @@ async fn test_circular_dependency_indirect() {
         "Error should mention circular dependency, got: {}",
         err_msg
     );
+    assert!(
+        err_msg.contains("pkg1 -> pkg2 -> pkg3 -> pkg1"),
+        "Error should contain the cycle, got: {}",
+        err_msg
+    );
+
+    // Every edge of the cycle points at its declaration in the dependant's manifest
+    let Some(DependencyError::CircularDependency { edges, .. }) = err.downcast_ref() else {
+        panic!("error should be CircularDependency");
+    };
+
+    assert_eq!(edges.len(), 3);
+
+    for edge in edges {
+        let manifest = edge.manifest.as_ref().expect("local manifest");
+        let span = edge.span.expect("label on the dependency declaration");
+        let declaration = &manifest.inner()[span.offset()..span.offset() + span.len()];
+
+        assert!(
+            declaration == format!("[dependencies.{}]", edge.dependency),
+            "label should point at the declaration of {}, got: {}",
+            edge.dependency,
+            declaration
+        );
+    }
 }
 
 #[tokio::test]
@@ -823,7 +848,10 @@ fn build_test_graph(nodes: Vec<(PackageName, Vec<PackageName>)>) -> DependencyGr
                     path: PathBuf::from("/tmp"),
                 },
                 dependencies,
+                declarations: vec![],
                 version: VersionReq::STAR,
+                resolved_version: None,
+                manifest_source: None,
             },
         );
     }
@@ -1101,7 +1129,10 @@ fn test_topo_sort_detects_cycle() {
                 path: PathBuf::from("/tmp"),
             },
             dependencies: vec!["b".parse().expect("valid package name")],
+            declarations: vec![],
             version: VersionReq::STAR,
+            resolved_version: None,
+            manifest_source: None,
         },
     );
 
@@ -1114,7 +1145,10 @@ fn test_topo_sort_detects_cycle() {
                 path: PathBuf::from("/tmp"),
             },
             dependencies: vec!["a".parse().expect("valid package name")],
+            declarations: vec![],
             version: VersionReq::STAR,
+            resolved_version: None,
+            manifest_source: None,
         },
     );
 
@@ -1130,10 +1164,11 @@ fn test_topo_sort_detects_cycle() {
     assert!(
         matches!(
             err,
-            buffrs::resolver::DependencyError::CircularDependency(_)
+            buffrs::resolver::DependencyError::CircularDependency { .. }
         ),
         "error should be CircularDependency"
     );
+    assert_eq!(err.to_string(), "circular dependency detected: a -> b -> a");
 }
 
 #[test]